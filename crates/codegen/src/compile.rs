@@ -0,0 +1,690 @@
+use std::collections::HashMap;
+
+use hir::{Analysis, ArenaMap, BinaryOp, ConstValue, Def, DefId, Name, Ty, UnaryOp};
+use mir::{Block, Function, Operand, PassManager, Rvalue, Stmt, Var, VarId};
+use num_bigint::BigInt;
+use num_traits::One;
+
+use crate::{
+    compose, estimate_cost, list_item, pretty, serialize, to_hex, CompileError, CompileErrorKind,
+    CostEstimate, Node, Op,
+};
+
+/// A compiled program, which takes the arguments to `main` as a list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    pub node: Node,
+    /// Where the code for each function which can be reached from `main` is, in the order they
+    /// were defined.
+    pub fns: Vec<FnLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FnLocation {
+    pub def: DefId,
+    pub name: Option<Name>,
+    /// The path to the function's code in the program.
+    pub path: BigInt,
+}
+
+impl Program {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serialize(&self.node)
+    }
+
+    pub fn to_hex(&self) -> String {
+        to_hex(&self.to_bytes())
+    }
+
+    /// Prints the program as Chialisp, optionally with a comment naming each function.
+    pub fn to_chialisp(&self, comments: bool) -> String {
+        let comments = if comments {
+            self.fns
+                .iter()
+                .map(|location| {
+                    let name = location.name.as_ref().map_or("<missing>", Name::as_str);
+                    (location.path.clone(), format!("def {name}"))
+                })
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        pretty(&self.node, &comments)
+    }
+
+    /// The most the program and each function can cost to run, assuming that no atom is longer
+    /// than `max_atom_bytes`.
+    pub fn estimate_cost(&self, max_atom_bytes: usize) -> CostEstimate {
+        estimate_cost(self, max_atom_bytes)
+    }
+}
+
+/// Compiles a module to a CLVM program which calls `main`.
+///
+/// The module is lowered to the IR and optimized first, which also removes the functions that
+/// can't be reached from `main`. The rest are put in a balanced tree, which is quoted in the
+/// program. Each function runs with an environment of `(functions . arguments)`, so calls can
+/// pass the tree along and parameters are found by their position in the arguments. Each
+/// statement evaluates its value and runs the rest of its block with `(value . environment)`,
+/// unless the value is only used once in the same block, in which case it's computed where it's
+/// used instead.
+///
+/// Integers and strings are atoms, `true` is 1, and `false` and `()` are nil.
+///
+/// Shifts check their operands first, so that they fail in the same cases as in the interpreter.
+pub fn compile(analysis: &Analysis) -> Result<Program, CompileError> {
+    let mut module = mir::lower(analysis);
+
+    if let Some(error) = module.errors.first() {
+        return Err(CompileError {
+            kind: CompileErrorKind::InvalidProgram,
+            span: error.span,
+        });
+    }
+
+    PassManager::optimize().run(&mut module);
+
+    let main = module
+        .fns
+        .iter()
+        .find(|function| {
+            function
+                .name
+                .as_ref()
+                .is_some_and(|name| name.as_str() == "main")
+        })
+        .ok_or(CompileError {
+            kind: CompileErrorKind::NoMain,
+            span: None,
+        })?;
+
+    let defs = module
+        .fns
+        .iter()
+        .map(|function| function.def)
+        .collect::<Vec<_>>();
+
+    let mut fn_paths = ArenaMap::new();
+    layout(&defs, BigInt::one(), &mut fn_paths);
+
+    let code = module
+        .fns
+        .iter()
+        .map(|function| FnCompiler::new(function, &fn_paths).compile())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // (a (q . (a MAIN 1)) (c (q . FUNCTIONS) 1))
+    let main_path = compose(&BigInt::from(2), fn_paths.get(main.def).unwrap());
+    let call_main = Node::op(
+        Op::Apply,
+        [Node::int(&main_path), Node::int(&BigInt::one())],
+    );
+    let env = Node::op(
+        Op::Cons,
+        [Node::quote(tree(&code)), Node::int(&BigInt::one())],
+    );
+
+    let tree_path = tree_path();
+
+    let fns = module
+        .fns
+        .iter()
+        .map(|function| FnLocation {
+            def: function.def,
+            name: function.name.clone(),
+            path: compose(&tree_path, fn_paths.get(function.def).unwrap()),
+        })
+        .collect();
+
+    Ok(Program {
+        node: Node::op(Op::Apply, [Node::quote(call_main), env]),
+        fns,
+    })
+}
+
+/// The path to the function tree in the program.
+pub(crate) fn tree_path() -> BigInt {
+    // The tree is the rest of `(q . FUNCTIONS)`, in the second argument of `c`,
+    // in the second argument of `a`.
+    compose(&compose(&list_item(2), &list_item(1)), &BigInt::from(3))
+}
+
+/// Assigns each function a path in the tree, which is built the same way by [`tree`].
+fn layout(fns: &[DefId], path: BigInt, paths: &mut ArenaMap<Def, BigInt>) {
+    match fns {
+        [] => {}
+        [def] => paths.insert(*def, path),
+        _ => {
+            let (left, right) = fns.split_at(fns.len() / 2);
+            layout(left, compose(&path, &BigInt::from(2)), paths);
+            layout(right, compose(&path, &BigInt::from(3)), paths);
+        }
+    }
+}
+
+fn tree(code: &[Node]) -> Node {
+    match code {
+        [] => Node::nil(),
+        [node] => node.clone(),
+        _ => {
+            let (left, right) = code.split_at(code.len() / 2);
+            Node::pair(tree(left), tree(right))
+        }
+    }
+}
+
+/// Where the function tree and each variable can be found in the current environment.
+#[derive(Clone)]
+struct Env {
+    fns: BigInt,
+    vars: ArenaMap<Var, BigInt>,
+}
+
+impl Env {
+    /// Moves everything into the rest of the environment, to make room for a new value first.
+    fn push(&mut self, var: VarId) {
+        let rest = BigInt::from(3);
+
+        self.fns = compose(&rest, &self.fns);
+
+        let mut vars = ArenaMap::new();
+        for (id, path) in self.vars.iter() {
+            vars.insert(id, compose(&rest, path));
+        }
+        self.vars = vars;
+
+        self.vars.insert(var, BigInt::from(2));
+    }
+}
+
+struct FnCompiler<'a> {
+    function: &'a Function,
+    fn_paths: &'a ArenaMap<Def, BigInt>,
+    /// How many times each variable is used, including in nested blocks.
+    uses: HashMap<VarId, usize>,
+    /// Statements whose values are computed where they're used, and haven't been yet.
+    pending: HashMap<VarId, &'a Stmt>,
+    env: Env,
+}
+
+impl<'a> FnCompiler<'a> {
+    fn new(function: &'a Function, fn_paths: &'a ArenaMap<Def, BigInt>) -> Self {
+        let mut uses = HashMap::new();
+        function.body.walk_operands(&mut |operand| {
+            if let Operand::Var(var) = operand {
+                *uses.entry(*var).or_default() += 1;
+            }
+        });
+
+        let mut vars = ArenaMap::new();
+        for (i, &param) in function.params.iter().enumerate() {
+            vars.insert(param, compose(&BigInt::from(3), &list_item(i)));
+        }
+
+        Self {
+            function,
+            fn_paths,
+            uses,
+            pending: HashMap::new(),
+            env: Env {
+                fns: BigInt::from(2),
+                vars,
+            },
+        }
+    }
+
+    fn compile(mut self) -> Result<Node, CompileError> {
+        self.block(&self.function.body, 0)
+    }
+
+    /// Compiles the statements of a block from an index onwards, followed by its result.
+    fn block(&mut self, block: &'a Block, index: usize) -> Result<Node, CompileError> {
+        let Some(stmt) = block.stmts.get(index) else {
+            return self.operand(&block.result);
+        };
+
+        // Moving a value into a nested block could skip it, so only the uses in this block count.
+        if self.uses.get(&stmt.var) == Some(&1) && uses_in(block, stmt.var) == 1 {
+            self.pending.insert(stmt.var, stmt);
+            return self.block(block, index + 1);
+        }
+
+        // The values which aren't used are kept too, so they still fail if they would have at
+        // runtime.
+        let value = self.rvalue(stmt)?;
+
+        let outer = self.env.clone();
+        self.env.push(stmt.var);
+        let rest = self.block(block, index + 1);
+        self.env = outer;
+
+        // (a (q . REST) (c VALUE 1))
+        Ok(Node::op(
+            Op::Apply,
+            [
+                Node::quote(rest?),
+                Node::op(Op::Cons, [value, Node::int(&BigInt::one())]),
+            ],
+        ))
+    }
+
+    fn rvalue(&mut self, stmt: &'a Stmt) -> Result<Node, CompileError> {
+        match &stmt.value {
+            Rvalue::Use(operand) => self.operand(operand),
+            Rvalue::Binary { op, lhs, rhs } => {
+                let lhs = self.operand(lhs)?;
+                let rhs = self.operand(rhs)?;
+                binary(*op, &self.function.vars[stmt.var].ty, lhs, rhs).ok_or_else(invalid)
+            }
+            Rvalue::Unary { op, operand } => {
+                let value = self.operand(operand)?;
+
+                Ok(match op {
+                    UnaryOp::Neg => Node::op(Op::Sub, [Node::nil(), value]),
+                    UnaryOp::Not => Node::op(Op::Not, [value]),
+                    UnaryOp::BitNot => Node::op(Op::Lognot, [value]),
+                })
+            }
+            Rvalue::Call { callee, args } => {
+                let callee = self.operand(callee)?;
+
+                let mut arg_list = Node::nil();
+                for arg in args.iter().rev() {
+                    arg_list = Node::op(Op::Cons, [self.operand(arg)?, arg_list]);
+                }
+
+                // (a CALLEE (c FUNCTIONS ARGS))
+                let env = Node::op(Op::Cons, [Node::int(&self.env.fns), arg_list]);
+                Ok(Node::op(Op::Apply, [callee, env]))
+            }
+            // The blocks are quoted, and only the one `i` picks is run.
+            // (a (i CONDITION (q . THEN) (q . ELSE)) 1)
+            Rvalue::If {
+                condition,
+                then_block,
+                else_block,
+            } => {
+                let condition = self.operand(condition)?;
+                let then_block = self.block(then_block, 0)?;
+                let else_block = self.block(else_block, 0)?;
+
+                Ok(lazy(Node::op(
+                    Op::If,
+                    [condition, branch(then_block), branch(else_block)],
+                )))
+            }
+        }
+    }
+
+    fn operand(&mut self, operand: &Operand) -> Result<Node, CompileError> {
+        match operand {
+            Operand::Var(var) => match self.pending.remove(var) {
+                Some(stmt) => self.rvalue(stmt),
+                None => self.env.vars.get(*var).map(Node::int).ok_or_else(invalid),
+            },
+            Operand::Const(value) => Ok(quote_value(value)),
+            Operand::Fn(def) => self
+                .fn_paths
+                .get(*def)
+                .map(|path| Node::int(&compose(&self.env.fns, path)))
+                .ok_or_else(invalid),
+            Operand::Unit => Ok(Node::nil()),
+        }
+    }
+}
+
+/// How many times a variable is used in a block, not counting its nested blocks.
+fn uses_in(block: &Block, var: VarId) -> usize {
+    let operands = block.stmts.iter().flat_map(|stmt| match &stmt.value {
+        Rvalue::Use(operand) | Rvalue::Unary { operand, .. } => vec![operand],
+        Rvalue::Binary { lhs, rhs, .. } => vec![lhs, rhs],
+        Rvalue::Call { callee, args } => std::iter::once(callee).chain(args).collect(),
+        Rvalue::If { condition, .. } => vec![condition],
+    });
+
+    operands
+        .chain([&block.result])
+        .filter(|&operand| *operand == Operand::Var(var))
+        .count()
+}
+
+/// Anything in the IR which can't be compiled was already reported when it was lowered.
+fn invalid() -> CompileError {
+    CompileError {
+        kind: CompileErrorKind::InvalidProgram,
+        span: None,
+    }
+}
+
+/// Compiles a binary operator, or returns `None` if the result type isn't supported.
+fn binary(op: BinaryOp, ty: &Ty, lhs: Node, rhs: Node) -> Option<Node> {
+    Some(match op {
+        BinaryOp::Add => match ty {
+            Ty::Int => Node::op(Op::Add, [lhs, rhs]),
+            Ty::String | Ty::Bytes => Node::op(Op::Concat, [lhs, rhs]),
+            _ => return None,
+        },
+        BinaryOp::Sub => Node::op(Op::Sub, [lhs, rhs]),
+        BinaryOp::Mul => Node::op(Op::Mul, [lhs, rhs]),
+        BinaryOp::Div => Node::op(Op::Div, [lhs, rhs]),
+        // (r (divmod LHS RHS))
+        BinaryOp::Rem => Node::op(Op::Rest, [Node::op(Op::Divmod, [lhs, rhs])]),
+        BinaryOp::Shl | BinaryOp::Shr | BinaryOp::UnsignedShr => shift(op, lhs, rhs),
+        // Booleans are 1 and nil, so the bitwise operators work on them as well.
+        BinaryOp::BitAnd => Node::op(Op::Logand, [lhs, rhs]),
+        BinaryOp::BitOr => Node::op(Op::Logior, [lhs, rhs]),
+        BinaryOp::BitXor => Node::op(Op::Logxor, [lhs, rhs]),
+        BinaryOp::Eq => Node::op(Op::Eq, [lhs, rhs]),
+        BinaryOp::Ne => Node::op(Op::Not, [Node::op(Op::Eq, [lhs, rhs])]),
+        BinaryOp::Gt => Node::op(Op::Gt, [lhs, rhs]),
+        BinaryOp::Lt => Node::op(Op::Gt, [rhs, lhs]),
+        BinaryOp::Ge => Node::op(Op::Not, [Node::op(Op::Gt, [rhs, lhs])]),
+        BinaryOp::Le => Node::op(Op::Not, [Node::op(Op::Gt, [lhs, rhs])]),
+        // These are lowered to `if`, so the right hand side is only run when it's needed.
+        BinaryOp::LazyAnd | BinaryOp::LazyOr => return None,
+    })
+}
+
+/// A block for `i` to pick, quoted so that it only runs if it's picked. Nil runs to nil, so it
+/// doesn't need to be quoted.
+fn branch(node: Node) -> Node {
+    if node.is_nil() {
+        node
+    } else {
+        Node::quote(node)
+    }
+}
+
+/// Runs the program picked by `i` in the current environment.
+fn lazy(pick: Node) -> Node {
+    Node::op(Op::Apply, [pick, Node::int(&BigInt::one())])
+}
+
+/// Shifts a number, after checking that the amount isn't negative, and for `>>>` that the
+/// number isn't either. Otherwise, the VM would shift the other way or shift the unsigned bytes,
+/// so it raises with both operands instead, like the interpreter fails.
+///
+/// The operands are evaluated once, and passed to a new program to check and shift them.
+/// (a (q . (a (i CHECK (q . (x 2 5)) (q . (SHIFT 2 AMOUNT))) 1)) (c LHS (c RHS ())))
+fn shift(op: BinaryOp, lhs: Node, rhs: Node) -> Node {
+    let value = || Node::int(&BigInt::from(2));
+    let amount = || Node::int(&BigInt::from(5));
+    let is_negative = |node: Node| Node::op(Op::Gt, [Node::nil(), node]);
+    let negate = |node: Node| Node::op(Op::Sub, [Node::nil(), node]);
+
+    let (check, shifted) = match op {
+        BinaryOp::Shl => (
+            is_negative(amount()),
+            Node::op(Op::Ash, [value(), amount()]),
+        ),
+        BinaryOp::Shr => (
+            is_negative(amount()),
+            Node::op(Op::Ash, [value(), negate(amount())]),
+        ),
+        _ => (
+            Node::op(Op::Any, [is_negative(value()), is_negative(amount())]),
+            Node::op(Op::Lsh, [value(), negate(amount())]),
+        ),
+    };
+
+    let body = lazy(Node::op(
+        Op::If,
+        [
+            check,
+            Node::quote(Node::op(Op::Raise, [value(), amount()])),
+            Node::quote(shifted),
+        ],
+    ));
+    let operands = Node::op(Op::Cons, [lhs, Node::op(Op::Cons, [rhs, Node::nil()])]);
+
+    Node::op(Op::Apply, [Node::quote(body), operands])
+}
+
+/// A constant as a program. Nil evaluates to itself, so it doesn't need to be quoted.
+fn quote_value(value: &ConstValue) -> Node {
+    let node = match value {
+        ConstValue::Int(value) => Node::int(value),
+        ConstValue::Bool(value) => Node::bool(*value),
+        ConstValue::Bytes(bytes) => Node::atom(bytes),
+    };
+
+    if node.is_nil() {
+        node
+    } else {
+        Node::quote(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use interpreter::{Interpreter, RuntimeErrorKind, Value};
+    use parser::parse;
+
+    use super::*;
+    use crate::EvalErrorKind;
+
+    fn compile_source(source: &str) -> Program {
+        let analysis = Analysis::new(&parse(source).tree());
+        assert_eq!(analysis.diagnostics(), Vec::new());
+        compile(&analysis).unwrap()
+    }
+
+    fn run(source: &str, args: impl IntoIterator<Item = Node>) -> String {
+        match compile_source(source).run(args) {
+            Ok(value) => value.to_string(),
+            Err(error) => format!("error: {error}"),
+        }
+    }
+
+    fn int(value: i64) -> Node {
+        Node::int(&BigInt::from(value))
+    }
+
+    fn check_program(source: &str, expect: Expect) {
+        let program = compile_source(source);
+        expect.assert_eq(&format!("{}\n{}\n", program.node, program.to_hex()));
+    }
+
+    #[test]
+    fn program_layout() {
+        check_program(
+            "def main(a: Int, b: Int) -> Int {\n    a + b\n}",
+            expect![[r#"
+                (2 (1 2 2 1) (4 (1 16 5 11) 1))
+                ff02ffff01ff02ff02ff0180ffff04ffff01ff10ff05ff0b80ff018080
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lets_and_calls() {
+        let source = "def main(x: Int) -> Int {\n    let y = x * 2;\n    double(y) + 1\n}\n\
+                      def double(n: Int) -> Int {\n    n + n\n}";
+
+        check_program(
+            source,
+            expect![[r#"
+                (2 (1 2 2 1) (4 (1 2 (1 16 (16 2 2) (1 . 1)) (4 (18 5 (1 . 2)) 1)) 1))
+                ff02ffff01ff02ff02ff0180ffff04ffff01ff02ffff01ff10ffff10ff02ff0280ffff010180ffff04ffff12ff05ffff010280ff018080ff018080
+            "#]],
+        );
+        assert_eq!(run(source, [int(5)]), "21");
+    }
+
+    #[test]
+    fn arithmetic() {
+        let source = "def main(a: Int, b: Int) -> Int {\n    \
+                      (a / b) * 1000 + a % b * 100 + (a << 2) - (a >> 1) + (~b & 0xff) - -(a >>> 1)\n}";
+
+        // The same results as the interpreter, with division and shifts rounding down.
+        assert_eq!(run(source, [int(7), int(2)]), "3381");
+        assert_eq!(run(source, [int(19), int(-4)]), "-5021");
+        assert_eq!(run(source, [int(7), int(0)]), "error: division by zero: 7");
+    }
+
+    #[test]
+    fn shifts_match_interpreter() {
+        let source = "def main(a: Int, b: Int) -> Int {\n    (a << b) + (a >> b) + (a >>> b)\n}";
+        let analysis = Analysis::new(&parse(source).tree());
+        let program = compile(&analysis).unwrap();
+
+        for (a, b) in [(5, 1), (-5, 1), (5, -1), (-1, 0), (300, 8), (1, 65536)] {
+            let interpreted = Interpreter::new(&analysis)
+                .run_main(vec![Value::Int(a.into()), Value::Int(b.into())])
+                .map(|value| value.to_string());
+            let compiled = program
+                .run([int(a), int(b)])
+                .map(|node| node.as_int().unwrap().to_string());
+
+            assert_eq!(
+                compiled.is_ok(),
+                interpreted.is_ok(),
+                "{a} and {b}: {compiled:?} {interpreted:?}"
+            );
+            assert_eq!(compiled.ok(), interpreted.ok(), "{a} and {b}");
+        }
+    }
+
+    #[test]
+    fn optimized_programs_match_interpreter() {
+        // `unused` isn't removed, since dividing by zero still has to fail.
+        let source = "const SCALE: Int = 10;\n\
+                      def main(a: Int, b: Int) -> Int {\n    let unused = a / b;\n    \
+                      let y = scale(a) + scale(a);\n    clamp(y, 2 * SCALE) + (a << b)\n}\n\
+                      def scale(n: Int) -> Int {\n    n * SCALE\n}\n\
+                      def clamp(n: Int, max: Int) -> Int {\n    n > max && n == n || false;\n    n - max\n}";
+        let analysis = Analysis::new(&parse(source).tree());
+        let program = compile(&analysis).unwrap();
+
+        for (a, b) in [(3, 1), (-7, 2), (5, 0), (1, -1)] {
+            let interpreted = Interpreter::new(&analysis)
+                .run_main(vec![Value::Int(a.into()), Value::Int(b.into())])
+                .map(|value| value.to_string());
+            let compiled = program
+                .run([int(a), int(b)])
+                .map(|node| node.as_int().unwrap().to_string());
+
+            assert_eq!(
+                compiled.is_ok(),
+                interpreted.is_ok(),
+                "{a} and {b}: {compiled:?} {interpreted:?}"
+            );
+            assert_eq!(compiled.ok(), interpreted.ok(), "{a} and {b}");
+        }
+
+        // `scale` and `clamp` are inlined, so only `main` is left.
+        let names = program
+            .fns
+            .iter()
+            .map(|location| location.name.as_ref().unwrap().as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["main"]);
+    }
+
+    #[test]
+    fn uninitialized_let_is_reported() {
+        // Both backends refuse to run this, so it must be reported before either is used.
+        let source = "def main(n: Int) -> Int {\n    let x: Int;\n    x + n\n}";
+        let analysis = Analysis::new(&parse(source).tree());
+
+        let messages = analysis
+            .diagnostics()
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["`x` is used before it is initialized"]);
+
+        assert_eq!(
+            compile(&analysis).unwrap_err().kind,
+            CompileErrorKind::InvalidProgram
+        );
+        assert_eq!(
+            Interpreter::new(&analysis)
+                .run_main(vec![Value::Int(1.into())])
+                .unwrap_err()
+                .kind,
+            RuntimeErrorKind::InvalidProgram
+        );
+    }
+
+    #[test]
+    fn comparisons_and_booleans() {
+        let source = "def main(a: Int, b: Int, s: String) -> Bool {\n    \
+                      let ordered = a < b && a <= b && b > a && b >= a;\n    \
+                      let same = a == b || a != a;\n    \
+                      ordered & !same ^ ((s == 'rue') | false)\n}";
+
+        assert_eq!(run(source, [int(1), int(2), Node::atom(b"x")]), "1");
+        assert_eq!(run(source, [int(2), int(2), Node::atom(b"x")]), "()");
+        assert_eq!(run(source, [int(2), int(2), Node::atom(b"rue")]), "1");
+    }
+
+    #[test]
+    fn lazy_operators() {
+        let source = "def main(a: Int) -> Bool {\n    \
+                      a != 0 && 10 / a > 1 || a == 0\n}";
+
+        assert_eq!(run(source, [int(0)]), "1");
+        assert_eq!(run(source, [int(5)]), "1");
+        assert_eq!(run(source, [int(20)]), "()");
+    }
+
+    #[test]
+    fn recursion_and_function_values() {
+        let source =
+            "def main(n: Int) -> Bool {\n    let f = is_even;\n    f(n) && !is_odd(n + 2)\n}\n\
+                      def is_even(n: Int) -> Bool {\n    n == 0 || is_odd(n - 1)\n}\n\
+                      def is_odd(n: Int) -> Bool {\n    n != 0 && is_even(n - 1)\n}";
+
+        assert_eq!(run(source, [int(10)]), "1");
+        assert_eq!(run(source, [int(7)]), "()");
+    }
+
+    #[test]
+    fn infinite_tail_recursion() {
+        let program = compile_source("def main(n: Int) -> Int {\n    main(n)\n}");
+
+        assert_eq!(
+            program
+                .run_with_limits([int(1)], u64::MAX, 10_000)
+                .map_err(|error| error.kind),
+            Err(EvalErrorKind::TooManySteps { max_steps: 10_000 })
+        );
+    }
+
+    #[test]
+    fn strings_and_consts() {
+        let source = "const PREFIX: String = 'coin:';\nconst SCALE: Int = 1 << 40;\n\
+                      def main(name: String, amount: Int) -> Bool {\n    \
+                      PREFIX + name == 'coin:rue' && amount * SCALE > SCALE\n}";
+
+        assert_eq!(run(source, [Node::atom(b"rue"), int(3)]), "1");
+        assert_eq!(run(source, [Node::atom(b"rue"), int(1)]), "()");
+        assert_eq!(run(source, [Node::atom(b"eur"), int(3)]), "()");
+
+        check_program(
+            "const A: Int = 2 * 3;\ndef main() -> Int {\n    A + 1\n}",
+            expect![[r#"
+                (2 (1 2 2 1) (4 (1 1 . 7) 1))
+                ff02ffff01ff02ff02ff0180ffff04ffff01ff0107ff018080
+            "#]],
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let analysis = Analysis::new(&parse("def other() {}").tree());
+        assert_eq!(
+            compile(&analysis).unwrap_err().kind,
+            CompileErrorKind::NoMain
+        );
+
+        let source = "def main() -> Int {\n    1 + missing\n}";
+        let analysis = Analysis::new(&parse(source).tree());
+        let error = compile(&analysis).unwrap_err();
+        assert_eq!(error.kind, CompileErrorKind::InvalidProgram);
+        assert_eq!(&source[error.span.unwrap()], "missing");
+    }
+}
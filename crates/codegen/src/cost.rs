@@ -0,0 +1,559 @@
+use std::{collections::HashMap, fmt, rc::Rc};
+
+use hir::{DefId, Name};
+use num_traits::ToPrimitive;
+
+use crate::{traverse_path, tree_path, Node, Op, Program, MAX_SHIFT};
+
+/// The cost of following a path into the environment, before the cost of each step.
+pub const PATH_BASE_COST: u64 = 40;
+/// The cost of each step down a path, and of each leading zero byte in the path.
+pub const PATH_COST_PER_STEP: u64 = 4;
+/// The cost of each byte in an atom which an operator creates.
+pub const MALLOC_COST_PER_BYTE: u64 = 10;
+
+/// How much an operator costs to run, depending on its arguments.
+///
+/// These are modeled on the costs of the target VM, simplified to a base cost, a cost for each
+/// argument and a cost for each byte in the atoms they're given.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OpCost {
+    pub base: u64,
+    pub per_arg: u64,
+    pub per_byte: u64,
+    /// Whether the result is a new atom, which costs [`MALLOC_COST_PER_BYTE`] for each byte.
+    pub allocates: bool,
+}
+
+impl OpCost {
+    const fn new(base: u64, per_arg: u64, per_byte: u64, allocates: bool) -> Self {
+        Self {
+            base,
+            per_arg,
+            per_byte,
+            allocates,
+        }
+    }
+
+    /// The cost of running the operator with arguments of the given sizes in bytes, where
+    /// pairs count as empty, and a result of the given size.
+    pub fn total(self, arg_bytes: &[usize], result_bytes: usize) -> u64 {
+        let bytes = arg_bytes.iter().sum::<usize>() as u64;
+        let malloc = if self.allocates {
+            MALLOC_COST_PER_BYTE * result_bytes as u64
+        } else {
+            0
+        };
+
+        self.base + self.per_arg * arg_bytes.len() as u64 + self.per_byte * bytes + malloc
+    }
+}
+
+impl Op {
+    pub fn cost(self) -> OpCost {
+        match self {
+            Self::Quote => OpCost::new(20, 0, 0, false),
+            Self::Apply => OpCost::new(90, 0, 0, false),
+            Self::If => OpCost::new(33, 0, 0, false),
+            Self::Cons => OpCost::new(50, 0, 0, false),
+            Self::First | Self::Rest => OpCost::new(30, 0, 0, false),
+            Self::Listp => OpCost::new(19, 0, 0, false),
+            Self::Raise => OpCost::new(0, 0, 0, false),
+            Self::Eq | Self::GtBytes => OpCost::new(117, 0, 1, false),
+            Self::Substr => OpCost::new(1, 0, 0, true),
+            Self::Strlen => OpCost::new(173, 0, 1, true),
+            Self::Concat => OpCost::new(142, 135, 3, true),
+            Self::Add | Self::Sub => OpCost::new(99, 320, 3, true),
+            Self::Mul => OpCost::new(92, 885, 6, true),
+            Self::Div => OpCost::new(988, 0, 4, true),
+            Self::Divmod => OpCost::new(1116, 0, 6, true),
+            Self::Gt => OpCost::new(498, 0, 2, false),
+            Self::Ash => OpCost::new(596, 0, 3, true),
+            Self::Lsh => OpCost::new(277, 0, 3, true),
+            Self::Logand | Self::Logior | Self::Logxor => OpCost::new(100, 264, 3, true),
+            Self::Lognot => OpCost::new(331, 0, 3, true),
+            Self::Not | Self::Any | Self::All => OpCost::new(200, 300, 0, false),
+        }
+    }
+}
+
+/// The cost of evaluating an atom, which looks up its path in the environment.
+pub fn path_cost(path: &[u8]) -> u64 {
+    let zeros = path.iter().take_while(|&&byte| byte == 0).count();
+    let steps = match path.get(zeros) {
+        Some(&first) => (path.len() - zeros - 1) * 8 + (7 - first.leading_zeros() as usize),
+        None => 0,
+    };
+
+    PATH_BASE_COST + PATH_COST_PER_STEP * (zeros + steps) as u64
+}
+
+/// The most a program and each of its functions can cost to run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostEstimate {
+    /// The cost of running the whole program, or `None` if there's no upper bound.
+    pub program: Option<u64>,
+    /// The cost of each function, in the order they were defined.
+    pub fns: Vec<FnCost>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FnCost {
+    pub def: DefId,
+    pub name: Option<Name>,
+    /// The cost of running the function once it's called, including the functions it calls,
+    /// or `None` if it can recurse and there's no upper bound.
+    pub cost: Option<u64>,
+}
+
+impl fmt::Display for CostEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "program: {}", CostDisplay(self.program))?;
+
+        for function in &self.fns {
+            let name = function.name.as_ref().map_or("<missing>", Name::as_str);
+            writeln!(f, "def {name}: {}", CostDisplay(function.cost))?;
+        }
+
+        Ok(())
+    }
+}
+
+struct CostDisplay(Option<u64>);
+
+impl fmt::Display for CostDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(cost) => write!(f, "{cost}"),
+            None => write!(f, "unbounded"),
+        }
+    }
+}
+
+/// Finds the worst case cost of a program and each function in it, without running it.
+///
+/// The arguments aren't known, so each atom in them is assumed to be `max_atom_bytes` long, and
+/// the result of an operator is as long as it can be for the sizes of its arguments. When a
+/// condition isn't known the more expensive branch is counted. Recursion, or running a program
+/// which isn't known, has no upper bound.
+pub fn estimate_cost(program: &Program, max_atom_bytes: usize) -> CostEstimate {
+    let mut estimator = Estimator {
+        max_atom_bytes,
+        running: Vec::new(),
+        memo: HashMap::new(),
+    };
+
+    let total = estimator
+        .apply(&Value::Known(program.node.clone()), &Value::Unknown)
+        .map(|(cost, _)| cost);
+
+    let tree = traverse_path(Node::int(&tree_path()).as_atom().unwrap(), &program.node)
+        .expect("the program contains the function tree");
+
+    let fns = program
+        .fns
+        .iter()
+        .map(|location| {
+            let code = traverse_path(Node::int(&location.path).as_atom().unwrap(), &program.node)
+                .expect("the program contains every function");
+
+            // Each function runs with an environment of `(functions . arguments)`.
+            let env = Value::pair(Value::Known(tree.clone()), Value::Unknown);
+
+            FnCost {
+                def: location.def,
+                name: location.name.clone(),
+                cost: estimator
+                    .apply(&Value::Known(code), &env)
+                    .map(|(cost, _)| cost),
+            }
+        })
+        .collect();
+
+    CostEstimate {
+        program: total,
+        fns,
+    }
+}
+
+/// What's known about a value at compile time.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Value {
+    Known(Node),
+    /// A pair where at least one side isn't known.
+    Pair(Rc<Value>, Rc<Value>),
+    /// One of two values, depending on a condition which isn't known.
+    Either(Rc<Value>, Rc<Value>),
+    /// An atom with at most this many bytes.
+    Atom(usize),
+    Unknown,
+}
+
+impl Value {
+    fn pair(first: Value, rest: Value) -> Self {
+        match (first, rest) {
+            (Self::Known(first), Self::Known(rest)) => Self::Known(Node::pair(first, rest)),
+            (first, rest) => Self::Pair(Rc::new(first), Rc::new(rest)),
+        }
+    }
+
+    fn either(a: Value, b: Value) -> Self {
+        if a == b {
+            a
+        } else {
+            Self::Either(Rc::new(a), Rc::new(b))
+        }
+    }
+
+    fn known(&self) -> Option<Node> {
+        match self {
+            Self::Known(node) => Some(node.clone()),
+            _ => None,
+        }
+    }
+
+    fn split(&self) -> Option<(Value, Value)> {
+        match self {
+            Self::Known(node) => {
+                let (first, rest) = node.as_pair()?;
+                Some((Self::Known(first.clone()), Self::Known(rest.clone())))
+            }
+            Self::Pair(first, rest) => Some((Value::clone(first), Value::clone(rest))),
+            Self::Either(..) | Self::Atom(_) | Self::Unknown => None,
+        }
+    }
+
+    /// Follows a path the same way as the runner, as far as the value is known.
+    fn traverse(&self, path: &[u8]) -> Value {
+        let Some(start) = path.iter().position(|&byte| byte != 0) else {
+            return Self::Known(Node::nil());
+        };
+        let path = &path[start..];
+
+        let mut value = self.clone();
+
+        for (index, &byte) in path.iter().enumerate().rev() {
+            let bits = if index == 0 {
+                7 - byte.leading_zeros()
+            } else {
+                8
+            };
+
+            for bit in 0..bits {
+                let Some((first, rest)) = value.split() else {
+                    return Self::Unknown;
+                };
+                value = if byte >> bit & 1 == 0 { first } else { rest };
+            }
+        }
+
+        value
+    }
+
+    /// The most bytes the value can have as an argument to an operator.
+    fn max_bytes(&self, max_atom_bytes: usize) -> usize {
+        match self {
+            Self::Known(node) => node.as_atom().map_or(0, <[u8]>::len),
+            Self::Pair(..) => 0,
+            Self::Either(a, b) => a.max_bytes(max_atom_bytes).max(b.max_bytes(max_atom_bytes)),
+            Self::Atom(bytes) => *bytes,
+            Self::Unknown => max_atom_bytes,
+        }
+    }
+}
+
+struct Estimator {
+    max_atom_bytes: usize,
+    /// The programs which are being applied, to find recursion.
+    running: Vec<Node>,
+    memo: HashMap<(Node, Value), Option<(u64, Value)>>,
+}
+
+impl Estimator {
+    /// The most it can cost to evaluate a program, and what's known about its value, or `None`
+    /// if there's no upper bound.
+    fn eval(&mut self, program: &Node, env: &Value) -> Option<(u64, Value)> {
+        let (op, args) = match program {
+            Node::Atom(path) => return Some((path_cost(path), env.traverse(path))),
+            Node::Pair(op, args) => (op, args),
+        };
+
+        // A program which fails stops running, so it doesn't cost anything more.
+        let Some(op) = op.as_atom().and_then(Op::from_atom) else {
+            return Some((0, Value::Unknown));
+        };
+
+        if op == Op::Quote {
+            return Some((op.cost().base, Value::Known(Node::clone(args))));
+        }
+
+        let mut cost = 0u64;
+        let mut values = Vec::new();
+
+        for arg in args.iter() {
+            let (arg_cost, value) = self.eval(arg, env)?;
+            cost = cost.saturating_add(arg_cost);
+            values.push(value);
+        }
+
+        let arg_bytes = values
+            .iter()
+            .map(|value| value.max_bytes(self.max_atom_bytes))
+            .collect::<Vec<_>>();
+        let base = op.cost().base;
+
+        let (op_cost, value) = match (op, values.as_slice()) {
+            (Op::Apply, [program, env]) => {
+                let (run_cost, value) = self.apply(program, env)?;
+                (base.saturating_add(run_cost), value)
+            }
+            (Op::If, [condition, then, otherwise]) => {
+                let value = match condition {
+                    Value::Known(node) if node.is_nil() => otherwise.clone(),
+                    Value::Known(_) | Value::Pair(..) => then.clone(),
+                    Value::Either(..) | Value::Atom(_) | Value::Unknown => {
+                        Value::either(then.clone(), otherwise.clone())
+                    }
+                };
+                (base, value)
+            }
+            (Op::Cons, [first, rest]) => (base, Value::pair(first.clone(), rest.clone())),
+            (Op::First | Op::Rest, [value]) => {
+                let value = match value.split() {
+                    Some((first, _)) if op == Op::First => first,
+                    Some((_, rest)) => rest,
+                    None => Value::Unknown,
+                };
+                (base, value)
+            }
+            _ => {
+                let (value, result_bytes) = bound(op, &values, &arg_bytes);
+                (op.cost().total(&arg_bytes, result_bytes), value)
+            }
+        };
+
+        Some((cost.saturating_add(op_cost), value))
+    }
+
+    fn apply(&mut self, program: &Value, env: &Value) -> Option<(u64, Value)> {
+        match program {
+            Value::Known(program) => {
+                let key = (program.clone(), env.clone());
+
+                if let Some(result) = self.memo.get(&key) {
+                    return result.clone();
+                }
+
+                // A program which runs itself again can recurse any number of times.
+                if self.running.contains(program) {
+                    return None;
+                }
+
+                self.running.push(program.clone());
+                let result = self.eval(program, env);
+                self.running.pop();
+
+                self.memo.insert(key, result.clone());
+                result
+            }
+            Value::Either(a, b) => {
+                let (a_cost, a) = self.apply(a, env)?;
+                let (b_cost, b) = self.apply(b, env)?;
+                Some((a_cost.max(b_cost), Value::either(a, b)))
+            }
+            // Running a program which isn't known could cost anything.
+            Value::Pair(..) | Value::Atom(_) | Value::Unknown => None,
+        }
+    }
+}
+
+/// The largest result of an operator on arguments with at most the given number of bytes, and
+/// how many bytes it allocates at most.
+fn bound(op: Op, values: &[Value], arg_bytes: &[usize]) -> (Value, usize) {
+    let first = arg_bytes.first().copied().unwrap_or(0);
+    let max = arg_bytes.iter().copied().max().unwrap_or(0);
+    let sum = arg_bytes.iter().sum::<usize>();
+
+    let bytes = match op {
+        Op::Concat => sum,
+        // The product of an `n` byte and an `m` byte number fits in `n + m` bytes, and with no
+        // arguments it's 1.
+        Op::Mul => sum.max(1),
+        // Each addition can carry into one more byte.
+        Op::Add | Op::Sub => max + arg_bytes.len().saturating_sub(1),
+        // Dividing the most negative number by -1 makes it positive, which needs another byte.
+        Op::Div => first + 1,
+        Op::Divmod => {
+            let quotient = first + 1;
+            let remainder = arg_bytes.get(1).copied().unwrap_or(0);
+            let value = Value::pair(Value::Atom(quotient), Value::Atom(remainder));
+            return (value, quotient + remainder);
+        }
+        // A number can be shifted left by up to the largest amount, unless the amount is known.
+        // Shifting the unsigned bytes can also add a zero byte to keep the result positive.
+        Op::Ash | Op::Lsh => {
+            let amount = values
+                .get(1)
+                .and_then(Value::known)
+                .and_then(|amount| amount.as_int())
+                .and_then(|amount| amount.to_i64())
+                .unwrap_or(MAX_SHIFT)
+                .clamp(0, MAX_SHIFT);
+            first + amount as usize / 8 + 1
+        }
+        Op::Logand | Op::Logior | Op::Logxor => max,
+        Op::Lognot | Op::Substr => first,
+        Op::Strlen => (usize::BITS - first.leading_zeros()) as usize / 8 + 1,
+        // Everything else returns a boolean, or doesn't return at all.
+        _ => 1,
+    };
+
+    (Value::Atom(bytes), bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use hir::Analysis;
+    use num_bigint::BigInt;
+    use parser::parse;
+
+    use super::*;
+    use crate::{compile, run_with_cost, EvalErrorKind};
+
+    fn int(value: i64) -> Node {
+        Node::int(&BigInt::from(value))
+    }
+
+    fn compile_source(source: &str) -> Program {
+        compile(&Analysis::new(&parse(source).tree())).unwrap()
+    }
+
+    fn check(source: &str, expect: Expect) {
+        expect.assert_eq(&compile_source(source).estimate_cost(32).to_string());
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(path_cost(&[]), 40);
+        assert_eq!(path_cost(&[1]), 40);
+        assert_eq!(path_cost(&[2]), 44);
+        assert_eq!(path_cost(&[0b1011]), 52);
+        assert_eq!(path_cost(&[0, 1, 0]), 40 + 4 + 8 * 4);
+    }
+
+    #[test]
+    fn runtime_cost() {
+        // (+ 2 (q . 5)), which costs a path lookup, a quote and an addition.
+        let program = Node::op(Op::Add, [int(2), Node::quote(int(5))]);
+        let env = Node::list([int(37)]);
+        let cost = 44 + 20 + (99 + 2 * 320 + 3 * 2 + 10);
+
+        let evaluation = run_with_cost(&program, &env, u64::MAX).unwrap();
+        assert_eq!(evaluation.value, int(42));
+        assert_eq!(evaluation.cost, cost);
+
+        assert_eq!(run_with_cost(&program, &env, cost).unwrap().cost, cost);
+        assert_eq!(
+            run_with_cost(&program, &env, cost - 1).map_err(|error| error.kind),
+            Err(EvalErrorKind::CostExceeded { max_cost: cost - 1 })
+        );
+    }
+
+    #[test]
+    fn straight_line() {
+        let source = "def main(a: Int, b: Int) -> Int {\n    let c = a * b;\n    c + 1\n}";
+
+        check(
+            source,
+            expect![[r#"
+                program: 4984
+                def main: 4590
+            "#]],
+        );
+
+        let program = compile_source(source);
+        let estimate = program.estimate_cost(32).program.unwrap();
+        let actual = program.run_with_cost([int(3), int(4)], u64::MAX).unwrap();
+        assert!(actual.cost <= estimate, "{} > {estimate}", actual.cost);
+    }
+
+    #[test]
+    fn branches_and_calls() {
+        let source = "const LIMIT: Int = 100;\n\
+                      def main(a: Int, name: String) -> Bool {\n    \
+                      a > LIMIT && check(a, name) || a == 0\n}\n\
+                      def check(n: Int, name: String) -> Bool {\n    \
+                      n / 3 == 1 && name + 'x' != 'yx'\n}";
+
+        check(
+            source,
+            expect![[r#"
+                program: 5186
+                def main: 4792
+            "#]],
+        );
+
+        let program = compile_source(source);
+        let estimate = program.estimate_cost(32).program.unwrap();
+
+        for args in [
+            [int(0), Node::atom(b"a")],
+            [int(150), Node::atom(b"abc")],
+            [int(1000), Node::atom(b"y")],
+        ] {
+            let actual = program.run_with_cost(args, u64::MAX).unwrap();
+            assert!(actual.cost <= estimate, "{} > {estimate}", actual.cost);
+        }
+    }
+
+    #[test]
+    fn growing_results() {
+        let product = "def main(a: Int, b: Int) -> Int {\n    a * b * a * b * a * b\n}";
+        let concat = "def main(a: Bytes, b: Bytes) -> Bytes {\n    a + b + a + b + a + b\n}";
+
+        check(
+            product,
+            expect![[r#"
+                program: 20244
+                def main: 19850
+            "#]],
+        );
+        check(
+            concat,
+            expect![[r#"
+                program: 11074
+                def main: 10680
+            "#]],
+        );
+
+        // The largest 32 byte numbers, so every result is as long as it can be.
+        for source in [product, concat] {
+            let program = compile_source(source);
+            let estimate = program.estimate_cost(32).program.unwrap();
+
+            for byte in [0x7f, 0x80] {
+                let arg = Node::atom(&[byte; 32]);
+                let actual = program.run_with_cost([arg.clone(), arg], u64::MAX).unwrap();
+                assert!(actual.cost <= estimate, "{} > {estimate}", actual.cost);
+            }
+        }
+    }
+
+    #[test]
+    fn recursion_is_unbounded() {
+        check(
+            "def main(n: Int) -> Bool {\n    n > 0 && is_even(leaf(n))\n}\n\
+             def is_even(n: Int) -> Bool {\n    n == 0 || is_odd(n - 1)\n}\n\
+             def is_odd(n: Int) -> Bool {\n    n != 0 && is_even(n - 1)\n}\n\
+             def leaf(n: Int) -> Int {\n    n * n * n * n * n * n * n * n * n * n\n}",
+            expect![[r#"
+                program: unbounded
+                def main: unbounded
+                def is_even: unbounded
+                def is_odd: unbounded
+                def leaf: 44886
+            "#]],
+        );
+    }
+}
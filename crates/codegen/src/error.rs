@@ -0,0 +1,53 @@
+use std::fmt;
+
+use diagnostics::Diagnostic;
+use rowan::TextRange;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileError {
+    pub kind: CompileErrorKind,
+    /// The expression which couldn't be compiled, if the error is at a point in the source.
+    pub span: Option<TextRange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompileErrorKind {
+    NoMain,
+    /// The program has an error which was reported before it was compiled.
+    InvalidProgram,
+}
+
+impl fmt::Display for CompileErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoMain => write!(f, "no `main` function to compile"),
+            Self::InvalidProgram => write!(f, "cannot compile an expression with errors"),
+        }
+    }
+}
+
+impl CompileError {
+    pub fn message(&self) -> String {
+        self.kind.to_string()
+    }
+
+    /// Converts the error to a diagnostic, or `None` if it isn't at a point in the source.
+    pub fn to_diagnostic(&self) -> Option<Diagnostic> {
+        Some(Diagnostic::error(self.message(), self.span?))
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(
+                f,
+                "{} at {}..{}",
+                self.kind,
+                u32::from(span.start()),
+                u32::from(span.end())
+            ),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+mod compile;
+mod cost;
+mod error;
+mod node;
+mod op;
+mod path;
+mod pretty;
+#[cfg(test)]
+mod runner;
+mod serialize;
+
+pub(crate) use compile::tree_path;
+pub use compile::{compile, FnLocation, Program};
+pub use cost::{estimate_cost, path_cost, CostEstimate, FnCost, OpCost, MALLOC_COST_PER_BYTE};
+pub use error::{CompileError, CompileErrorKind};
+pub use node::Node;
+pub use op::Op;
+pub(crate) use op::MAX_SHIFT;
+pub(crate) use path::*;
+pub use pretty::pretty;
+#[cfg(test)]
+pub(crate) use runner::{run_with_cost, EvalErrorKind};
+pub use serialize::{deserialize, from_hex, serialize, to_hex, DeserializeError};
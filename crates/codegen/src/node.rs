@@ -0,0 +1,228 @@
+use std::{fmt, rc::Rc};
+
+use num_bigint::BigInt;
+use num_traits::Zero;
+
+use crate::Op;
+
+/// A CLVM value, which is either an atom of bytes or a pair of two values.
+///
+/// Both kinds of node are reference counted, so programs and environments can be shared
+/// cheaply while they are being built and run.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum Node {
+    Atom(Rc<[u8]>),
+    Pair(Rc<Node>, Rc<Node>),
+}
+
+impl Node {
+    /// The empty atom, which is also the empty list, zero and false.
+    pub fn nil() -> Self {
+        Self::Atom(Rc::from([]))
+    }
+
+    pub fn atom(bytes: &[u8]) -> Self {
+        Self::Atom(Rc::from(bytes))
+    }
+
+    /// An integer, encoded in the fewest bytes of big endian two's complement.
+    pub fn int(value: &BigInt) -> Self {
+        if value.is_zero() {
+            return Self::nil();
+        }
+
+        Self::atom(&value.to_signed_bytes_be())
+    }
+
+    pub fn bool(value: bool) -> Self {
+        if value {
+            Self::atom(&[1])
+        } else {
+            Self::nil()
+        }
+    }
+
+    pub fn pair(first: Node, rest: Node) -> Self {
+        Self::Pair(Rc::new(first), Rc::new(rest))
+    }
+
+    /// A proper list, which ends with nil.
+    pub fn list(items: impl IntoIterator<Item = Node, IntoIter: DoubleEndedIterator>) -> Self {
+        items
+            .into_iter()
+            .rev()
+            .fold(Self::nil(), |rest, item| Self::pair(item, rest))
+    }
+
+    /// `(q . value)`, which evaluates to the value itself.
+    pub fn quote(value: Node) -> Self {
+        Self::pair(Self::atom(&[Op::Quote.opcode()]), value)
+    }
+
+    /// A call to an operator, with the programs for its arguments.
+    pub fn op(op: Op, args: impl IntoIterator<Item = Node, IntoIter: DoubleEndedIterator>) -> Self {
+        Self::pair(Self::atom(&[op.opcode()]), Self::list(args))
+    }
+
+    pub fn as_atom(&self) -> Option<&[u8]> {
+        match self {
+            Self::Atom(bytes) => Some(bytes),
+            Self::Pair(..) => None,
+        }
+    }
+
+    pub fn as_pair(&self) -> Option<(&Node, &Node)> {
+        match self {
+            Self::Atom(_) => None,
+            Self::Pair(first, rest) => Some((first, rest)),
+        }
+    }
+
+    pub fn is_nil(&self) -> bool {
+        self.as_atom().is_some_and(<[u8]>::is_empty)
+    }
+
+    /// The atom as a signed integer.
+    pub fn as_int(&self) -> Option<BigInt> {
+        self.as_atom().map(BigInt::from_signed_bytes_be)
+    }
+
+    /// The items of a list, and the atom it ends with.
+    pub fn iter(&self) -> ListIter<'_> {
+        ListIter { node: self }
+    }
+}
+
+/// Walks down the spine of a list, yielding the first item of each pair.
+pub struct ListIter<'a> {
+    node: &'a Node,
+}
+
+impl<'a> ListIter<'a> {
+    /// The atom at the end of the list, once every item has been visited.
+    pub fn terminator(&self) -> &'a Node {
+        self.node
+    }
+}
+
+impl<'a> Iterator for ListIter<'a> {
+    type Item = &'a Node;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (first, rest) = self.node.as_pair()?;
+        self.node = rest;
+        Some(first)
+    }
+}
+
+/// Whether an atom is written the same way that [`Node::int`] would write its value.
+pub(crate) fn is_canonical_int(bytes: &[u8]) -> bool {
+    match bytes {
+        [] => true,
+        [0] => false,
+        [0, next, ..] => next & 0x80 != 0,
+        [0xff, next, ..] => next & 0x80 == 0,
+        _ => true,
+    }
+}
+
+/// Prints the node in the usual text form, with printable text in quotes, small integers
+/// in decimal and anything else in hex.
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Atom(bytes) => write_atom(f, bytes),
+            Self::Pair(..) => {
+                write!(f, "(")?;
+
+                let mut items = self.iter();
+                let mut first = true;
+
+                for item in items.by_ref() {
+                    if !first {
+                        write!(f, " ")?;
+                    }
+                    first = false;
+                    write!(f, "{item}")?;
+                }
+
+                let terminator = items.terminator();
+
+                if !terminator.is_nil() {
+                    write!(f, " . {terminator}")?;
+                }
+
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+fn write_atom(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    if bytes.is_empty() {
+        return write!(f, "()");
+    }
+
+    // Short atoms are more likely to be numbers than text, such as paths and opcodes.
+    if bytes.len() > 2 && bytes.iter().all(|&byte| (0x20..0x7f).contains(&byte)) {
+        if let Ok(text) = std::str::from_utf8(bytes) {
+            return write!(f, "{text:?}");
+        }
+    }
+
+    if bytes.len() <= 4 && is_canonical_int(bytes) {
+        return write!(f, "{}", BigInt::from_signed_bytes_be(bytes));
+    }
+
+    write!(f, "0x")?;
+    bytes.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ints() {
+        for (value, bytes) in [
+            (0, &[][..]),
+            (1, &[1]),
+            (127, &[0x7f]),
+            (128, &[0, 0x80]),
+            (-1, &[0xff]),
+            (-128, &[0x80]),
+            (-129, &[0xff, 0x7f]),
+            (256, &[1, 0]),
+        ] {
+            let node = Node::int(&BigInt::from(value));
+            assert_eq!(node.as_atom(), Some(bytes), "{value}");
+            assert_eq!(node.as_int(), Some(BigInt::from(value)));
+            assert!(is_canonical_int(bytes));
+        }
+
+        assert!(!is_canonical_int(&[0]));
+        assert!(!is_canonical_int(&[0, 1]));
+        assert!(!is_canonical_int(&[0xff, 0x80]));
+    }
+
+    #[test]
+    fn display() {
+        let node = Node::list([
+            Node::int(&BigInt::from(16)),
+            Node::pair(Node::atom(&[1]), Node::atom(b"hello")),
+            Node::list([Node::nil(), Node::atom(&[0, 0, 0, 0, 1])]),
+            Node::int(&BigInt::from(-5)),
+        ]);
+
+        assert_eq!(
+            node.to_string(),
+            "(16 (1 . \"hello\") (() 0x0000000001) -5)"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+/// The largest amount `ash` and `lsh` can shift a number by, in either direction.
+pub(crate) const MAX_SHIFT: i64 = 65535;
+
+/// The operators which programs are compiled to, by their opcode on the target VM.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Op {
+    /// `q`, which returns its argument without evaluating it.
+    Quote,
+    /// `a`, which runs a program with an environment.
+    Apply,
+    /// `i`, which picks between two values. Both are evaluated, so any laziness comes from
+    /// picking between two quoted programs and applying the result.
+    If,
+    Cons,
+    First,
+    Rest,
+    Listp,
+    Raise,
+    Eq,
+    GtBytes,
+    Substr,
+    Strlen,
+    Concat,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Divmod,
+    Gt,
+    Ash,
+    Lsh,
+    Logand,
+    Logior,
+    Logxor,
+    Lognot,
+    Not,
+    Any,
+    All,
+}
+
+impl Op {
+    pub const ALL: [Op; 28] = [
+        Op::Quote,
+        Op::Apply,
+        Op::If,
+        Op::Cons,
+        Op::First,
+        Op::Rest,
+        Op::Listp,
+        Op::Raise,
+        Op::Eq,
+        Op::GtBytes,
+        Op::Substr,
+        Op::Strlen,
+        Op::Concat,
+        Op::Add,
+        Op::Sub,
+        Op::Mul,
+        Op::Div,
+        Op::Divmod,
+        Op::Gt,
+        Op::Ash,
+        Op::Lsh,
+        Op::Logand,
+        Op::Logior,
+        Op::Logxor,
+        Op::Lognot,
+        Op::Not,
+        Op::Any,
+        Op::All,
+    ];
+
+    pub fn opcode(self) -> u8 {
+        match self {
+            Self::Quote => 1,
+            Self::Apply => 2,
+            Self::If => 3,
+            Self::Cons => 4,
+            Self::First => 5,
+            Self::Rest => 6,
+            Self::Listp => 7,
+            Self::Raise => 8,
+            Self::Eq => 9,
+            Self::GtBytes => 10,
+            Self::Substr => 12,
+            Self::Strlen => 13,
+            Self::Concat => 14,
+            Self::Add => 16,
+            Self::Sub => 17,
+            Self::Mul => 18,
+            Self::Div => 19,
+            Self::Divmod => 20,
+            Self::Gt => 21,
+            Self::Ash => 22,
+            Self::Lsh => 23,
+            Self::Logand => 24,
+            Self::Logior => 25,
+            Self::Logxor => 26,
+            Self::Lognot => 27,
+            Self::Not => 32,
+            Self::Any => 33,
+            Self::All => 34,
+        }
+    }
+
+    /// The operator for an atom, if it's the opcode of one.
+    pub fn from_atom(atom: &[u8]) -> Option<Self> {
+        match atom {
+            [opcode] => Self::ALL.into_iter().find(|op| op.opcode() == *opcode),
+            _ => None,
+        }
+    }
+
+    /// The name of the operator in Chialisp.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Quote => "q",
+            Self::Apply => "a",
+            Self::If => "i",
+            Self::Cons => "c",
+            Self::First => "f",
+            Self::Rest => "r",
+            Self::Listp => "l",
+            Self::Raise => "x",
+            Self::Eq => "=",
+            Self::GtBytes => ">s",
+            Self::Substr => "substr",
+            Self::Strlen => "strlen",
+            Self::Concat => "concat",
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Divmod => "divmod",
+            Self::Gt => ">",
+            Self::Ash => "ash",
+            Self::Lsh => "lsh",
+            Self::Logand => "logand",
+            Self::Logior => "logior",
+            Self::Logxor => "logxor",
+            Self::Lognot => "lognot",
+            Self::Not => "not",
+            Self::Any => "any",
+            Self::All => "all",
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use num_bigint::BigInt;
+use num_traits::One;
+
+use crate::Node;
+
+/// The path which follows `outer` and then `inner`.
+///
+/// Paths are read from the lowest bit, where 0 means the first of a pair and 1 means the rest,
+/// up to the highest set bit which marks the end. So the steps of the inner path go above the
+/// outer ones, in place of the bit which marks the end of the outer path.
+pub(crate) fn compose(outer: &BigInt, inner: &BigInt) -> BigInt {
+    let depth = outer.bits() - 1;
+    (inner << depth) | (outer ^ (BigInt::one() << depth))
+}
+
+/// The path to an item of a list.
+pub(crate) fn list_item(index: usize) -> BigInt {
+    (0..index).fold(BigInt::from(2), |path, _| compose(&BigInt::from(3), &path))
+}
+
+/// Follows a path through the environment, or returns `None` if it goes into an atom.
+///
+/// The bits of the path are read from least to most significant, where 0 means the first item
+/// and 1 means the rest, until the highest set bit which marks the end. The path 0 is nil.
+pub(crate) fn traverse_path(path: &[u8], env: &Node) -> Option<Node> {
+    let start = path.iter().position(|&byte| byte != 0);
+    let Some(start) = start else {
+        return Some(Node::nil());
+    };
+    let path = &path[start..];
+
+    let mut node = env;
+
+    for (index, &byte) in path.iter().enumerate().rev() {
+        let bits = if index == 0 {
+            7 - byte.leading_zeros()
+        } else {
+            8
+        };
+
+        for bit in 0..bits {
+            let (first, rest) = node.as_pair()?;
+            node = if byte >> bit & 1 == 0 { first } else { rest };
+        }
+    }
+
+    Some(node.clone())
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+
+use num_bigint::BigInt;
+
+use crate::{compose, Node, Op};
+
+/// How wide a line can be before a list is split over several lines.
+const WIDTH: usize = 80;
+
+/// Prints a program as Chialisp S-expressions, for reading and diffing compiler output.
+///
+/// Opcodes at the start of a list are written by name, and `(q . X)` is always written with
+/// the dot, so it's clear where quoted code starts. Lists which don't fit on a line have each
+/// argument on its own line. Nodes can be labeled with a comment by their path in the program,
+/// which is written on the line before them.
+pub fn pretty(node: &Node, comments: &HashMap<BigInt, String>) -> String {
+    let mut printer = Printer {
+        comments,
+        out: String::new(),
+    };
+    printer.write(node, &BigInt::from(1), 0);
+    printer.out.push('\n');
+    printer.out
+}
+
+/// How a node is split into parts, which is the same whether or not it fits on a line.
+enum Layout<'a> {
+    Atom(String),
+    /// `(q . X)`, with the quoted node and its path.
+    Quote(&'a Node, BigInt),
+    List {
+        /// The name of the operator, if the list starts with an opcode.
+        op: Option<&'static str>,
+        items: Vec<(&'a Node, BigInt)>,
+        /// What the list ends with instead of nil, such as a labeled node in a tree.
+        tail: Option<(&'a Node, BigInt)>,
+    },
+}
+
+struct Printer<'a> {
+    comments: &'a HashMap<BigInt, String>,
+    out: String,
+}
+
+impl Printer<'_> {
+    fn layout<'n>(&self, node: &'n Node, path: &BigInt) -> Layout<'n> {
+        let (first, rest) = match node {
+            Node::Atom(_) => return Layout::Atom(node.to_string()),
+            Node::Pair(first, rest) => (first, rest),
+        };
+
+        if first.as_atom().and_then(Op::from_atom) == Some(Op::Quote) {
+            return Layout::Quote(rest, compose(path, &BigInt::from(3)));
+        }
+
+        let op = first.as_atom().and_then(Op::from_atom).map(Op::name);
+
+        let mut items = vec![(&**first, compose(path, &BigInt::from(2)))];
+        let mut node: &Node = rest;
+        let mut path = compose(path, &BigInt::from(3));
+
+        // A labeled node has to start on its own line, so it can't be flattened into the list.
+        while let (Some((first, rest)), false) = (node.as_pair(), self.comments.contains_key(&path))
+        {
+            items.push((first, compose(&path, &BigInt::from(2))));
+            node = rest;
+            path = compose(&path, &BigInt::from(3));
+        }
+
+        let tail = (!node.is_nil() || self.comments.contains_key(&path)).then_some((node, path));
+
+        Layout::List { op, items, tail }
+    }
+
+    /// The node on a single line, or `None` if part of it has a comment.
+    fn inline(&self, node: &Node, path: &BigInt) -> Option<String> {
+        let child = |node: &Node, path: &BigInt| {
+            if self.comments.contains_key(path) {
+                None
+            } else {
+                self.inline(node, path)
+            }
+        };
+
+        match self.layout(node, path) {
+            Layout::Atom(text) => Some(text),
+            Layout::Quote(node, path) => Some(format!("(q . {})", child(node, &path)?)),
+            Layout::List { op, items, tail } => {
+                let mut parts = Vec::new();
+
+                for (i, (item, path)) in items.iter().enumerate() {
+                    match op {
+                        Some(op) if i == 0 => parts.push(op.to_string()),
+                        _ => parts.push(child(item, path)?),
+                    }
+                }
+
+                if let Some((tail, path)) = tail {
+                    parts.push(".".to_string());
+                    parts.push(child(tail, &path)?);
+                }
+
+                Some(format!("({})", parts.join(" ")))
+            }
+        }
+    }
+
+    fn write(&mut self, node: &Node, path: &BigInt, indent: usize) {
+        if let Some(comment) = self.comments.get(path) {
+            self.out.push_str(&format!("; {comment}\n{:indent$}", ""));
+        }
+
+        if let Some(text) = self.inline(node, path) {
+            if indent + text.len() <= WIDTH {
+                self.out.push_str(&text);
+                return;
+            }
+        }
+
+        match self.layout(node, path) {
+            Layout::Atom(text) => self.out.push_str(&text),
+            Layout::Quote(node, path) => {
+                self.out.push_str("(q .");
+                self.line(indent + 2);
+                self.write(node, &path, indent + 2);
+                self.out.push(')');
+            }
+            Layout::List { op, items, tail } => {
+                self.out.push('(');
+
+                for (i, (item, path)) in items.iter().enumerate() {
+                    match op {
+                        Some(op) if i == 0 => self.out.push_str(op),
+                        _ => {
+                            self.line(indent + 2);
+                            self.write(item, path, indent + 2);
+                        }
+                    }
+                }
+
+                if let Some((tail, path)) = tail {
+                    self.line(indent + 2);
+                    self.out.push('.');
+                    self.line(indent + 2);
+                    self.write(tail, &path, indent + 2);
+                }
+
+                self.out.push(')');
+            }
+        }
+    }
+
+    fn line(&mut self, indent: usize) {
+        self.out.push_str(&format!("\n{:indent$}", ""));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use hir::Analysis;
+    use parser::parse;
+
+    use super::*;
+    use crate::compile;
+
+    fn check(source: &str, comments: bool, expect: Expect) {
+        let analysis = Analysis::new(&parse(source).tree());
+        let program = compile(&analysis).unwrap();
+        expect.assert_eq(&program.to_chialisp(comments));
+    }
+
+    #[test]
+    fn short_program() {
+        check(
+            "def main(a: Int, b: Int) -> Int {\n    a + b\n}",
+            false,
+            expect![[r#"
+                (a (q . (a 2 1)) (c (q . (+ 5 11)) 1))
+            "#]],
+        );
+    }
+
+    #[test]
+    fn comments() {
+        check(
+            "def main(x: Int) -> Bool {\n    let y = x * 2;\n    is_even(y + 1)\n}\n\
+             def is_even(n: Int) -> Bool {\n    n == 0 || !is_even(n - 1)\n}",
+            true,
+            expect![[r#"
+                (a
+                  (q . (a 4 1))
+                  (c
+                    (q .
+                      (
+                        ; def main
+                        (a 6 (c 2 (c (+ (* 5 (q . 2)) (q . 1)) ())))
+                        .
+                        ; def is_even
+                        (a
+                          (i
+                            (= 5 ())
+                            (q . (q . 1))
+                            (q . (not (a 6 (c 2 (c (- 5 (q . 1)) ()))))))
+                          1)))
+                    1))
+            "#]],
+        );
+    }
+
+    #[test]
+    fn without_comments() {
+        check(
+            "def main(x: Int) -> Bool {\n    let y = x * 2;\n    is_even(y + 1)\n}\n\
+             def is_even(n: Int) -> Bool {\n    n == 0 || !is_even(n - 1)\n}",
+            false,
+            expect![[r#"
+                (a
+                  (q . (a 4 1))
+                  (c
+                    (q .
+                      (
+                        (a 6 (c 2 (c (+ (* 5 (q . 2)) (q . 1)) ())))
+                        2
+                        (i (= 5 ()) (q . (q . 1)) (q . (not (a 6 (c 2 (c (- 5 (q . 1)) ()))))))
+                        1))
+                    1))
+            "#]],
+        );
+    }
+
+    #[test]
+    fn larger_program() {
+        check(
+            "const GREETING: String = 'hello, ';\n\
+             def main(name: String, n: Int) -> Bool {\n    \
+             let message = GREETING + name;\n    \
+             is_even(n) && message != 'hello, world' || n > 100\n}\n\
+             def is_even(n: Int) -> Bool {\n    n == 0 || is_odd(n - 1)\n}\n\
+             def is_odd(n: Int) -> Bool {\n    n != 0 && is_even(n - 1)\n}",
+            true,
+            expect![[r#"
+                (a
+                  (q . (a 4 1))
+                  (c
+                    (q .
+                      (
+                        ; def main
+                        (a
+                          (q .
+                            (a
+                              (i
+                                (a
+                                  (i
+                                    (a 21 (c 5 (c 23 ())))
+                                    (q . (not (= 2 (q . "hello, world"))))
+                                    ())
+                                  1)
+                                (q . (q . 1))
+                                (q . (> 23 (q . 100))))
+                              1))
+                          (c (concat (q . "hello, ") 5) 1))
+                        ; def is_even
+                        (a (i (= 5 ()) (q . (q . 1)) (q . (a 14 (c 2 (c (- 5 (q . 1)) ()))))) 1)
+                        .
+                        ; def is_odd
+                        (a (i (not (= 5 ())) (q . (a 10 (c 2 (c (- 5 (q . 1)) ())))) ()) 1)))
+                    1))
+            "#]],
+        );
+    }
+
+    #[test]
+    fn data() {
+        let node = Node::list([
+            Node::atom(b"text"),
+            Node::pair(Node::atom(&[1, 2, 3, 4, 5]), Node::int(&BigInt::from(-7))),
+            Node::quote(Node::nil()),
+            Node::nil(),
+        ]);
+
+        assert_eq!(
+            pretty(&node, &HashMap::new()),
+            "(\"text\" (0x0102030405 . -7) (q . ()) ())\n"
+        );
+    }
+}
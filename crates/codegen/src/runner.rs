@@ -0,0 +1,618 @@
+use std::fmt;
+
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::{path_cost, traverse_path, Node, Op, Program, MAX_SHIFT};
+
+/// How many pending operations and intermediate values there can be at once, to stop programs
+/// which recurse forever.
+const MAX_STACK: usize = 1 << 20;
+
+/// How many operations and path lookups a program can run by default, to stop programs which
+/// loop forever without growing the stack, since tail calls don't.
+const MAX_STEPS: u64 = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalError {
+    pub kind: EvalErrorKind,
+    /// The program or arguments which caused the error.
+    pub node: Node,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalErrorKind {
+    PathIntoAtom,
+    PairAsOperator,
+    UnknownOperator,
+    ArgCount {
+        op: Op,
+    },
+    ExpectedAtom {
+        op: Op,
+    },
+    ExpectedPair {
+        op: Op,
+    },
+    DivisionByZero,
+    ShiftOutOfRange,
+    OutOfBounds,
+    /// The program failed on purpose with `x`.
+    Raise,
+    StackOverflow,
+    TooManySteps {
+        max_steps: u64,
+    },
+    CostExceeded {
+        max_cost: u64,
+    },
+}
+
+impl fmt::Display for EvalErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PathIntoAtom => write!(f, "path into atom"),
+            Self::PairAsOperator => write!(f, "an operator can't be a pair"),
+            Self::UnknownOperator => write!(f, "unknown operator"),
+            Self::ArgCount { op } => write!(f, "wrong number of arguments to `{}`", op.name()),
+            Self::ExpectedAtom { op } => write!(f, "`{}` expects atoms", op.name()),
+            Self::ExpectedPair { op } => write!(f, "`{}` expects a pair", op.name()),
+            Self::DivisionByZero => write!(f, "division by zero"),
+            Self::ShiftOutOfRange => write!(f, "shift amount out of range"),
+            Self::OutOfBounds => write!(f, "substring out of bounds"),
+            Self::Raise => write!(f, "raised an error"),
+            Self::StackOverflow => write!(f, "stack overflow"),
+            Self::TooManySteps { max_steps } => write!(f, "ran for more than {max_steps} steps"),
+            Self::CostExceeded { max_cost } => write!(f, "cost exceeded the limit of {max_cost}"),
+        }
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.node)
+    }
+}
+
+enum Task {
+    Eval { program: Node, env: Node },
+    Apply { op: Op, argc: usize },
+}
+
+/// The value a program evaluated to, and how much it cost to run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evaluation {
+    pub value: Node,
+    pub cost: u64,
+}
+
+/// Runs a CLVM program with an environment, and returns the value it evaluates to.
+///
+/// An atom is a path into the environment, and a list applies the operator in its first item to
+/// the values of the rest, except for `q` which returns the rest as is. Operators are checked
+/// strictly, so unknown opcodes are an error rather than doing nothing. Programs which run for
+/// too long fail instead of looping forever.
+pub(crate) fn run_program(program: &Node, env: &Node) -> Result<Node, EvalError> {
+    run_with_cost(program, env, u64::MAX).map(|evaluation| evaluation.value)
+}
+
+/// Runs a program like [`run_program`], and adds up the cost of every operator and path lookup
+/// along the way. The program fails once the cost goes over the limit.
+pub(crate) fn run_with_cost(
+    program: &Node,
+    env: &Node,
+    max_cost: u64,
+) -> Result<Evaluation, EvalError> {
+    run_with_limits(program, env, max_cost, MAX_STEPS)
+}
+
+/// Runs a program like [`run_with_cost`], and also fails once it has evaluated more than
+/// `max_steps` operators and path lookups.
+pub(crate) fn run_with_limits(
+    program: &Node,
+    env: &Node,
+    max_cost: u64,
+    max_steps: u64,
+) -> Result<Evaluation, EvalError> {
+    let root = program;
+
+    // An explicit stack is used, so deeply nested programs can't overflow the call stack.
+    let mut tasks = vec![Task::Eval {
+        program: program.clone(),
+        env: env.clone(),
+    }];
+    let mut values = Vec::new();
+    let mut cost = 0u64;
+    let mut steps = 0u64;
+
+    while let Some(task) = tasks.pop() {
+        if tasks.len() + values.len() > MAX_STACK {
+            return Err(error(EvalErrorKind::StackOverflow, root.clone()));
+        }
+
+        steps += 1;
+        if steps > max_steps {
+            return Err(error(
+                EvalErrorKind::TooManySteps { max_steps },
+                root.clone(),
+            ));
+        }
+
+        if cost > max_cost {
+            return Err(error(
+                EvalErrorKind::CostExceeded { max_cost },
+                root.clone(),
+            ));
+        }
+
+        match task {
+            Task::Eval { program, env } => {
+                let (op, args) = match &program {
+                    Node::Atom(path) => {
+                        let value = traverse_path(path, &env)
+                            .ok_or_else(|| error(EvalErrorKind::PathIntoAtom, program.clone()))?;
+                        cost = cost.saturating_add(path_cost(path));
+                        values.push(value);
+                        continue;
+                    }
+                    Node::Pair(op, args) => (op, args),
+                };
+
+                let Some(atom) = op.as_atom() else {
+                    return Err(error(EvalErrorKind::PairAsOperator, program.clone()));
+                };
+
+                let op = Op::from_atom(atom)
+                    .ok_or_else(|| error(EvalErrorKind::UnknownOperator, program.clone()))?;
+
+                if op == Op::Quote {
+                    cost = cost.saturating_add(op.cost().base);
+                    values.push(Node::clone(args));
+                    continue;
+                }
+
+                let args = args.iter().collect::<Vec<_>>();
+                tasks.push(Task::Apply {
+                    op,
+                    argc: args.len(),
+                });
+
+                // The arguments are pushed in reverse, so they are evaluated from left to right.
+                for arg in args.into_iter().rev() {
+                    tasks.push(Task::Eval {
+                        program: arg.clone(),
+                        env: env.clone(),
+                    });
+                }
+            }
+            Task::Apply { op, argc } => {
+                let args = values.split_off(values.len() - argc);
+                let arg_bytes = args
+                    .iter()
+                    .map(|arg| arg.as_atom().map_or(0, <[u8]>::len))
+                    .collect::<Vec<_>>();
+
+                if op == Op::Apply {
+                    cost = cost.saturating_add(op.cost().total(&arg_bytes, 0));
+                    let [program, env] = expect_args(op, args)?;
+                    tasks.push(Task::Eval { program, env });
+                } else {
+                    let value = apply_op(op, args)?;
+                    cost = cost.saturating_add(op.cost().total(&arg_bytes, atom_bytes(&value)));
+                    values.push(value);
+                }
+            }
+        }
+    }
+
+    if cost > max_cost {
+        return Err(error(
+            EvalErrorKind::CostExceeded { max_cost },
+            root.clone(),
+        ));
+    }
+
+    Ok(Evaluation {
+        value: values.pop().expect("the program produces a value"),
+        cost,
+    })
+}
+
+impl Program {
+    pub(crate) fn run(&self, args: impl IntoIterator<Item = Node>) -> Result<Node, EvalError> {
+        let args = args.into_iter().collect::<Vec<_>>();
+        run_program(&self.node, &Node::list(args))
+    }
+
+    /// Runs the program, and fails if it costs more than the limit.
+    pub(crate) fn run_with_cost(
+        &self,
+        args: impl IntoIterator<Item = Node>,
+        max_cost: u64,
+    ) -> Result<Evaluation, EvalError> {
+        let args = args.into_iter().collect::<Vec<_>>();
+        run_with_cost(&self.node, &Node::list(args), max_cost)
+    }
+
+    /// Runs the program, and fails if it costs more than the limit or takes too many steps.
+    pub(crate) fn run_with_limits(
+        &self,
+        args: impl IntoIterator<Item = Node>,
+        max_cost: u64,
+        max_steps: u64,
+    ) -> Result<Evaluation, EvalError> {
+        let args = args.into_iter().collect::<Vec<_>>();
+        run_with_limits(&self.node, &Node::list(args), max_cost, max_steps)
+    }
+}
+
+/// The number of bytes in every atom in a node, which is what an operator allocates.
+fn atom_bytes(node: &Node) -> usize {
+    match node {
+        Node::Atom(bytes) => bytes.len(),
+        Node::Pair(first, rest) => atom_bytes(first) + atom_bytes(rest),
+    }
+}
+
+fn error(kind: EvalErrorKind, node: Node) -> EvalError {
+    EvalError { kind, node }
+}
+
+fn args_error(kind: EvalErrorKind, args: &[Node]) -> EvalError {
+    error(kind, Node::list(args.iter().cloned()))
+}
+
+fn expect_args<const N: usize>(op: Op, args: Vec<Node>) -> Result<[Node; N], EvalError> {
+    args.try_into()
+        .map_err(|args: Vec<Node>| args_error(EvalErrorKind::ArgCount { op }, &args))
+}
+
+fn atom(op: Op, node: &Node) -> Result<&[u8], EvalError> {
+    node.as_atom()
+        .ok_or_else(|| error(EvalErrorKind::ExpectedAtom { op }, node.clone()))
+}
+
+fn int(op: Op, node: &Node) -> Result<BigInt, EvalError> {
+    atom(op, node).map(BigInt::from_signed_bytes_be)
+}
+
+fn ints(op: Op, args: &[Node]) -> Result<Vec<BigInt>, EvalError> {
+    args.iter().map(|arg| int(op, arg)).collect()
+}
+
+fn shift_amount(op: Op, node: &Node) -> Result<i64, EvalError> {
+    int(op, node)?
+        .to_i64()
+        .filter(|amount| amount.abs() <= MAX_SHIFT)
+        .ok_or_else(|| error(EvalErrorKind::ShiftOutOfRange, node.clone()))
+}
+
+fn shift(value: BigInt, amount: i64) -> BigInt {
+    if amount >= 0 {
+        value << amount as usize
+    } else {
+        value >> amount.unsigned_abs() as usize
+    }
+}
+
+fn apply_op(op: Op, args: Vec<Node>) -> Result<Node, EvalError> {
+    let value = match op {
+        Op::Quote | Op::Apply => unreachable!("`q` and `a` are handled by the evaluator"),
+        Op::If => {
+            let [condition, then, otherwise] = expect_args(op, args)?;
+            if condition.is_nil() {
+                otherwise
+            } else {
+                then
+            }
+        }
+        Op::Cons => {
+            let [first, rest] = expect_args(op, args)?;
+            Node::pair(first, rest)
+        }
+        Op::First | Op::Rest => {
+            let [node] = expect_args(op, args)?;
+            let (first, rest) = node
+                .as_pair()
+                .ok_or_else(|| error(EvalErrorKind::ExpectedPair { op }, node.clone()))?;
+            if op == Op::First {
+                first.clone()
+            } else {
+                rest.clone()
+            }
+        }
+        Op::Listp => {
+            let [node] = expect_args(op, args)?;
+            Node::bool(node.as_pair().is_some())
+        }
+        Op::Raise => return Err(args_error(EvalErrorKind::Raise, &args)),
+        Op::Eq => {
+            let [lhs, rhs] = expect_args(op, args)?;
+            Node::bool(atom(op, &lhs)? == atom(op, &rhs)?)
+        }
+        Op::GtBytes => {
+            let [lhs, rhs] = expect_args(op, args)?;
+            Node::bool(atom(op, &lhs)? > atom(op, &rhs)?)
+        }
+        Op::Substr => {
+            if !matches!(args.len(), 2 | 3) {
+                return Err(args_error(EvalErrorKind::ArgCount { op }, &args));
+            }
+
+            let bytes = atom(op, &args[0])?;
+            let start = int(op, &args[1])?;
+            let end = match args.get(2) {
+                Some(end) => int(op, end)?,
+                None => BigInt::from(bytes.len()),
+            };
+
+            let range = start
+                .to_usize()
+                .zip(end.to_usize())
+                .filter(|&(start, end)| start <= end && end <= bytes.len())
+                .ok_or_else(|| args_error(EvalErrorKind::OutOfBounds, &args))?;
+
+            Node::atom(&bytes[range.0..range.1])
+        }
+        Op::Strlen => {
+            let [node] = expect_args(op, args)?;
+            Node::int(&BigInt::from(atom(op, &node)?.len()))
+        }
+        Op::Concat => {
+            let mut bytes = Vec::new();
+            for arg in &args {
+                bytes.extend_from_slice(atom(op, arg)?);
+            }
+            Node::atom(&bytes)
+        }
+        Op::Add => Node::int(&ints(op, &args)?.into_iter().sum()),
+        Op::Sub => {
+            let mut values = ints(op, &args)?.into_iter();
+            let first = values.next().unwrap_or_default();
+            Node::int(&values.fold(first, |total, value| total - value))
+        }
+        Op::Mul => Node::int(&ints(op, &args)?.into_iter().product()),
+        Op::Div | Op::Divmod => {
+            let [lhs, rhs] = expect_args(op, args)?;
+            let (lhs, rhs) = (int(op, &lhs)?, int(op, &rhs)?);
+
+            if rhs.is_zero() {
+                return Err(error(EvalErrorKind::DivisionByZero, Node::int(&lhs)));
+            }
+
+            let (quotient, remainder) = lhs.div_mod_floor(&rhs);
+
+            if op == Op::Div {
+                Node::int(&quotient)
+            } else {
+                Node::pair(Node::int(&quotient), Node::int(&remainder))
+            }
+        }
+        Op::Gt => {
+            let [lhs, rhs] = expect_args(op, args)?;
+            Node::bool(int(op, &lhs)? > int(op, &rhs)?)
+        }
+        Op::Ash => {
+            let [value, amount] = expect_args(op, args)?;
+            Node::int(&shift(int(op, &value)?, shift_amount(op, &amount)?))
+        }
+        Op::Lsh => {
+            let [value, amount] = expect_args(op, args)?;
+            let value = BigInt::from_bytes_be(Sign::Plus, atom(op, &value)?);
+            Node::int(&shift(value, shift_amount(op, &amount)?))
+        }
+        Op::Logand => Node::int(
+            &ints(op, &args)?
+                .into_iter()
+                .fold(-BigInt::one(), |total, value| total & value),
+        ),
+        Op::Logior => Node::int(
+            &ints(op, &args)?
+                .into_iter()
+                .fold(BigInt::zero(), |total, value| total | value),
+        ),
+        Op::Logxor => Node::int(
+            &ints(op, &args)?
+                .into_iter()
+                .fold(BigInt::zero(), |total, value| total ^ value),
+        ),
+        Op::Lognot => {
+            let [value] = expect_args(op, args)?;
+            Node::int(&!int(op, &value)?)
+        }
+        Op::Not => {
+            let [value] = expect_args(op, args)?;
+            Node::bool(value.is_nil())
+        }
+        Op::Any => Node::bool(args.iter().any(|arg| !arg.is_nil())),
+        Op::All => Node::bool(args.iter().all(|arg| !arg.is_nil())),
+    };
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deserialize, from_hex};
+
+    fn int(value: i64) -> Node {
+        Node::int(&BigInt::from(value))
+    }
+
+    fn run(program: &Node, env: &Node) -> String {
+        match run_program(program, env) {
+            Ok(value) => value.to_string(),
+            Err(error) => format!("error: {error}"),
+        }
+    }
+
+    #[test]
+    fn paths() {
+        let env = Node::list([int(10), int(20), Node::pair(int(30), int(40))]);
+
+        assert_eq!(run(&Node::nil(), &env), "()");
+        assert_eq!(run(&int(1), &env), "(10 20 (30 . 40))");
+        assert_eq!(run(&int(2), &env), "10");
+        assert_eq!(run(&int(5), &env), "20");
+        assert_eq!(run(&int(11), &env), "(30 . 40)");
+        assert_eq!(run(&int(27), &env), "40");
+        assert_eq!(run(&Node::atom(&[0, 0, 2]), &env), "10");
+        assert_eq!(run(&int(4), &env), "error: path into atom: 4");
+    }
+
+    #[test]
+    fn arithmetic() {
+        let env = Node::list([int(7), int(-2)]);
+
+        assert_eq!(
+            run(
+                &Node::op(Op::Add, [int(2), int(5), Node::quote(int(100))]),
+                &env
+            ),
+            "105"
+        );
+        assert_eq!(run(&Node::op(Op::Sub, [int(2)]), &env), "7");
+        assert_eq!(
+            run(&Node::op(Op::Sub, [int(2), int(5), int(5)]), &env),
+            "11"
+        );
+        assert_eq!(run(&Node::op(Op::Mul, []), &env), "1");
+        assert_eq!(run(&Node::op(Op::Div, [int(2), int(5)]), &env), "-4");
+        assert_eq!(
+            run(&Node::op(Op::Divmod, [int(2), int(5)]), &env),
+            "(-4 . -1)"
+        );
+        assert_eq!(
+            run(&Node::op(Op::Ash, [int(2), Node::quote(int(3))]), &env),
+            "56"
+        );
+        assert_eq!(
+            run(&Node::op(Op::Ash, [int(5), Node::quote(int(-1))]), &env),
+            "-1"
+        );
+        assert_eq!(
+            run(&Node::op(Op::Lsh, [int(5), Node::quote(int(1))]), &env),
+            "508"
+        );
+        assert_eq!(
+            run(&Node::op(Op::Logand, [int(2), Node::quote(int(3))]), &env),
+            "3"
+        );
+        assert_eq!(run(&Node::op(Op::Lognot, [int(2)]), &env), "-8");
+        assert_eq!(run(&Node::op(Op::Gt, [int(2), int(5)]), &env), "1");
+        assert_eq!(
+            run(&Node::op(Op::Div, [int(2), Node::quote(Node::nil())]), &env),
+            "error: division by zero: 7"
+        );
+        assert_eq!(
+            run(&Node::op(Op::Ash, [int(2), Node::quote(int(65536))]), &env),
+            "error: shift amount out of range: 65536"
+        );
+    }
+
+    #[test]
+    fn apply_and_if() {
+        // (a (i 2 (q . (q . "yes")) (q . (x 5))) 1)
+        let program = Node::op(
+            Op::Apply,
+            [
+                Node::op(
+                    Op::If,
+                    [
+                        int(2),
+                        Node::quote(Node::quote(Node::atom(b"yes"))),
+                        Node::quote(Node::op(Op::Raise, [int(5)])),
+                    ],
+                ),
+                int(1),
+            ],
+        );
+
+        assert_eq!(run(&program, &Node::list([int(1), int(9)])), "\"yes\"");
+        assert_eq!(
+            run(&program, &Node::list([Node::nil(), int(9)])),
+            "error: raised an error: (9)"
+        );
+    }
+
+    #[test]
+    fn bytes() {
+        let env = Node::list([Node::atom(b"hello"), Node::atom(b" world")]);
+
+        assert_eq!(
+            run(&Node::op(Op::Concat, [int(2), int(5)]), &env),
+            "\"hello world\""
+        );
+        assert_eq!(run(&Node::op(Op::Strlen, [int(2)]), &env), "5");
+        assert_eq!(
+            run(
+                &Node::op(
+                    Op::Substr,
+                    [int(2), Node::quote(int(1)), Node::quote(int(4))]
+                ),
+                &env
+            ),
+            "\"ell\""
+        );
+        assert_eq!(run(&Node::op(Op::Eq, [int(2), int(2)]), &env), "1");
+        assert_eq!(run(&Node::op(Op::GtBytes, [int(2), int(5)]), &env), "1");
+        assert_eq!(
+            run(
+                &Node::op(
+                    Op::Substr,
+                    [int(2), Node::quote(int(4)), Node::quote(int(9))]
+                ),
+                &env
+            ),
+            "error: substring out of bounds: (\"hello\" 4 9)"
+        );
+    }
+
+    #[test]
+    fn serialized_program() {
+        // (+ 2 (q . 5)), which adds 5 to the first argument.
+        let program = deserialize(&from_hex("ff10ff02ffff010580").unwrap()).unwrap();
+
+        assert_eq!(program.to_string(), "(16 2 (1 . 5))");
+        assert_eq!(run(&program, &Node::list([int(37)])), "42");
+    }
+
+    #[test]
+    fn invalid_programs() {
+        let env = Node::nil();
+
+        assert_eq!(
+            run(&Node::pair(Node::list([int(1)]), Node::nil()), &env),
+            "error: an operator can't be a pair: ((1))"
+        );
+        assert_eq!(
+            run(&Node::op(Op::Cons, [int(1)]), &env),
+            "error: wrong number of arguments to `c`: (())"
+        );
+        assert_eq!(
+            run(&Node::list([int(99)]), &env),
+            "error: unknown operator: (99)"
+        );
+        assert_eq!(
+            run(&Node::op(Op::First, [Node::quote(int(1))]), &env),
+            "error: `f` expects a pair: 1"
+        );
+    }
+
+    #[test]
+    fn infinite_recursion() {
+        // (+ (a 1 1)), run with itself as the environment.
+        let program = Node::op(Op::Add, [Node::op(Op::Apply, [int(1), int(1)])]);
+        let program = Node::op(
+            Op::Apply,
+            [Node::quote(program.clone()), Node::quote(program)],
+        );
+
+        assert_eq!(
+            run_with_limits(&program, &Node::nil(), u64::MAX, u64::MAX).map_err(|error| error.kind),
+            Err(EvalErrorKind::StackOverflow)
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use std::fmt;
+
+use crate::Node;
+
+/// The byte which starts a pair, followed by its first and rest.
+const PAIR: u8 = 0xff;
+
+/// The byte for nil, which is an atom of length zero.
+const NIL: u8 = 0x80;
+
+/// Writes a node in the standard CLVM binary format.
+///
+/// Pairs are written as `0xff` followed by both halves. Atoms of a single byte up to `0x7f` are
+/// written as is, and other atoms are prefixed with their length.
+pub fn serialize(node: &Node) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut stack = vec![node];
+
+    // An explicit stack is used, since lists can be much longer than the call stack is deep.
+    while let Some(node) = stack.pop() {
+        match node {
+            Node::Atom(bytes) => write_atom(&mut out, bytes),
+            Node::Pair(first, rest) => {
+                out.push(PAIR);
+                stack.push(rest);
+                stack.push(first);
+            }
+        }
+    }
+
+    out
+}
+
+fn write_atom(out: &mut Vec<u8>, bytes: &[u8]) {
+    let len = bytes.len() as u64;
+
+    match bytes {
+        [] => return out.push(NIL),
+        [byte] if *byte <= 0x7f => return out.push(*byte),
+        _ if len < 0x40 => out.push(0x80 | len as u8),
+        _ if len < 0x2000 => out.extend([0xc0 | (len >> 8) as u8, len as u8]),
+        _ if len < 0x10_0000 => {
+            out.extend([0xe0 | (len >> 16) as u8, (len >> 8) as u8, len as u8]);
+        }
+        _ if len < 0x800_0000 => out.extend([
+            0xf0 | (len >> 24) as u8,
+            (len >> 16) as u8,
+            (len >> 8) as u8,
+            len as u8,
+        ]),
+        _ => out.extend([
+            0xf8 | (len >> 32) as u8,
+            (len >> 24) as u8,
+            (len >> 16) as u8,
+            (len >> 8) as u8,
+            len as u8,
+        ]),
+    }
+
+    out.extend_from_slice(bytes);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeserializeError {
+    UnexpectedEnd,
+    InvalidLengthPrefix,
+    TrailingBytes,
+    InvalidHex,
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "unexpected end of input"),
+            Self::InvalidLengthPrefix => write!(f, "invalid atom length prefix"),
+            Self::TrailingBytes => write!(f, "unexpected bytes after the end of the program"),
+            Self::InvalidHex => write!(f, "invalid hex"),
+        }
+    }
+}
+
+/// Reads a node in the standard CLVM binary format, which must take up all of the input.
+pub fn deserialize(bytes: &[u8]) -> Result<Node, DeserializeError> {
+    enum Task {
+        Parse,
+        Cons,
+    }
+
+    let mut input = bytes;
+    let mut tasks = vec![Task::Parse];
+    let mut values = Vec::new();
+
+    while let Some(task) = tasks.pop() {
+        match task {
+            Task::Parse => {
+                let (&byte, rest) = input.split_first().ok_or(DeserializeError::UnexpectedEnd)?;
+                input = rest;
+
+                if byte == PAIR {
+                    tasks.extend([Task::Cons, Task::Parse, Task::Parse]);
+                } else {
+                    values.push(read_atom(byte, &mut input)?);
+                }
+            }
+            Task::Cons => {
+                let rest = values.pop().unwrap();
+                let first = values.pop().unwrap();
+                values.push(Node::pair(first, rest));
+            }
+        }
+    }
+
+    if !input.is_empty() {
+        return Err(DeserializeError::TrailingBytes);
+    }
+
+    Ok(values.pop().unwrap())
+}
+
+fn read_atom(byte: u8, input: &mut &[u8]) -> Result<Node, DeserializeError> {
+    if byte <= 0x7f {
+        return Ok(Node::atom(&[byte]));
+    }
+
+    // The number of leading ones is the number of bytes in the length prefix.
+    let prefix_len = byte.leading_ones() as usize;
+
+    if prefix_len > 5 {
+        return Err(DeserializeError::InvalidLengthPrefix);
+    }
+
+    let (prefix, rest) = input
+        .split_at_checked(prefix_len - 1)
+        .ok_or(DeserializeError::UnexpectedEnd)?;
+
+    let len = prefix.iter().fold(
+        u64::from(byte & (0xff >> (prefix_len + 1))),
+        |len, &byte| len << 8 | u64::from(byte),
+    );
+
+    let (atom, rest) = usize::try_from(len)
+        .ok()
+        .and_then(|len| rest.split_at_checked(len))
+        .ok_or(DeserializeError::UnexpectedEnd)?;
+    *input = rest;
+
+    Ok(Node::atom(atom))
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub fn from_hex(hex: &str) -> Result<Vec<u8>, DeserializeError> {
+    let hex = hex.trim();
+
+    if !hex.len().is_multiple_of(2) {
+        return Err(DeserializeError::InvalidHex);
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                .ok_or(DeserializeError::InvalidHex)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigInt;
+
+    use super::*;
+
+    fn hex(node: &Node) -> String {
+        to_hex(&serialize(node))
+    }
+
+    #[test]
+    fn atoms() {
+        assert_eq!(hex(&Node::nil()), "80");
+        assert_eq!(hex(&Node::atom(&[0x7f])), "7f");
+        assert_eq!(hex(&Node::atom(&[0x80])), "8180");
+        assert_eq!(hex(&Node::atom(&[0])), "00");
+        assert_eq!(hex(&Node::atom(b"hello")), "8568656c6c6f");
+        assert_eq!(hex(&Node::int(&BigInt::from(-1))), "81ff");
+
+        let long = vec![7; 0x40];
+        assert_eq!(&hex(&Node::atom(&long))[..6], "c04007");
+
+        let longer = vec![7; 0x2000];
+        assert_eq!(&hex(&Node::atom(&longer))[..8], "e0200007");
+    }
+
+    #[test]
+    fn pairs() {
+        let node = Node::list([
+            Node::atom(&[16]),
+            Node::pair(Node::atom(&[1]), Node::atom(&[2])),
+            Node::pair(Node::atom(&[1]), Node::atom(&[3])),
+        ]);
+
+        assert_eq!(hex(&node), "ff10ffff0102ffff010380");
+    }
+
+    #[test]
+    fn roundtrip() {
+        let node = Node::list([
+            Node::atom(&[]),
+            Node::atom(&vec![0xab; 300]),
+            Node::pair(Node::atom(b"rue"), Node::int(&BigInt::from(-1000))),
+            Node::list((0..1000).map(|i| Node::int(&BigInt::from(i)))),
+        ]);
+
+        let bytes = serialize(&node);
+        assert_eq!(deserialize(&bytes), Ok(node));
+        assert_eq!(from_hex(&to_hex(&bytes)), Ok(bytes));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(deserialize(&[]), Err(DeserializeError::UnexpectedEnd));
+        assert_eq!(
+            deserialize(&[0xff, 0x01]),
+            Err(DeserializeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            deserialize(&[0x83, 1, 2]),
+            Err(DeserializeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            deserialize(&[0xfe]),
+            Err(DeserializeError::InvalidLengthPrefix)
+        );
+        assert_eq!(
+            deserialize(&[0x80, 0x80]),
+            Err(DeserializeError::TrailingBytes)
+        );
+        assert_eq!(from_hex("ff0"), Err(DeserializeError::InvalidHex));
+        assert_eq!(from_hex("zz"), Err(DeserializeError::InvalidHex));
+    }
+}
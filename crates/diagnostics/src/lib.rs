@@ -0,0 +1,9 @@
+mod diagnostic;
+mod render;
+mod severity;
+mod source_file;
+
+pub use diagnostic::{Diagnostic, Label};
+pub use render::render;
+pub use severity::Severity;
+pub use source_file::SourceFile;
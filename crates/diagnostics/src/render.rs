@@ -0,0 +1,190 @@
+use std::fmt::Write;
+
+use crate::{Diagnostic, Label, SourceFile};
+
+/// Renders a diagnostic in the style of rustc, with the offending source lines underlined.
+///
+/// ```text
+/// error[E0001]: expected a name
+///  --> main.rue:2:9
+///   |
+/// 2 |     let = 5;
+///   |         ^ expected a name
+///   |
+///   = help: add a name to the binding
+/// ```
+pub fn render(diagnostic: &Diagnostic, file: &SourceFile) -> String {
+    let mut out = String::new();
+
+    match &diagnostic.code {
+        Some(code) => writeln!(
+            out,
+            "{}[{}]: {}",
+            diagnostic.severity, code, diagnostic.message
+        ),
+        None => writeln!(out, "{}: {}", diagnostic.severity, diagnostic.message),
+    }
+    .unwrap();
+
+    let mut labels = vec![(&diagnostic.primary, '^')];
+    labels.extend(diagnostic.secondary.iter().map(|label| (label, '-')));
+
+    let mut lines = labels
+        .iter()
+        .map(|(label, _)| file.line_index.line_col(label.span.start()).line)
+        .collect::<Vec<_>>();
+    lines.sort_unstable();
+    lines.dedup();
+
+    let width = (lines.last().copied().unwrap_or(0) + 1).to_string().len();
+    let gutter = " ".repeat(width);
+
+    let start = file.line_index.line_col(diagnostic.primary.span.start());
+    writeln!(
+        out,
+        "{gutter}--> {}:{}:{}",
+        file.name,
+        start.line + 1,
+        start.col + 1
+    )
+    .unwrap();
+    writeln!(out, "{gutter} |").unwrap();
+
+    let mut previous_line = None;
+
+    for line in lines {
+        if previous_line.is_some_and(|previous| line > previous + 1) {
+            writeln!(out, "{gutter}...").unwrap();
+        }
+        previous_line = Some(line);
+
+        let Some(range) = file.line_index.line_range(line) else {
+            continue;
+        };
+        let text = &file.text[range];
+        let text = text.strip_suffix('\r').unwrap_or(text);
+
+        writeln!(out, "{:>width$} | {}", line + 1, text).unwrap();
+
+        let mut line_labels = labels
+            .iter()
+            .filter(|(label, _)| file.line_index.line_col(label.span.start()).line == line)
+            .collect::<Vec<_>>();
+        line_labels.sort_by_key(|(label, mark)| (*mark != '^', label.span.start()));
+
+        for (label, mark) in line_labels {
+            let underline = underline(file, line, text, label, *mark);
+            writeln!(out, "{gutter} | {}", underline.trim_end()).unwrap();
+        }
+    }
+
+    if !diagnostic.notes.is_empty() || !diagnostic.help.is_empty() {
+        writeln!(out, "{gutter} |").unwrap();
+    }
+
+    for note in &diagnostic.notes {
+        writeln!(out, "{gutter} = note: {note}").unwrap();
+    }
+
+    for help in &diagnostic.help {
+        writeln!(out, "{gutter} = help: {help}").unwrap();
+    }
+
+    out
+}
+
+fn underline(file: &SourceFile, line: u32, text: &str, label: &Label, mark: char) -> String {
+    let line_start = file.line_index.line_range(line).unwrap().start();
+    let start = usize::from(label.span.start() - line_start).min(text.len());
+    let end = usize::from(label.span.end().max(label.span.start()) - line_start).min(text.len());
+
+    let mut result = String::new();
+
+    for c in text[..start].chars() {
+        result.push(if c == '\t' { '\t' } else { ' ' });
+    }
+
+    let len = text[start..end].chars().count().max(1);
+    result.extend(std::iter::repeat_n(mark, len));
+
+    if !label.message.is_empty() {
+        result.push(' ');
+        result.push_str(&label.message);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use rowan::TextRange;
+
+    use super::*;
+
+    fn range(start: u32, end: u32) -> TextRange {
+        TextRange::new(start.into(), end.into())
+    }
+
+    #[test]
+    fn render_primary() {
+        let file = SourceFile::new("main.rue", "def main() {\n    let = 5;\n}");
+        let diagnostic = Diagnostic::error("expected a name", range(21, 22))
+            .with_code("E0001")
+            .with_primary_message("expected a name");
+
+        expect![[r#"
+            error[E0001]: expected a name
+             --> main.rue:2:9
+              |
+            2 |     let = 5;
+              |         ^ expected a name
+        "#]]
+        .assert_eq(&render(&diagnostic, &file));
+    }
+
+    #[test]
+    fn render_labels_and_notes() {
+        let file = SourceFile::new(
+            "main.rue",
+            "def main(a: Int, a: Int) {\n\n\n\n\n\n\n\n    let x = a;\n}",
+        );
+        let diagnostic = Diagnostic::warning("parameter `a` is shadowed", range(17, 18))
+            .with_primary_message("second definition")
+            .with_label(range(9, 10), "first definition")
+            .with_label(range(46, 47), "used here")
+            .with_note("later parameters shadow earlier ones")
+            .with_help("rename one of the parameters");
+
+        expect![[r#"
+            warning: parameter `a` is shadowed
+             --> main.rue:1:18
+              |
+            1 | def main(a: Int, a: Int) {
+              |                  ^ second definition
+              |          - first definition
+             ...
+            9 |     let x = a;
+              |             - used here
+              |
+              = note: later parameters shadow earlier ones
+              = help: rename one of the parameters
+        "#]]
+        .assert_eq(&render(&diagnostic, &file));
+    }
+
+    #[test]
+    fn render_end_of_file() {
+        let file = SourceFile::new("main.rue", "def main() {");
+        let diagnostic = Diagnostic::error("expected CloseBrace", range(12, 12));
+
+        expect![[r#"
+            error: expected CloseBrace
+             --> main.rue:1:13
+              |
+            1 | def main() {
+              |             ^
+        "#]]
+        .assert_eq(&render(&diagnostic, &file));
+    }
+}
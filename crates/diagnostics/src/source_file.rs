@@ -0,0 +1,17 @@
+use syntax::LineIndex;
+
+pub struct SourceFile<'a> {
+    pub name: &'a str,
+    pub text: &'a str,
+    pub line_index: LineIndex,
+}
+
+impl<'a> SourceFile<'a> {
+    pub fn new(name: &'a str, text: &'a str) -> Self {
+        Self {
+            name,
+            text,
+            line_index: LineIndex::new(text),
+        }
+    }
+}
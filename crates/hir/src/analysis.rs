@@ -0,0 +1,152 @@
+use rowan::TextSize;
+use syntax::{ast, SyntaxKind, SyntaxNode};
+
+use crate::{
+    check, const_eval, lower, resolve, ConstDiagnostic, ConstEval, Module, NameResolution,
+    ResolveDiagnostic, SourceMap, Ty, TypeCheck, TypeDiagnostic,
+};
+
+/// The results of every pass over a source file, for the compiler and tooling to query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    pub module: Module,
+    pub source_map: SourceMap,
+    pub resolution: NameResolution,
+    pub types: TypeCheck,
+    pub consts: ConstEval,
+    pub resolve_diagnostics: Vec<ResolveDiagnostic>,
+    pub type_diagnostics: Vec<TypeDiagnostic>,
+    pub const_diagnostics: Vec<ConstDiagnostic>,
+}
+
+impl Analysis {
+    pub fn new(root: &ast::Root) -> Self {
+        let (module, source_map) = lower(root);
+        let (resolution, resolve_diagnostics) = resolve(&module, &source_map);
+        let (types, type_diagnostics) = check(&module, &source_map, &resolution);
+        let (consts, const_diagnostics) = const_eval(&module, &source_map, &resolution);
+
+        Self {
+            module,
+            source_map,
+            resolution,
+            types,
+            consts,
+            resolve_diagnostics,
+            type_diagnostics,
+            const_diagnostics,
+        }
+    }
+
+    /// Every diagnostic from name resolution, type checking and constant evaluation,
+    /// sorted by position.
+    pub fn diagnostics(&self) -> Vec<diagnostics::Diagnostic> {
+        let mut diagnostics = self
+            .resolve_diagnostics
+            .iter()
+            .map(ResolveDiagnostic::to_diagnostic)
+            .chain(
+                self.type_diagnostics
+                    .iter()
+                    .map(TypeDiagnostic::to_diagnostic),
+            )
+            .chain(
+                self.const_diagnostics
+                    .iter()
+                    .map(ConstDiagnostic::to_diagnostic),
+            )
+            .collect::<Vec<_>>();
+
+        diagnostics.sort_by_key(|diagnostic| diagnostic.primary.span.start());
+        diagnostics
+    }
+
+    /// The type of an expression, parameter or `let` statement node.
+    pub fn type_of_node(&self, node: &SyntaxNode) -> Option<&Ty> {
+        let def_item = node
+            .ancestors()
+            .find(|node| matches!(node.kind(), SyntaxKind::DefItem | SyntaxKind::ConstItem))?;
+        let def = self.source_map.node_def(&def_item)?;
+        let source_map = self.source_map.body(def);
+        let types = self.types.body(def);
+
+        if let Some(expr) = source_map.node_expr(node) {
+            return Some(types.expr_ty(expr));
+        }
+
+        source_map
+            .node_local(node)
+            .map(|local| types.local_ty(local))
+    }
+
+    /// The type of the innermost expression or binding at an offset, such as for showing on hover.
+    pub fn type_at(&self, root: &SyntaxNode, offset: TextSize) -> Option<&Ty> {
+        let token = root.token_at_offset(offset).right_biased()?;
+        token
+            .parent_ancestors()
+            .find_map(|node| self.type_of_node(&node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parser::parse;
+
+    use super::*;
+
+    fn type_at(source: &str, marker: &str) -> Option<String> {
+        let parse = parse(source);
+        let analysis = Analysis::new(&parse.tree());
+        let offset = TextSize::from(source.find(marker).unwrap() as u32);
+
+        analysis
+            .type_at(&parse.syntax_node(), offset)
+            .map(ToString::to_string)
+    }
+
+    #[test]
+    fn infer_let_from_value() {
+        let source = "def main() -> Bool {\n    let x = 5;\n    let y = x > 1;\n    let _z: Int = y;\n    y\n}";
+        let parse = parse(source);
+        let analysis = Analysis::new(&parse.tree());
+
+        let messages = analysis
+            .diagnostics()
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect::<Vec<_>>();
+
+        assert_eq!(messages, ["mismatched types: expected `Int`, found `Bool`"]);
+    }
+
+    #[test]
+    fn uninitialized_let_is_reported() {
+        let source = "def main() -> Int {\n    let x;\n    let y = x;\n    y + 1\n}";
+        let parse = parse(source);
+        let analysis = Analysis::new(&parse.tree());
+
+        let messages = analysis
+            .diagnostics()
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect::<Vec<_>>();
+
+        assert_eq!(messages, ["`x` is used before it is initialized"]);
+        assert_eq!(type_at(source, "y =").as_deref(), Some("{unknown}"));
+    }
+
+    #[test]
+    fn hover_types() {
+        let source = "def main(a: Int) {\n    let s = 'hi' + 'there';\n    let n = a * 2;\n    let _b = n > a;\n    double;\n}\ndef double(x: Int) -> Int {\n    x * 2\n}";
+
+        assert_eq!(type_at(source, "s =").as_deref(), Some("String"));
+        assert_eq!(type_at(source, "n >").as_deref(), Some("Int"));
+        assert_eq!(type_at(source, "> a").as_deref(), Some("Bool"));
+        assert_eq!(type_at(source, "a:").as_deref(), Some("Int"));
+        assert_eq!(
+            type_at(source, "double;").as_deref(),
+            Some("fn(Int) -> Int")
+        );
+        assert_eq!(type_at(source, "def main"), None);
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
+
+/// An index into an [`Arena`], which is only valid for the arena it was allocated in.
+pub struct Idx<T> {
+    raw: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Idx<T> {
+    pub fn from_raw(raw: u32) -> Self {
+        Self {
+            raw,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn into_raw(self) -> u32 {
+        self.raw
+    }
+
+    fn index(self) -> usize {
+        self.raw as usize
+    }
+}
+
+// These are implemented by hand, since deriving them would require `T` to implement them too.
+impl<T> Clone for Idx<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Idx<T> {}
+
+impl<T> PartialEq for Idx<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<T> Eq for Idx<T> {}
+
+impl<T> PartialOrd for Idx<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Idx<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.raw.cmp(&other.raw)
+    }
+}
+
+impl<T> Hash for Idx<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.raw.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Idx<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.raw)
+    }
+}
+
+/// Owns values of a single type, which are referred to by their [`Idx`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arena<T> {
+    data: Vec<T>,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    pub fn alloc(&mut self, value: T) -> Idx<T> {
+        let idx = Idx::from_raw(self.data.len() as u32);
+        self.data.push(value);
+        idx
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Idx<T>, &T)> {
+        self.data
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (Idx::from_raw(index as u32), value))
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Index<Idx<T>> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, idx: Idx<T>) -> &T {
+        &self.data[idx.index()]
+    }
+}
+
+impl<T> IndexMut<Idx<T>> for Arena<T> {
+    fn index_mut(&mut self, idx: Idx<T>) -> &mut T {
+        &mut self.data[idx.index()]
+    }
+}
+
+/// Associates extra data with some of the values in an [`Arena`], without storing it inline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArenaMap<K, V> {
+    data: Vec<Option<V>>,
+    _marker: PhantomData<fn() -> K>,
+}
+
+impl<K, V> ArenaMap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn insert(&mut self, idx: Idx<K>, value: V) {
+        let index = idx.index();
+
+        if index >= self.data.len() {
+            self.data.resize_with(index + 1, || None);
+        }
+
+        self.data[index] = Some(value);
+    }
+
+    pub fn get(&self, idx: Idx<K>) -> Option<&V> {
+        self.data.get(idx.index())?.as_ref()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Idx<K>, &V)> {
+        self.data.iter().enumerate().filter_map(|(index, value)| {
+            value
+                .as_ref()
+                .map(|value| (Idx::from_raw(index as u32), value))
+        })
+    }
+}
+
+impl<K, V> Default for ArenaMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
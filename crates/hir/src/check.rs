@@ -0,0 +1,750 @@
+use std::fmt;
+
+use diagnostics::Diagnostic;
+use num_bigint::BigInt;
+use num_traits::{One, Zero};
+use rowan::TextRange;
+
+use crate::{
+    ArenaMap, BinaryOp, Body, BodyResolution, BodySourceMap, Def, DefId, DefKind, Expr, ExprId,
+    Literal, Local, LocalId, Module, Name, NameResolution, Resolution, SourceMap, Stmt, Ty,
+    TypeRef, UnaryOp,
+};
+
+/// The parameter and return types of a function.
+///
+/// A constant has a signature without any parameters, whose return type is the constant's type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub params: Vec<Ty>,
+    pub ret: Ty,
+}
+
+impl Signature {
+    pub fn ty(&self) -> Ty {
+        Ty::Fn {
+            params: self.params.clone(),
+            ret: Box::new(self.ret.clone()),
+        }
+    }
+}
+
+/// The types of every definition in a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeCheck {
+    signatures: ArenaMap<Def, Signature>,
+    bodies: ArenaMap<Def, BodyTypes>,
+}
+
+impl TypeCheck {
+    pub fn signature(&self, def: DefId) -> &Signature {
+        self.signatures.get(def).expect("every def has a signature")
+    }
+
+    pub fn body(&self, def: DefId) -> &BodyTypes {
+        self.bodies.get(def).expect("every def is checked")
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BodyTypes {
+    exprs: ArenaMap<Expr, Ty>,
+    locals: ArenaMap<Local, Ty>,
+}
+
+impl BodyTypes {
+    pub fn expr_ty(&self, expr: ExprId) -> &Ty {
+        self.exprs.get(expr).unwrap_or(&Ty::Unknown)
+    }
+
+    pub fn local_ty(&self, local: LocalId) -> &Ty {
+        self.locals.get(local).unwrap_or(&Ty::Unknown)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeDiagnostic {
+    pub kind: TypeDiagnosticKind,
+    pub span: TextRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeDiagnosticKind {
+    Mismatch {
+        expected: Ty,
+        found: Ty,
+    },
+    UnknownType {
+        name: Name,
+    },
+    InvalidBinary {
+        op: BinaryOp,
+        lhs: Ty,
+        rhs: Ty,
+    },
+    InvalidUnary {
+        op: UnaryOp,
+        ty: Ty,
+    },
+    NotCallable {
+        ty: Ty,
+    },
+    ArgCount {
+        expected: usize,
+        found: usize,
+    },
+    NoField {
+        ty: Ty,
+        name: Name,
+    },
+    InvalidTry {
+        ty: Ty,
+    },
+    /// An integer literal which doesn't fit in the type its suffix names.
+    OutOfRange {
+        suffix: String,
+        min: BigInt,
+        max: BigInt,
+    },
+    Uninitialized {
+        name: Name,
+    },
+}
+
+impl fmt::Display for TypeDiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mismatch { expected, found } => {
+                write!(
+                    f,
+                    "mismatched types: expected `{expected}`, found `{found}`"
+                )
+            }
+            Self::UnknownType { name } => write!(f, "cannot find type `{name}`"),
+            Self::InvalidBinary { op, lhs, rhs } => {
+                write!(f, "cannot apply `{}` to `{lhs}` and `{rhs}`", op.text())
+            }
+            Self::InvalidUnary { op, ty } => {
+                write!(f, "cannot apply unary `{}` to `{ty}`", op.text())
+            }
+            Self::NotCallable { ty } => write!(f, "`{ty}` is not a function"),
+            Self::ArgCount { expected, found } => write!(
+                f,
+                "this function takes {expected} argument{} but {found} {} supplied",
+                if *expected == 1 { "" } else { "s" },
+                if *found == 1 { "was" } else { "were" }
+            ),
+            Self::NoField { ty, name } => write!(f, "no field `{name}` on type `{ty}`"),
+            Self::InvalidTry { ty } => {
+                write!(f, "the `?` operator cannot be applied to `{ty}`")
+            }
+            Self::OutOfRange { suffix, .. } => write!(f, "literal out of range for `{suffix}`"),
+            Self::Uninitialized { name } => write!(f, "`{name}` is used before it is initialized"),
+        }
+    }
+}
+
+impl TypeDiagnostic {
+    pub fn message(&self) -> String {
+        self.kind.to_string()
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let diagnostic = Diagnostic::error(self.message(), self.span);
+
+        match &self.kind {
+            TypeDiagnosticKind::Mismatch { expected, .. } => {
+                diagnostic.with_primary_message(format!("expected `{expected}`"))
+            }
+            TypeDiagnosticKind::UnknownType { .. } => diagnostic
+                .with_primary_message("not found")
+                .with_help("the built-in types are `Int`, `Bool`, `String` and `Bytes`"),
+            TypeDiagnosticKind::OutOfRange { suffix, min, max } => {
+                diagnostic.with_note(format!("`{suffix}` holds values from {min} to {max}"))
+            }
+            TypeDiagnosticKind::Uninitialized { name } => diagnostic
+                .with_primary_message("used here")
+                .with_help(format!("give it a value with `let {name} = ...;`")),
+            _ => diagnostic,
+        }
+    }
+}
+
+/// Checks the types of every definition in a module.
+///
+/// Types flow from the leaves of each expression up to the root, and are then checked against
+/// the annotation or parameter type they are used for, if any. This is the only inference there
+/// is: an unannotated `let` gets the type of its value, and nothing is inferred from how a
+/// binding is used later on.
+///
+/// Each function is checked against the signatures of the others, so the bodies can be checked
+/// in any order. Expressions with errors get the unknown type, which is compatible with
+/// everything so that a single mistake is only reported once.
+pub fn check(
+    module: &Module,
+    source_map: &SourceMap,
+    resolution: &NameResolution,
+) -> (TypeCheck, Vec<TypeDiagnostic>) {
+    let mut diagnostics = Vec::new();
+    let mut signatures = ArenaMap::new();
+
+    for (id, def) in module.defs.iter() {
+        let signature = signature(def, source_map.body(id), &mut diagnostics);
+        signatures.insert(id, signature);
+    }
+
+    let mut bodies = ArenaMap::new();
+
+    for (id, def) in module.defs.iter() {
+        let mut checker = BodyChecker {
+            module,
+            kind: def.kind,
+            body: &def.body,
+            source_map: source_map.body(id),
+            resolution: resolution.body(id),
+            signatures: &signatures,
+            types: BodyTypes::default(),
+            uninitialized: Vec::new(),
+            diagnostics: &mut diagnostics,
+        };
+
+        checker.check_body(signatures.get(id).unwrap());
+        bodies.insert(id, checker.types);
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start());
+
+    (TypeCheck { signatures, bodies }, diagnostics)
+}
+
+fn signature(
+    def: &Def,
+    source_map: &BodySourceMap,
+    diagnostics: &mut Vec<TypeDiagnostic>,
+) -> Signature {
+    let params = def
+        .body
+        .params
+        .iter()
+        .map(|&param| {
+            let span = source_map.local_type_range(param);
+            match &def.body.locals[param].ty {
+                Some(ty) => resolve_type(ty, span, diagnostics),
+                None => Ty::Unknown,
+            }
+        })
+        .collect();
+
+    let ret = match (&def.return_type, def.kind) {
+        (Some(ty), _) => resolve_type(ty, source_map.return_type_range(), diagnostics),
+        (None, DefKind::Fn) => Ty::Unit,
+        // The parser has already reported the missing type.
+        (None, DefKind::Const) => Ty::Unknown,
+    };
+
+    Signature { params, ret }
+}
+
+fn resolve_type(
+    type_ref: &TypeRef,
+    span: Option<TextRange>,
+    diagnostics: &mut Vec<TypeDiagnostic>,
+) -> Ty {
+    if let Some(ty) = Ty::from_type_ref(type_ref) {
+        return ty;
+    }
+
+    if let (TypeRef::Name(name), Some(span)) = (type_ref, span) {
+        diagnostics.push(TypeDiagnostic {
+            kind: TypeDiagnosticKind::UnknownType { name: name.clone() },
+            span,
+        });
+    }
+
+    Ty::Unknown
+}
+
+struct BodyChecker<'a> {
+    module: &'a Module,
+    kind: DefKind,
+    body: &'a Body,
+    source_map: &'a BodySourceMap,
+    resolution: &'a BodyResolution,
+    signatures: &'a ArenaMap<Def, Signature>,
+    types: BodyTypes,
+    /// The `let` bindings without a value, which can't be used since nothing can assign them.
+    uninitialized: Vec<LocalId>,
+    diagnostics: &'a mut Vec<TypeDiagnostic>,
+}
+
+impl BodyChecker<'_> {
+    fn check_body(&mut self, signature: &Signature) {
+        for (&param, ty) in self.body.params.iter().zip(&signature.params) {
+            self.types.locals.insert(param, ty.clone());
+        }
+
+        for stmt in &self.body.stmts {
+            match stmt {
+                Stmt::Let { local, value } => self.check_let(*local, *value),
+                Stmt::Expr(expr) => {
+                    self.infer_expr(*expr);
+                }
+            }
+        }
+
+        match self.body.tail {
+            Some(tail) => {
+                self.check_expr(tail, &signature.ret);
+            }
+            // A constant without a value has already been reported by the parser.
+            None if self.kind == DefKind::Const => {}
+            None => {
+                if !Ty::Unit.is_assignable_to(&signature.ret) {
+                    if let Some(span) = self.source_map.return_type_range() {
+                        self.push(
+                            TypeDiagnosticKind::Mismatch {
+                                expected: signature.ret.clone(),
+                                found: Ty::Unit,
+                            },
+                            span,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks the value of a `let` statement against its annotation, or infers the type of the
+    /// binding from its value if there isn't one.
+    ///
+    /// A binding without either is unknown, and one without a value is an error wherever it's
+    /// used, since nothing else can give it a value.
+    fn check_let(&mut self, local: LocalId, value: Option<ExprId>) {
+        let annotation = self.body.locals[local].ty.as_ref().map(|ty| {
+            let span = self.source_map.local_type_range(local);
+            resolve_type(ty, span, self.diagnostics)
+        });
+
+        let ty = match (annotation, value) {
+            (Some(ty), Some(value)) => {
+                self.check_expr(value, &ty);
+                ty
+            }
+            (Some(ty), None) => ty,
+            (None, Some(value)) => self.infer_expr(value),
+            (None, None) => Ty::Unknown,
+        };
+
+        if value.is_none() {
+            self.uninitialized.push(local);
+        }
+
+        self.types.locals.insert(local, ty);
+    }
+
+    fn check_expr(&mut self, expr: ExprId, expected: &Ty) -> Ty {
+        let ty = self.infer_expr(expr);
+
+        if !ty.is_assignable_to(expected) {
+            self.push_at_expr(
+                TypeDiagnosticKind::Mismatch {
+                    expected: expected.clone(),
+                    found: ty.clone(),
+                },
+                expr,
+            );
+        }
+
+        ty
+    }
+
+    fn infer_expr(&mut self, expr: ExprId) -> Ty {
+        let ty = match &self.body.exprs[expr] {
+            Expr::Missing => Ty::Unknown,
+            Expr::Literal(literal) => match literal {
+                Literal::Bool(_) => Ty::Bool,
+                Literal::Int { value, suffix } => {
+                    if let Some(suffix) = suffix {
+                        self.check_range(value, suffix, expr);
+                    }
+                    Ty::Int
+                }
+                Literal::String(_) => Ty::String,
+            },
+            Expr::Name(name) => match self.resolution.resolve_expr(expr) {
+                Some(Resolution::Local(local)) => {
+                    if self.uninitialized.contains(&local) {
+                        let name = name.clone();
+                        self.push_at_expr(TypeDiagnosticKind::Uninitialized { name }, expr);
+                    }
+
+                    self.types.local_ty(local).clone()
+                }
+                Some(Resolution::Def(def)) => {
+                    let signature = self.signatures.get(def).unwrap();
+                    match self.module.defs[def].kind {
+                        DefKind::Fn => signature.ty(),
+                        DefKind::Const => signature.ret.clone(),
+                    }
+                }
+                None => Ty::Unknown,
+            },
+            Expr::Binary { op, lhs, rhs } => {
+                let (op, lhs, rhs) = (*op, *lhs, *rhs);
+                let lhs = self.infer_expr(lhs);
+                let rhs = self.infer_expr(rhs);
+
+                match binary_result(op, &lhs, &rhs) {
+                    Some(ty) => ty,
+                    None => {
+                        self.push_at_expr(TypeDiagnosticKind::InvalidBinary { op, lhs, rhs }, expr);
+                        Ty::Unknown
+                    }
+                }
+            }
+            Expr::Unary { op, expr: inner } => {
+                let (op, inner) = (*op, *inner);
+                let body = self.body;
+
+                // The sign counts towards the range, so `-128i8` fits even though `128i8` doesn't.
+                let ty = match (op, &body.exprs[inner]) {
+                    (
+                        UnaryOp::Neg,
+                        Expr::Literal(Literal::Int {
+                            value,
+                            suffix: Some(suffix),
+                        }),
+                    ) => {
+                        self.check_range(&-value, suffix, expr);
+                        self.types.exprs.insert(inner, Ty::Int);
+                        Ty::Int
+                    }
+                    _ => self.infer_expr(inner),
+                };
+
+                match (op, &ty) {
+                    (_, Ty::Unknown) => Ty::Unknown,
+                    (UnaryOp::Neg | UnaryOp::BitNot, Ty::Int) => Ty::Int,
+                    (UnaryOp::Not, Ty::Bool) => Ty::Bool,
+                    _ => {
+                        self.push_at_expr(TypeDiagnosticKind::InvalidUnary { op, ty }, expr);
+                        Ty::Unknown
+                    }
+                }
+            }
+            Expr::Call { callee, args } => self.infer_call(expr, *callee, args),
+            Expr::Field { expr: inner, name } => {
+                let ty = self.infer_expr(*inner);
+
+                if let (false, Some(name)) = (ty.is_unknown(), name) {
+                    let name = name.clone();
+                    self.push_at_expr(TypeDiagnosticKind::NoField { ty, name }, expr);
+                }
+
+                Ty::Unknown
+            }
+            Expr::Try { expr: inner } => {
+                let ty = self.infer_expr(*inner);
+
+                if !ty.is_unknown() {
+                    self.push_at_expr(TypeDiagnosticKind::InvalidTry { ty }, expr);
+                }
+
+                Ty::Unknown
+            }
+        };
+
+        self.types.exprs.insert(expr, ty.clone());
+        ty
+    }
+
+    fn infer_call(&mut self, expr: ExprId, callee: ExprId, args: &[ExprId]) -> Ty {
+        let (params, ret) = match self.infer_expr(callee) {
+            Ty::Fn { params, ret } => (params, *ret),
+            ty => {
+                if !ty.is_unknown() {
+                    self.push_at_expr(TypeDiagnosticKind::NotCallable { ty }, callee);
+                }
+
+                for &arg in args {
+                    self.infer_expr(arg);
+                }
+
+                return Ty::Unknown;
+            }
+        };
+
+        if params.len() != args.len() {
+            self.push_at_expr(
+                TypeDiagnosticKind::ArgCount {
+                    expected: params.len(),
+                    found: args.len(),
+                },
+                expr,
+            );
+        }
+
+        for (i, &arg) in args.iter().enumerate() {
+            match params.get(i) {
+                Some(param) => self.check_expr(arg, param),
+                None => self.infer_expr(arg),
+            };
+        }
+
+        ret
+    }
+
+    /// Reports an integer literal which doesn't fit in the type its suffix names. Suffixes which
+    /// don't name a type were already reported by the parser.
+    fn check_range(&mut self, value: &BigInt, suffix: &str, expr: ExprId) {
+        let Some((min, max)) = int_range(suffix) else {
+            return;
+        };
+
+        if *value < min || *value > max {
+            let suffix = suffix.to_string();
+            self.push_at_expr(TypeDiagnosticKind::OutOfRange { suffix, min, max }, expr);
+        }
+    }
+
+    fn push_at_expr(&mut self, kind: TypeDiagnosticKind, expr: ExprId) {
+        if let Some(span) = self.source_map.expr_range(expr) {
+            self.push(kind, span);
+        }
+    }
+
+    fn push(&mut self, kind: TypeDiagnosticKind, span: TextRange) {
+        self.diagnostics.push(TypeDiagnostic { kind, span });
+    }
+}
+
+/// The smallest and largest values of an integer suffix, such as `u8` or `i64`.
+fn int_range(suffix: &str) -> Option<(BigInt, BigInt)> {
+    let (signed, bits) = match suffix.strip_prefix('i') {
+        Some(bits) => (true, bits),
+        None => (false, suffix.strip_prefix('u')?),
+    };
+    let bits = bits
+        .parse::<u32>()
+        .ok()
+        .filter(|bits| matches!(bits, 8 | 16 | 32 | 64 | 128))?;
+
+    Some(if signed {
+        let half = BigInt::one() << (bits - 1);
+        (-half.clone(), half - 1)
+    } else {
+        (BigInt::zero(), (BigInt::one() << bits) - 1)
+    })
+}
+
+/// The type of a binary operation, or `None` if the operator can't be applied to the operands.
+fn binary_result(op: BinaryOp, lhs: &Ty, rhs: &Ty) -> Option<Ty> {
+    let is_comparison = matches!(
+        op,
+        BinaryOp::Eq
+            | BinaryOp::Ne
+            | BinaryOp::Lt
+            | BinaryOp::Le
+            | BinaryOp::Gt
+            | BinaryOp::Ge
+            | BinaryOp::LazyAnd
+            | BinaryOp::LazyOr
+    );
+
+    if lhs.is_unknown() || rhs.is_unknown() {
+        return Some(if is_comparison { Ty::Bool } else { Ty::Unknown });
+    }
+
+    let is_bytes = |ty: &Ty| matches!(ty, Ty::String | Ty::Bytes);
+
+    Some(match (op, lhs, rhs) {
+        (BinaryOp::Add, Ty::String, Ty::String) => Ty::String,
+        (BinaryOp::Add, lhs, rhs) if is_bytes(lhs) && is_bytes(rhs) => Ty::Bytes,
+        (
+            BinaryOp::Add
+            | BinaryOp::Sub
+            | BinaryOp::Mul
+            | BinaryOp::Div
+            | BinaryOp::Rem
+            | BinaryOp::Shl
+            | BinaryOp::Shr
+            | BinaryOp::UnsignedShr,
+            Ty::Int,
+            Ty::Int,
+        ) => Ty::Int,
+        (BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor, Ty::Int, Ty::Int) => Ty::Int,
+        (BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor, Ty::Bool, Ty::Bool) => Ty::Bool,
+        (BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge, Ty::Int, Ty::Int) => Ty::Bool,
+        (BinaryOp::Eq | BinaryOp::Ne, lhs, rhs)
+            if !matches!(lhs, Ty::Fn { .. } | Ty::Unit)
+                && (lhs.is_assignable_to(rhs) || rhs.is_assignable_to(lhs)) =>
+        {
+            Ty::Bool
+        }
+        (BinaryOp::LazyAnd | BinaryOp::LazyOr, Ty::Bool, Ty::Bool) => Ty::Bool,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use diagnostics::{render, SourceFile};
+    use expect_test::{expect, Expect};
+    use parser::parse;
+
+    use super::*;
+    use crate::{lower, resolve};
+
+    fn diagnostics(source: &str) -> Vec<TypeDiagnostic> {
+        let (module, source_map) = lower(&parse(source).tree());
+        let (resolution, _) = resolve(&module, &source_map);
+        check(&module, &source_map, &resolution).1
+    }
+
+    fn check_diagnostics(source: &str, expect: Expect) {
+        let actual = diagnostics(source)
+            .iter()
+            .map(|diagnostic| {
+                format!(
+                    "{} at {}..{}\n",
+                    diagnostic.message(),
+                    u32::from(diagnostic.span.start()),
+                    u32::from(diagnostic.span.end())
+                )
+            })
+            .collect::<String>();
+
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn well_typed() {
+        check_diagnostics(
+            "def add(a: Int, b: Int) -> Int {\n    a + b\n}\n\
+             def main() -> Bool {\n    let x: Int = add(1, 2);\n    let s: Bytes = 'hi' + 'there';\n    x > 2 && !false\n}",
+            expect![""],
+        );
+    }
+
+    #[test]
+    fn let_annotations() {
+        check_diagnostics(
+            "def main() {\n    let a: Int = true;\n    let b: String = 1 + 2;\n    let c: Foo = 1;\n}",
+            expect![[r#"
+                mismatched types: expected `Int`, found `Bool` at 30..34
+                mismatched types: expected `String`, found `Int` at 56..61
+                cannot find type `Foo` at 74..77
+            "#]],
+        );
+    }
+
+    #[test]
+    fn operators() {
+        check_diagnostics(
+            "def main(b: Bool, s: String) {\n    let _x: Int = 1 + b;\n    let _y: Bool = -b;\n    let _z: Bool = s < s;\n    let _w: Bool = !1 == true;\n}",
+            expect![[r#"
+                cannot apply `+` to `Int` and `Bool` at 49..54
+                cannot apply unary `-` to `Bool` at 75..77
+                cannot apply `<` to `String` and `String` at 98..103
+                cannot apply unary `!` to `Int` at 124..126
+            "#]],
+        );
+    }
+
+    #[test]
+    fn calls() {
+        check_diagnostics(
+            "def f(a: Int, b: Bool) -> Int {\n    a\n}\ndef main(x: Int) {\n    f(1);\n    f(true, false);\n    x(1);\n}",
+            expect![[r#"
+                this function takes 2 arguments but 1 was supplied at 63..67
+                mismatched types: expected `Int`, found `Bool` at 75..79
+                `Int` is not a function at 93..94
+            "#]],
+        );
+    }
+
+    #[test]
+    fn return_types() {
+        check_diagnostics(
+            "def a() -> Int {\n    true\n}\ndef b() -> Int {\n}\ndef c() {\n    1\n}",
+            expect![[r#"
+                mismatched types: expected `Int`, found `Bool` at 21..25
+                mismatched types: expected `Int`, found `()` at 39..42
+                mismatched types: expected `()`, found `Int` at 61..62
+            "#]],
+        );
+    }
+
+    #[test]
+    fn literal_ranges() {
+        check_diagnostics(
+            "def main() -> Int {\n    255u8 + 300u8 + -128i8 + 128i8 + -1u8 + 0x1_0000u16 + 99q\n}",
+            expect![[r#"
+                literal out of range for `u8` at 32..37
+                literal out of range for `i8` at 49..54
+                literal out of range for `u8` at 57..61
+                literal out of range for `u16` at 64..75
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unsupported_expressions() {
+        check_diagnostics(
+            "def main(s: String) {\n    s.len;\n    s?;\n}",
+            expect![[r#"
+                no field `len` on type `String` at 26..31
+                the `?` operator cannot be applied to `String` at 37..39
+            "#]],
+        );
+    }
+
+    #[test]
+    fn consts() {
+        check_diagnostics(
+            "const LIMIT: Int = 100;\nconst NAME: String = true;\nconst BAD: Foo = 1;\n\
+             def main() -> Bool {\n    LIMIT > 5 && LIMIT(1)\n}",
+            expect![[r#"
+                mismatched types: expected `String`, found `Bool` at 45..49
+                cannot find type `Foo` at 62..65
+                `Int` is not a function at 109..114
+            "#]],
+        );
+    }
+
+    #[test]
+    fn errors_are_not_repeated() {
+        check_diagnostics(
+            "def main(x: Foo) -> Int {\n    let y: Int = x + missing;\n    y + x\n}",
+            expect![[r#"
+                cannot find type `Foo` at 12..15
+            "#]],
+        );
+    }
+
+    #[test]
+    fn uninitialized_lets() {
+        check_diagnostics(
+            "def main(n: Int) -> Int {\n    let x: Int;\n    let y;\n    let _z = y;\n    x + n\n}",
+            expect![[r#"
+                `y` is used before it is initialized at 66..67
+                `x` is used before it is initialized at 73..74
+            "#]],
+        );
+    }
+
+    #[test]
+    fn render_mismatch() {
+        let source = "def main() {\n    let a: Int = 'hello';\n}";
+        let file = SourceFile::new("main.rue", source);
+
+        expect![[r#"
+            error: mismatched types: expected `Int`, found `String`
+             --> main.rue:2:18
+              |
+            2 |     let a: Int = 'hello';
+              |                  ^^^^^^^ expected `Int`
+        "#]]
+        .assert_eq(&render(&diagnostics(source)[0].to_diagnostic(), &file));
+    }
+}
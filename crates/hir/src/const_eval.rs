@@ -0,0 +1,583 @@
+use std::fmt;
+
+use diagnostics::Diagnostic;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_traits::{Signed, ToPrimitive, Zero};
+use rowan::TextRange;
+
+use crate::{
+    ArenaMap, BinaryOp, Body, BodyResolution, BodySourceMap, Def, DefId, DefKind, Expr, ExprId,
+    Literal, Module, Name, NameResolution, Resolution, SourceMap, UnaryOp,
+};
+
+/// The largest amount a number can be shifted by, which is the same as on the target VM.
+pub const MAX_SHIFT: usize = 65535;
+
+/// A value which is known at compile time.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConstValue {
+    Int(BigInt),
+    Bool(bool),
+    /// Both strings and bytes, since every string is a sequence of bytes.
+    Bytes(Vec<u8>),
+}
+
+impl fmt::Display for ConstValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Bytes(bytes) => match std::str::from_utf8(bytes) {
+                Ok(text) => write!(f, "{text:?}"),
+                Err(_) => {
+                    write!(f, "0x")?;
+                    bytes.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+                }
+            },
+        }
+    }
+}
+
+/// An operation which can't produce a value, no matter when it's evaluated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArithmeticError {
+    DivisionByZero,
+    /// Integers have arbitrary precision, so the only way to overflow is to shift too far.
+    ShiftOverflow {
+        amount: BigInt,
+    },
+    NegativeUnsignedShift,
+}
+
+impl fmt::Display for ArithmeticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DivisionByZero => write!(f, "attempt to divide by zero"),
+            Self::ShiftOverflow { amount } => {
+                write!(f, "attempt to shift by {amount}, which is out of range")
+            }
+            Self::NegativeUnsignedShift => {
+                write!(f, "attempt to apply `>>>` to a negative number")
+            }
+        }
+    }
+}
+
+/// Applies a binary operator to two values, or returns `None` if it can't be applied to them.
+///
+/// Both operands are expected to be evaluated already, so `&&` and `||` don't short circuit here.
+/// Division and remainder round down like on the target VM.
+pub fn fold_binary(
+    op: BinaryOp,
+    lhs: &ConstValue,
+    rhs: &ConstValue,
+) -> Result<Option<ConstValue>, ArithmeticError> {
+    let value = match (op, lhs, rhs) {
+        (BinaryOp::Eq, lhs, rhs) => ConstValue::Bool(lhs == rhs),
+        (BinaryOp::Ne, lhs, rhs) => ConstValue::Bool(lhs != rhs),
+        (BinaryOp::Add, ConstValue::Bytes(lhs), ConstValue::Bytes(rhs)) => {
+            ConstValue::Bytes([lhs.as_slice(), rhs].concat())
+        }
+        (op, ConstValue::Bool(lhs), ConstValue::Bool(rhs)) => ConstValue::Bool(match op {
+            BinaryOp::BitAnd | BinaryOp::LazyAnd => lhs & rhs,
+            BinaryOp::BitOr | BinaryOp::LazyOr => lhs | rhs,
+            BinaryOp::BitXor => lhs ^ rhs,
+            _ => return Ok(None),
+        }),
+        (op, ConstValue::Int(lhs), ConstValue::Int(rhs)) => return fold_int(op, lhs, rhs),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(value))
+}
+
+fn fold_int(
+    op: BinaryOp,
+    lhs: &BigInt,
+    rhs: &BigInt,
+) -> Result<Option<ConstValue>, ArithmeticError> {
+    let value = match op {
+        BinaryOp::Add => lhs + rhs,
+        BinaryOp::Sub => lhs - rhs,
+        BinaryOp::Mul => lhs * rhs,
+        BinaryOp::Div | BinaryOp::Rem if rhs.is_zero() => {
+            return Err(ArithmeticError::DivisionByZero)
+        }
+        BinaryOp::Div => lhs.div_floor(rhs),
+        BinaryOp::Rem => lhs.mod_floor(rhs),
+        BinaryOp::BitAnd => lhs & rhs,
+        BinaryOp::BitOr => lhs | rhs,
+        BinaryOp::BitXor => lhs ^ rhs,
+        BinaryOp::Shl => lhs << shift_amount(rhs)?,
+        BinaryOp::Shr => lhs >> shift_amount(rhs)?,
+        BinaryOp::UnsignedShr => {
+            let amount = shift_amount(rhs)?;
+
+            if lhs.is_negative() {
+                return Err(ArithmeticError::NegativeUnsignedShift);
+            }
+
+            lhs >> amount
+        }
+        BinaryOp::Lt => return Ok(Some(ConstValue::Bool(lhs < rhs))),
+        BinaryOp::Le => return Ok(Some(ConstValue::Bool(lhs <= rhs))),
+        BinaryOp::Gt => return Ok(Some(ConstValue::Bool(lhs > rhs))),
+        BinaryOp::Ge => return Ok(Some(ConstValue::Bool(lhs >= rhs))),
+        BinaryOp::Eq | BinaryOp::Ne | BinaryOp::LazyAnd | BinaryOp::LazyOr => return Ok(None),
+    };
+
+    Ok(Some(ConstValue::Int(value)))
+}
+
+fn shift_amount(amount: &BigInt) -> Result<usize, ArithmeticError> {
+    amount
+        .to_usize()
+        .filter(|&amount| amount <= MAX_SHIFT)
+        .ok_or_else(|| ArithmeticError::ShiftOverflow {
+            amount: amount.clone(),
+        })
+}
+
+/// Applies a unary operator to a value, or returns `None` if it can't be applied to it.
+pub fn fold_unary(op: UnaryOp, value: &ConstValue) -> Option<ConstValue> {
+    match (op, value) {
+        (UnaryOp::Neg, ConstValue::Int(value)) => Some(ConstValue::Int(-value)),
+        (UnaryOp::BitNot, ConstValue::Int(value)) => Some(ConstValue::Int(!value)),
+        (UnaryOp::Not, ConstValue::Bool(value)) => Some(ConstValue::Bool(!value)),
+        _ => None,
+    }
+}
+
+/// The values of every expression which could be evaluated at compile time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConstEval {
+    bodies: ArenaMap<Def, ArenaMap<Expr, ConstValue>>,
+}
+
+impl ConstEval {
+    /// The value of a `const` item, or `None` if it isn't one or couldn't be evaluated.
+    pub fn const_value(&self, module: &Module, def: DefId) -> Option<&ConstValue> {
+        let def_data = &module.defs[def];
+
+        if def_data.kind != DefKind::Const {
+            return None;
+        }
+
+        self.expr_value(def, def_data.body.tail?)
+    }
+
+    /// The folded value of an expression in any definition, if it's known at compile time.
+    pub fn expr_value(&self, def: DefId, expr: ExprId) -> Option<&ConstValue> {
+        self.bodies.get(def)?.get(expr)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstDiagnostic {
+    pub kind: ConstDiagnosticKind,
+    pub span: TextRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstDiagnosticKind {
+    Arithmetic(ArithmeticError),
+    /// A constant whose value depends on itself.
+    Cycle {
+        name: Name,
+    },
+    /// Something in the value of a constant which can't be evaluated at compile time.
+    NotConstant,
+}
+
+impl fmt::Display for ConstDiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Arithmetic(error) => write!(f, "{error}"),
+            Self::Cycle { name } => {
+                write!(f, "cycle detected when evaluating constant `{name}`")
+            }
+            Self::NotConstant => write!(f, "this can't be evaluated at compile time"),
+        }
+    }
+}
+
+impl ConstDiagnostic {
+    pub fn message(&self) -> String {
+        self.kind.to_string()
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let diagnostic = Diagnostic::error(self.message(), self.span);
+
+        match &self.kind {
+            ConstDiagnosticKind::Arithmetic(_) => {
+                diagnostic.with_note("this expression would always fail when evaluated")
+            }
+            ConstDiagnosticKind::Cycle { .. } => {
+                diagnostic.with_primary_message("refers back to the constant being evaluated")
+            }
+            ConstDiagnosticKind::NotConstant => diagnostic
+                .with_help("constants can only use literals, operators and other constants"),
+        }
+    }
+}
+
+/// Folds every expression in a module whose value is known at compile time.
+///
+/// Operators are folded in every body, and names are replaced with the values of the constants
+/// they refer to. Operations which would always fail, such as dividing by zero, are reported
+/// as errors wherever they can be evaluated, which is everywhere except the right hand side of an
+/// `&&` or `||` whose left hand side is already known to decide the result. The value of a `const` item must be known, so anything
+/// else in it is also an error, unless it has an error which was already reported.
+pub fn const_eval(
+    module: &Module,
+    source_map: &SourceMap,
+    resolution: &NameResolution,
+) -> (ConstEval, Vec<ConstDiagnostic>) {
+    let mut evaluator = Evaluator {
+        module,
+        source_map,
+        resolution,
+        bodies: ArenaMap::new(),
+        in_progress: ArenaMap::new(),
+        diagnostics: Vec::new(),
+    };
+
+    for (def, _) in module.defs.iter() {
+        evaluator.eval_def(def);
+    }
+
+    let mut diagnostics = evaluator.diagnostics;
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start());
+
+    (
+        ConstEval {
+            bodies: evaluator.bodies,
+        },
+        diagnostics,
+    )
+}
+
+struct Evaluator<'a> {
+    module: &'a Module,
+    source_map: &'a SourceMap,
+    resolution: &'a NameResolution,
+    bodies: ArenaMap<Def, ArenaMap<Expr, ConstValue>>,
+    /// Constants which are being evaluated, to detect when one depends on itself.
+    in_progress: ArenaMap<Def, ()>,
+    diagnostics: Vec<ConstDiagnostic>,
+}
+
+struct BodyEval<'a> {
+    body: &'a Body,
+    source_map: &'a BodySourceMap,
+    resolution: &'a BodyResolution,
+    values: ArenaMap<Expr, ConstValue>,
+    /// Operations which would fail, which are only reported if they aren't skipped.
+    errors: Vec<(ExprId, ArithmeticError)>,
+    /// Operands which are never evaluated, because `&&` or `||` short circuits before them.
+    skipped: Vec<ExprId>,
+    /// Whether an error was reported in this body, so the value of a constant isn't reported twice.
+    has_error: bool,
+}
+
+impl Evaluator<'_> {
+    fn eval_def(&mut self, def: DefId) {
+        if self.bodies.get(def).is_some() || self.in_progress.get(def).is_some() {
+            return;
+        }
+
+        self.in_progress.insert(def, ());
+
+        let def_data = &self.module.defs[def];
+        let mut body = BodyEval {
+            body: &def_data.body,
+            source_map: self.source_map.body(def),
+            resolution: self.resolution.body(def),
+            values: ArenaMap::new(),
+            errors: Vec::new(),
+            skipped: Vec::new(),
+            has_error: false,
+        };
+
+        // Subexpressions are always allocated before their parents, so they are folded first.
+        for (expr, _) in body.body.exprs.iter() {
+            if let Some(value) = self.fold_expr(&mut body, expr) {
+                body.values.insert(expr, value);
+            }
+        }
+
+        let mut skipped = ArenaMap::new();
+        for &expr in &body.skipped {
+            skip(body.body, expr, &mut skipped);
+        }
+
+        for (expr, error) in std::mem::take(&mut body.errors) {
+            if skipped.get(expr).is_none() {
+                self.push(&mut body, ConstDiagnosticKind::Arithmetic(error), expr);
+            }
+        }
+
+        if def_data.kind == DefKind::Const && !body.has_error {
+            if let Some(tail) = def_data.body.tail {
+                self.check_constant(&body, tail);
+            }
+        }
+
+        self.bodies.insert(def, body.values);
+    }
+
+    fn fold_expr(&mut self, body: &mut BodyEval, expr: ExprId) -> Option<ConstValue> {
+        match &body.body.exprs[expr] {
+            Expr::Literal(literal) => match literal {
+                Literal::Bool(value) => Some(ConstValue::Bool(*value)),
+                Literal::Int { value, .. } => Some(ConstValue::Int(value.clone())),
+                Literal::String(value) => Some(ConstValue::Bytes(value.as_bytes().to_vec())),
+            },
+            Expr::Name(name) => {
+                let Some(Resolution::Def(def)) = body.resolution.resolve_expr(expr) else {
+                    return None;
+                };
+
+                if self.module.defs[def].kind != DefKind::Const {
+                    return None;
+                }
+
+                if self.in_progress.get(def).is_some() && self.bodies.get(def).is_none() {
+                    let kind = ConstDiagnosticKind::Cycle { name: name.clone() };
+                    self.push(body, kind, expr);
+                    return None;
+                }
+
+                self.eval_def(def);
+
+                let tail = self.module.defs[def].body.tail?;
+                self.bodies.get(def)?.get(tail).cloned()
+            }
+            Expr::Binary { op, lhs, rhs } => {
+                let lhs = body.values.get(*lhs)?;
+
+                if let (BinaryOp::LazyAnd, ConstValue::Bool(false))
+                | (BinaryOp::LazyOr, ConstValue::Bool(true)) = (op, lhs)
+                {
+                    body.skipped.push(*rhs);
+                    return Some(lhs.clone());
+                }
+
+                match fold_binary(*op, lhs, body.values.get(*rhs)?) {
+                    Ok(value) => value,
+                    Err(error) => {
+                        body.errors.push((expr, error));
+                        None
+                    }
+                }
+            }
+            Expr::Unary { op, expr: inner } => fold_unary(*op, body.values.get(*inner)?),
+            Expr::Missing | Expr::Call { .. } | Expr::Field { .. } | Expr::Try { .. } => None,
+        }
+    }
+
+    /// Reports the outermost part of the value of a constant which stops it from being folded.
+    fn check_constant(&mut self, body: &BodyEval, expr: ExprId) {
+        if body.values.get(expr).is_some() {
+            return;
+        }
+
+        let data = &body.body.exprs[expr];
+
+        let is_constant = match data {
+            Expr::Call { .. } => false,
+            Expr::Name(_) => !matches!(
+                body.resolution.resolve_expr(expr),
+                Some(Resolution::Def(def)) if self.module.defs[def].kind == DefKind::Fn
+            ),
+            _ => true,
+        };
+
+        if !is_constant {
+            if let Some(span) = body.source_map.expr_range(expr) {
+                self.diagnostics.push(ConstDiagnostic {
+                    kind: ConstDiagnosticKind::NotConstant,
+                    span,
+                });
+            }
+            return;
+        }
+
+        // Anything else which couldn't be folded has an error from another pass,
+        // or contains the expression which stopped it.
+        data.walk_child_exprs(|child| self.check_constant(body, child));
+    }
+
+    fn push(&mut self, body: &mut BodyEval, kind: ConstDiagnosticKind, expr: ExprId) {
+        body.has_error = true;
+
+        if let Some(span) = body.source_map.expr_range(expr) {
+            self.diagnostics.push(ConstDiagnostic { kind, span });
+        }
+    }
+}
+
+/// Marks an expression and everything in it as skipped.
+fn skip(body: &Body, expr: ExprId, skipped: &mut ArenaMap<Expr, ()>) {
+    skipped.insert(expr, ());
+    body.exprs[expr].walk_child_exprs(|child| skip(body, child, skipped));
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use parser::parse;
+
+    use super::*;
+    use crate::{lower, resolve};
+
+    fn check_consts(source: &str, expect: Expect) {
+        let (module, source_map) = lower(&parse(source).tree());
+        let (resolution, _) = resolve(&module, &source_map);
+        let (consts, diagnostics) = const_eval(&module, &source_map, &resolution);
+
+        let mut actual = String::new();
+
+        for (def, data) in module.defs.iter() {
+            if let (DefKind::Const, Some(name)) = (data.kind, &data.name) {
+                let value = consts
+                    .const_value(&module, def)
+                    .map_or("<unknown>".to_string(), ToString::to_string);
+                actual.push_str(&format!("{name} = {value}\n"));
+            }
+        }
+
+        for diagnostic in diagnostics {
+            actual.push_str(&format!(
+                "{} at `{}`\n",
+                diagnostic.message(),
+                &source[diagnostic.span]
+            ));
+        }
+
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn fold_consts() {
+        check_consts(
+            "const AMOUNT: Int = 1000 * 1000;\n\
+             const FEE: Int = AMOUNT / 7 + -(3 % 2);\n\
+             const HUGE: Int = 1 << 128;\n\
+             const NAME: String = 'coin' + 's';\n\
+             const ENABLED: Bool = !(FEE > AMOUNT) && true;\n\
+             const MASK: Int = ~0 >> 4;",
+            expect![[r#"
+                AMOUNT = 1000000
+                FEE = 142856
+                HUGE = 340282366920938463463374607431768211456
+                NAME = "coins"
+                ENABLED = true
+                MASK = -1
+            "#]],
+        );
+    }
+
+    #[test]
+    fn consts_can_be_used_before_they_are_defined() {
+        check_consts(
+            "const B: Int = A * 2;\nconst A: Int = 21;",
+            expect![[r#"
+                B = 42
+                A = 21
+            "#]],
+        );
+    }
+
+    #[test]
+    fn arithmetic_errors() {
+        check_consts(
+            "const A: Int = 10 / (5 - 5);\n\
+             const B: Int = 1 << 70000;\n\
+             const C: Int = -8 >>> 1;\n\
+             const D: Int = A + 1;\n\
+             def main() -> Int {\n    7 % 0\n}",
+            expect![[r#"
+                A = <unknown>
+                B = <unknown>
+                C = <unknown>
+                D = <unknown>
+                attempt to divide by zero at `10 / (5 - 5)`
+                attempt to shift by 70000, which is out of range at `1 << 70000`
+                attempt to apply `>>>` to a negative number at `-8 >>> 1`
+                attempt to divide by zero at `7 % 0`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn short_circuits() {
+        check_consts(
+            "const A: Bool = false && 1 / 0 == 0;\n\
+             const B: Bool = true || (1 << -1 == 0 && true);\n\
+             const C: Bool = true && 1 / 0 == 0;\n\
+             def main(x: Bool) -> Bool {\n    false && 7 % 0 == 0 || x && 1 / 0 == 1\n}",
+            expect![[r#"
+                A = false
+                B = true
+                C = <unknown>
+                attempt to divide by zero at `1 / 0`
+                attempt to divide by zero at `1 / 0`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn cycles() {
+        check_consts(
+            "const A: Int = B + 1;\nconst B: Int = A;\nconst C: Int = C;",
+            expect![[r#"
+                A = <unknown>
+                B = <unknown>
+                C = <unknown>
+                cycle detected when evaluating constant `A` at `A`
+                cycle detected when evaluating constant `C` at `C`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn not_constant() {
+        check_consts(
+            "const A: Int = 1 + double(2);\n\
+             const B: Int = double;\n\
+             const C: Int = 1 + unknown;\n\
+             def double(x: Int) -> Int {\n    x * 2\n}",
+            expect![[r#"
+                A = <unknown>
+                B = <unknown>
+                C = <unknown>
+                this can't be evaluated at compile time at `double(2)`
+                this can't be evaluated at compile time at `double`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn fold_function_bodies() {
+        let source = "const LIMIT: Int = 10;\ndef main(x: Int) -> Bool {\n    x < LIMIT * 2 + 1\n}";
+        let (module, source_map) = lower(&parse(source).tree());
+        let (resolution, _) = resolve(&module, &source_map);
+        let (consts, _) = const_eval(&module, &source_map, &resolution);
+
+        let main = DefId::from_raw(1);
+        let Expr::Binary { lhs, rhs, .. } =
+            &module.defs[main].body.exprs[module.defs[main].body.tail.unwrap()]
+        else {
+            panic!("expected a comparison");
+        };
+
+        assert_eq!(consts.expr_value(main, *lhs), None);
+        assert_eq!(
+            consts.expr_value(main, *rhs),
+            Some(&ConstValue::Int(21.into()))
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{Arena, Expr, ExprId, Idx, Name};
+
+pub type DefId = Idx<Def>;
+pub type LocalId = Idx<Local>;
+
+/// Every item in a source file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Module {
+    pub defs: Arena<Def>,
+}
+
+/// A function or constant definition.
+///
+/// Each definition owns the arenas for its body, so the IDs inside of it stay the same
+/// when other definitions are edited.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Def {
+    pub kind: DefKind,
+    pub name: Option<Name>,
+    /// The return type of a function, or the declared type of a constant.
+    pub return_type: Option<TypeRef>,
+    pub body: Body,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DefKind {
+    Fn,
+    /// A `const` item, whose body has no parameters or statements and whose tail is its value.
+    Const,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Body {
+    pub exprs: Arena<Expr>,
+    pub locals: Arena<Local>,
+    pub params: Vec<LocalId>,
+    pub stmts: Vec<Stmt>,
+    /// The expression at the end of the body, which the function returns.
+    pub tail: Option<ExprId>,
+}
+
+/// A parameter or `let` binding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Local {
+    pub name: Option<Name>,
+    pub ty: Option<TypeRef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stmt {
+    Let {
+        local: LocalId,
+        value: Option<ExprId>,
+    },
+    Expr(ExprId),
+}
+
+/// A type written in the source, which hasn't been resolved yet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeRef {
+    Name(Name),
+    /// A type which was expected but couldn't be parsed.
+    Missing,
+}
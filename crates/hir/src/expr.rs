@@ -0,0 +1,128 @@
+use num_bigint::BigInt;
+
+use crate::{Idx, Name};
+
+pub type ExprId = Idx<Expr>;
+
+/// An expression, with parentheses and unary plus desugared away.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    /// An expression which was expected but couldn't be parsed.
+    Missing,
+    Literal(Literal),
+    Name(Name),
+    Binary {
+        op: BinaryOp,
+        lhs: ExprId,
+        rhs: ExprId,
+    },
+    Unary {
+        op: UnaryOp,
+        expr: ExprId,
+    },
+    Call {
+        callee: ExprId,
+        args: Vec<ExprId>,
+    },
+    Field {
+        expr: ExprId,
+        name: Option<Name>,
+    },
+    Try {
+        expr: ExprId,
+    },
+}
+
+impl Expr {
+    /// Calls the function with each direct subexpression, from left to right.
+    pub fn walk_child_exprs(&self, mut f: impl FnMut(ExprId)) {
+        match self {
+            Self::Missing | Self::Literal(_) | Self::Name(_) => {}
+            Self::Binary { lhs, rhs, .. } => {
+                f(*lhs);
+                f(*rhs);
+            }
+            Self::Unary { expr, .. } | Self::Field { expr, .. } | Self::Try { expr } => f(*expr),
+            Self::Call { callee, args } => {
+                f(*callee);
+                args.iter().copied().for_each(f);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Literal {
+    Bool(bool),
+    Int {
+        value: BigInt,
+        suffix: Option<String>,
+    },
+    String(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+    UnsignedShr,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    LazyAnd,
+    LazyOr,
+}
+
+impl BinaryOp {
+    pub fn text(self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Rem => "%",
+            Self::BitAnd => "&",
+            Self::BitOr => "|",
+            Self::BitXor => "^",
+            Self::Shl => "<<",
+            Self::Shr => ">>",
+            Self::UnsignedShr => ">>>",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::LazyAnd => "&&",
+            Self::LazyOr => "||",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+    BitNot,
+}
+
+impl UnaryOp {
+    pub fn text(self) -> &'static str {
+        match self {
+            Self::Neg => "-",
+            Self::Not => "!",
+            Self::BitNot => "~",
+        }
+    }
+}
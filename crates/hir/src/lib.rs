@@ -0,0 +1,26 @@
+mod analysis;
+mod arena;
+mod check;
+mod const_eval;
+mod def;
+mod expr;
+mod lower;
+mod name;
+mod pretty;
+mod resolve;
+mod scope;
+mod source_map;
+mod ty;
+
+pub use analysis::*;
+pub use arena::*;
+pub use check::*;
+pub use const_eval::*;
+pub use def::*;
+pub use expr::*;
+pub use lower::*;
+pub use name::*;
+pub use resolve::*;
+pub use scope::*;
+pub use source_map::*;
+pub use ty::*;
@@ -0,0 +1,499 @@
+use lexer::{Base, Lexer, TokenKind};
+use num_bigint::BigInt;
+use rowan::TextRange;
+use syntax::{
+    ast::{self, AstNode},
+    SyntaxKind, SyntaxNode, SyntaxNodePtr, SyntaxToken,
+};
+
+use crate::{
+    BinaryOp, Body, BodySourceMap, Def, DefKind, Expr, ExprId, Literal, Local, LocalId, Module,
+    Name, SourceMap, Stmt, TypeRef, UnaryOp,
+};
+
+/// Lowers a source file to HIR, along with a map back to the syntax it came from.
+///
+/// Anything which is missing after error recovery is kept as `None` or a `Missing` placeholder,
+/// so the rest of the file can still be analyzed.
+pub fn lower(root: &ast::Root) -> (Module, SourceMap) {
+    let mut module = Module::default();
+    let mut source_map = SourceMap::default();
+
+    for item in root.items() {
+        match item {
+            ast::Item::DefItem(def_item) => {
+                let (def, body_source_map) = lower_def(&def_item);
+                let def = module.defs.alloc(def);
+                let ptr = SyntaxNodePtr::new(def_item.syntax());
+                source_map.def_map.insert(ptr.clone(), def);
+                source_map.defs.insert(def, ptr);
+                source_map.bodies.insert(def, body_source_map);
+            }
+            ast::Item::ConstItem(const_item) => {
+                let (def, body_source_map) = lower_const(&const_item);
+                let def = module.defs.alloc(def);
+                let ptr = SyntaxNodePtr::new(const_item.syntax());
+                source_map.def_map.insert(ptr.clone(), def);
+                source_map.defs.insert(def, ptr);
+                source_map.bodies.insert(def, body_source_map);
+            }
+        }
+    }
+
+    (module, source_map)
+}
+
+fn lower_def(def_item: &ast::DefItem) -> (Def, BodySourceMap) {
+    let mut lowerer = BodyLowerer::default();
+
+    if let Some(param_list) = def_item.param_list() {
+        for param in param_list.params() {
+            let local = lowerer.param(&param);
+            lowerer.body.params.push(local);
+        }
+    }
+
+    if let Some(block) = def_item.body() {
+        for stmt in block.stmts() {
+            let stmt = lowerer.stmt(&stmt);
+            lowerer.body.stmts.push(stmt);
+        }
+
+        lowerer.body.tail = block.tail_expr().map(|expr| lowerer.lower_expr(&expr));
+    }
+
+    if let Some(return_type) = def_item.return_type() {
+        lowerer.source_map.return_type_range = Some(trimmed_range(return_type.syntax()));
+    }
+
+    let def = Def {
+        kind: DefKind::Fn,
+        name: name(def_item.name()),
+        return_type: has_token(def_item.syntax(), SyntaxKind::Arrow)
+            .then(|| type_ref(def_item.return_type())),
+        body: lowerer.body,
+    };
+
+    (def, lowerer.source_map)
+}
+
+fn lower_const(const_item: &ast::ConstItem) -> (Def, BodySourceMap) {
+    let mut lowerer = BodyLowerer::default();
+    let syntax = const_item.syntax();
+
+    if has_token(syntax, SyntaxKind::Equals) {
+        lowerer.body.tail = Some(lowerer.expr(const_item.value(), syntax));
+    }
+
+    if let Some(ty) = const_item.ty() {
+        lowerer.source_map.return_type_range = Some(trimmed_range(ty.syntax()));
+    }
+
+    let def = Def {
+        kind: DefKind::Const,
+        name: name(const_item.name()),
+        return_type: has_token(syntax, SyntaxKind::Colon).then(|| type_ref(const_item.ty())),
+        body: lowerer.body,
+    };
+
+    (def, lowerer.source_map)
+}
+
+#[derive(Default)]
+struct BodyLowerer {
+    body: Body,
+    source_map: BodySourceMap,
+}
+
+impl BodyLowerer {
+    fn param(&mut self, param: &ast::Param) -> LocalId {
+        let local = Local {
+            name: name(param.name()),
+            ty: Some(type_ref(param.ty())),
+        };
+        let id = self.alloc_local(local, param.syntax(), param.name());
+        self.record_type_range(id, param.ty());
+        id
+    }
+
+    fn stmt(&mut self, stmt: &ast::Stmt) -> Stmt {
+        match stmt {
+            ast::Stmt::LetStmt(let_stmt) => {
+                let syntax = let_stmt.syntax();
+
+                let local = Local {
+                    name: name(let_stmt.name()),
+                    ty: has_token(syntax, SyntaxKind::Colon).then(|| type_ref(let_stmt.ty())),
+                };
+
+                // The value is lowered first, since the binding isn't in scope inside of it.
+                let value = has_token(syntax, SyntaxKind::Equals)
+                    .then(|| self.expr(let_stmt.value(), syntax));
+
+                let local = self.alloc_local(local, syntax, let_stmt.name());
+                self.record_type_range(local, let_stmt.ty());
+                Stmt::Let { local, value }
+            }
+            ast::Stmt::ExprStmt(expr_stmt) => {
+                Stmt::Expr(self.expr(expr_stmt.expr(), expr_stmt.syntax()))
+            }
+        }
+    }
+
+    /// Lowers an expression, or a placeholder for it if it's missing from the parent node.
+    fn expr(&mut self, expr: Option<ast::Expr>, parent: &SyntaxNode) -> ExprId {
+        match expr {
+            Some(expr) => self.lower_expr(&expr),
+            None => self.missing(parent),
+        }
+    }
+
+    fn lower_expr(&mut self, expr: &ast::Expr) -> ExprId {
+        let syntax = expr.syntax();
+
+        let lowered = match expr {
+            ast::Expr::Literal(literal) => match lower_literal(literal) {
+                Some(literal) => Expr::Literal(literal),
+                None => Expr::Missing,
+            },
+            ast::Expr::NameRef(name_ref) => match name(name_ref.name()) {
+                Some(name) => Expr::Name(name),
+                None => Expr::Missing,
+            },
+            ast::Expr::ParenExpr(paren) => {
+                let inner = self.expr(paren.expr(), syntax);
+                self.source_map
+                    .expr_map
+                    .insert(SyntaxNodePtr::new(syntax), inner);
+                return inner;
+            }
+            ast::Expr::BinaryExpr(binary) => {
+                let op = binary.op().and_then(|op| binary_op(op.kind()));
+                let lhs = self.expr(binary.lhs(), syntax);
+                let rhs = self.expr(binary.rhs(), syntax);
+
+                match op {
+                    Some(op) => Expr::Binary { op, lhs, rhs },
+                    None => Expr::Missing,
+                }
+            }
+            ast::Expr::PrefixExpr(prefix) => {
+                let op = prefix.op().map(|op| op.kind());
+                let inner = self.expr(prefix.expr(), syntax);
+
+                match op.and_then(unary_op) {
+                    Some(op) => Expr::Unary { op, expr: inner },
+                    None if op == Some(SyntaxKind::Plus) => {
+                        self.source_map
+                            .expr_map
+                            .insert(SyntaxNodePtr::new(syntax), inner);
+                        return inner;
+                    }
+                    None => Expr::Missing,
+                }
+            }
+            ast::Expr::CallExpr(call) => {
+                let callee = self.expr(call.callee(), syntax);
+                let args = call
+                    .arg_list()
+                    .into_iter()
+                    .flat_map(|arg_list| arg_list.args())
+                    .map(|arg| self.lower_expr(&arg))
+                    .collect();
+                Expr::Call { callee, args }
+            }
+            ast::Expr::FieldExpr(field) => Expr::Field {
+                expr: self.expr(field.expr(), syntax),
+                name: name(field.name()),
+            },
+            ast::Expr::TryExpr(try_expr) => Expr::Try {
+                expr: self.expr(try_expr.expr(), syntax),
+            },
+        };
+
+        self.alloc_expr(lowered, syntax)
+    }
+
+    fn missing(&mut self, parent: &SyntaxNode) -> ExprId {
+        let placeholder = parent
+            .children()
+            .find(|node| matches!(node.kind(), SyntaxKind::Missing | SyntaxKind::Error));
+
+        match placeholder {
+            Some(node) => self.alloc_expr(Expr::Missing, &node),
+            None => self.body.exprs.alloc(Expr::Missing),
+        }
+    }
+
+    fn alloc_expr(&mut self, expr: Expr, syntax: &SyntaxNode) -> ExprId {
+        let id = self.body.exprs.alloc(expr);
+        let ptr = SyntaxNodePtr::new(syntax);
+        self.source_map.expr_map.insert(ptr.clone(), id);
+        self.source_map.expr_map_back.insert(id, ptr);
+        self.source_map
+            .expr_ranges
+            .insert(id, trimmed_range(syntax));
+        id
+    }
+
+    fn record_type_range(&mut self, local: LocalId, ty: Option<ast::Type>) {
+        if let Some(ty) = ty {
+            self.source_map
+                .local_type_ranges
+                .insert(local, trimmed_range(ty.syntax()));
+        }
+    }
+
+    fn alloc_local(
+        &mut self,
+        local: Local,
+        syntax: &SyntaxNode,
+        name: Option<SyntaxToken>,
+    ) -> LocalId {
+        let id = self.body.locals.alloc(local);
+        let ptr = SyntaxNodePtr::new(syntax);
+        self.source_map.local_map.insert(ptr.clone(), id);
+        self.source_map.local_map_back.insert(id, ptr);
+
+        if let Some(name) = name {
+            self.source_map
+                .local_name_ranges
+                .insert(id, name.text_range());
+        }
+
+        id
+    }
+}
+
+fn name(token: Option<SyntaxToken>) -> Option<Name> {
+    token.map(|token| Name::new(token.text()))
+}
+
+fn type_ref(ty: Option<ast::Type>) -> TypeRef {
+    match ty.and_then(|ty| match ty {
+        ast::Type::NameType(name_type) => name(name_type.name()),
+    }) {
+        Some(name) => TypeRef::Name(name),
+        None => TypeRef::Missing,
+    }
+}
+
+/// The range of a node without the trivia at its edges, which the parser attaches to it.
+fn trimmed_range(node: &SyntaxNode) -> TextRange {
+    let mut tokens = node
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| !token.kind().is_trivia());
+
+    let Some(first) = tokens.next() else {
+        return TextRange::empty(node.text_range().start());
+    };
+    let last = tokens.last().unwrap_or_else(|| first.clone());
+
+    TextRange::new(first.text_range().start(), last.text_range().end())
+}
+
+fn has_token(node: &SyntaxNode, kind: SyntaxKind) -> bool {
+    node.children_with_tokens()
+        .any(|element| element.kind() == kind)
+}
+
+/// Converts a literal to its value, or `None` if it's invalid and an error was already reported.
+fn lower_literal(literal: &ast::Literal) -> Option<Literal> {
+    let token = literal.token()?;
+    let text = token.text();
+
+    match token.kind() {
+        SyntaxKind::TrueKw => Some(Literal::Bool(true)),
+        SyntaxKind::FalseKw => Some(Literal::Bool(false)),
+        SyntaxKind::Integer => lower_integer(text),
+        // There's no type for floats yet, which the parser already reported.
+        SyntaxKind::Float => None,
+        SyntaxKind::String => {
+            let quote = text.chars().next()?;
+            let inner = &text[1..];
+            let inner = inner.strip_suffix(quote)?;
+            Some(Literal::String(inner.to_string()))
+        }
+        _ => None,
+    }
+}
+
+fn lower_integer(text: &str) -> Option<Literal> {
+    let TokenKind::Integer {
+        base,
+        is_empty: false,
+        suffix_start,
+    } = Lexer::new(text).next()?.kind
+    else {
+        return None;
+    };
+
+    let prefix_len = if base == Base::Decimal { 0 } else { 2 };
+    let digits_end = suffix_start.map_or(text.len(), |start| start as usize);
+    let digits = text[prefix_len..digits_end].replace('_', "");
+
+    Some(Literal::Int {
+        value: BigInt::parse_bytes(digits.as_bytes(), base as u32)?,
+        suffix: suffix_start.map(|start| text[start as usize..].to_string()),
+    })
+}
+
+fn binary_op(kind: SyntaxKind) -> Option<BinaryOp> {
+    Some(match kind {
+        SyntaxKind::Plus => BinaryOp::Add,
+        SyntaxKind::Minus => BinaryOp::Sub,
+        SyntaxKind::Star => BinaryOp::Mul,
+        SyntaxKind::Slash => BinaryOp::Div,
+        SyntaxKind::Percent => BinaryOp::Rem,
+        SyntaxKind::And => BinaryOp::BitAnd,
+        SyntaxKind::Or => BinaryOp::BitOr,
+        SyntaxKind::Xor => BinaryOp::BitXor,
+        SyntaxKind::LeftShift => BinaryOp::Shl,
+        SyntaxKind::RightShift => BinaryOp::Shr,
+        SyntaxKind::UnsignedRightShift => BinaryOp::UnsignedShr,
+        SyntaxKind::EqualTo => BinaryOp::Eq,
+        SyntaxKind::NotEqual => BinaryOp::Ne,
+        SyntaxKind::LessThan => BinaryOp::Lt,
+        SyntaxKind::LessThanEquals => BinaryOp::Le,
+        SyntaxKind::GreaterThan => BinaryOp::Gt,
+        SyntaxKind::GreaterThanEquals => BinaryOp::Ge,
+        SyntaxKind::LazyAnd => BinaryOp::LazyAnd,
+        SyntaxKind::LazyOr => BinaryOp::LazyOr,
+        _ => return None,
+    })
+}
+
+fn unary_op(kind: SyntaxKind) -> Option<UnaryOp> {
+    Some(match kind {
+        SyntaxKind::Minus => UnaryOp::Neg,
+        SyntaxKind::Exclamation => UnaryOp::Not,
+        SyntaxKind::Tilde => UnaryOp::BitNot,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use parser::parse;
+
+    use super::*;
+
+    fn check(source: &str, expect: Expect) {
+        let (module, _) = lower(&parse(source).tree());
+        expect.assert_eq(&module.debug_hir());
+    }
+
+    #[test]
+    fn lower_def() {
+        check(
+            "def main(x: Int, y: Bool) -> Int {\n    let a: Int = (x + 1) * -2;\n    let b = f(a, 'hi').len?;\n    g(b);\n    a\n}",
+            expect![[r#"
+                def main(x#0: Int, y#1: Bool) -> Int {
+                    let a#2: Int = ((x + 1) * -2);
+                    let b#3 = f(a, "hi").len?;
+                    g(b);
+                    a
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lower_const() {
+        check(
+            "const MAX: Int = (1 << 8) - 1;\ndef main() -> Int {\n    MAX\n}\nconst BROKEN = ;",
+            expect![[r#"
+                const MAX: Int = ((1 << 8) - 1);
+                def main() -> Int {
+                    MAX
+                }
+                const BROKEN = <missing>;
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lower_literals() {
+        check(
+            "def main() {\n    let a = 0x1F + 1_000u8 + 0b101;\n    let b = !true || +false;\n    let c = 1.5;\n}",
+            expect![[r#"
+                def main() {
+                    let a#0 = ((31 + 1000u8) + 5);
+                    let b#1 = (!true || false);
+                    let c#2 = <missing>;
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lower_with_errors() {
+        check(
+            "def (a: , b) {\n    let = ;\n    let c: = 1 +;\n    let d;\n}\ndef f() -> {}",
+            expect![[r#"
+                def <missing>(a#0: <missing>, b#1: <missing>) {
+                    let <missing>#2 = <missing>;
+                    let c#3: <missing> = (1 + <missing>);
+                    let d#4;
+                }
+                def f() -> <missing> {
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn source_map() {
+        let parse = parse("def main(x: Int) {\n    let y = (x) + @;\n}");
+        let root = parse.syntax_node();
+        let (module, source_map) = lower(&parse.tree());
+
+        let (def, data) = module.defs.iter().next().unwrap();
+        let body_map = source_map.body(def);
+
+        let Stmt::Let {
+            local,
+            value: Some(value),
+        } = data.body.stmts[0]
+        else {
+            panic!("expected a let statement with a value");
+        };
+
+        let Expr::Binary { lhs, rhs, .. } = data.body.exprs[value] else {
+            panic!("expected a binary expression");
+        };
+
+        let paren = root
+            .descendants()
+            .find(|node| node.kind() == SyntaxKind::ParenExpr)
+            .unwrap();
+        assert_eq!(body_map.node_expr(&paren), Some(lhs));
+
+        let rhs_syntax = body_map.expr_syntax(rhs).unwrap().to_node(&root);
+        assert!(matches!(
+            rhs_syntax.kind(),
+            SyntaxKind::Missing | SyntaxKind::Error
+        ));
+
+        let let_syntax = body_map.local_syntax(local).unwrap().to_node(&root);
+        assert_eq!(let_syntax.kind(), SyntaxKind::LetStmt);
+        assert_eq!(
+            source_map.def_syntax(def).unwrap().to_node(&root).kind(),
+            SyntaxKind::DefItem
+        );
+    }
+
+    #[test]
+    fn ids_are_stable_across_other_defs() {
+        let (before, _) = lower(&parse("def a() {}\ndef main() {\n    let x = 1;\n}").tree());
+        let (after, _) = lower(
+            &parse("def a(p: Int) {\n    let q = p * p;\n}\ndef main() {\n    let x = 1;\n}")
+                .tree(),
+        );
+
+        let main = crate::DefId::from_raw(1);
+        assert_eq!(before.defs[main], after.defs[main]);
+    }
+}
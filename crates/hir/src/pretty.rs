@@ -0,0 +1,146 @@
+use std::fmt::Write;
+
+use crate::{Body, Def, DefKind, Expr, ExprId, Literal, LocalId, Module, Stmt, TypeRef};
+
+impl Module {
+    /// Prints the IR in a source-like form for debugging, with every local labeled by its ID.
+    pub fn debug_hir(&self) -> String {
+        let mut out = String::new();
+
+        for (_, def) in self.defs.iter() {
+            write_def(&mut out, def);
+        }
+
+        out
+    }
+}
+
+fn write_def(out: &mut String, def: &Def) {
+    let body = &def.body;
+
+    let name = def.name.as_ref().map_or("<missing>", |name| name.as_str());
+
+    if def.kind == DefKind::Const {
+        write!(out, "const {name}").unwrap();
+
+        if let Some(ty) = &def.return_type {
+            write!(out, ": {}", type_ref(ty)).unwrap();
+        }
+
+        if let Some(value) = body.tail {
+            out.push_str(" = ");
+            write_expr(out, body, value);
+        }
+
+        out.push_str(";\n");
+        return;
+    }
+
+    write!(out, "def {name}(").unwrap();
+
+    for (i, param) in body.params.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write_local(out, body, *param);
+    }
+
+    out.push(')');
+
+    if let Some(return_type) = &def.return_type {
+        write!(out, " -> {}", type_ref(return_type)).unwrap();
+    }
+
+    out.push_str(" {\n");
+
+    for stmt in &body.stmts {
+        match stmt {
+            Stmt::Let { local, value } => {
+                out.push_str("    let ");
+                write_local(out, body, *local);
+
+                if let Some(value) = value {
+                    out.push_str(" = ");
+                    write_expr(out, body, *value);
+                }
+
+                out.push_str(";\n");
+            }
+            Stmt::Expr(expr) => {
+                out.push_str("    ");
+                write_expr(out, body, *expr);
+                out.push_str(";\n");
+            }
+        }
+    }
+
+    if let Some(tail) = body.tail {
+        out.push_str("    ");
+        write_expr(out, body, tail);
+        out.push('\n');
+    }
+
+    out.push_str("}\n");
+}
+
+fn write_local(out: &mut String, body: &Body, local: LocalId) {
+    let data = &body.locals[local];
+    let name = data.name.as_ref().map_or("<missing>", |name| name.as_str());
+    write!(out, "{name}{local:?}").unwrap();
+
+    if let Some(ty) = &data.ty {
+        write!(out, ": {}", type_ref(ty)).unwrap();
+    }
+}
+
+fn type_ref(ty: &TypeRef) -> &str {
+    match ty {
+        TypeRef::Name(name) => name.as_str(),
+        TypeRef::Missing => "<missing>",
+    }
+}
+
+fn write_expr(out: &mut String, body: &Body, expr: ExprId) {
+    match &body.exprs[expr] {
+        Expr::Missing => out.push_str("<missing>"),
+        Expr::Literal(Literal::Bool(value)) => write!(out, "{value}").unwrap(),
+        Expr::Literal(Literal::Int { value, suffix }) => {
+            write!(out, "{value}{}", suffix.as_deref().unwrap_or_default()).unwrap()
+        }
+        Expr::Literal(Literal::String(value)) => write!(out, "{value:?}").unwrap(),
+        Expr::Name(name) => out.push_str(name.as_str()),
+        Expr::Binary { op, lhs, rhs } => {
+            out.push('(');
+            write_expr(out, body, *lhs);
+            write!(out, " {} ", op.text()).unwrap();
+            write_expr(out, body, *rhs);
+            out.push(')');
+        }
+        Expr::Unary { op, expr } => {
+            out.push_str(op.text());
+            write_expr(out, body, *expr);
+        }
+        Expr::Call { callee, args } => {
+            write_expr(out, body, *callee);
+            out.push('(');
+
+            for (i, arg) in args.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_expr(out, body, *arg);
+            }
+
+            out.push(')');
+        }
+        Expr::Field { expr, name } => {
+            write_expr(out, body, *expr);
+            let name = name.as_ref().map_or("<missing>", |name| name.as_str());
+            write!(out, ".{name}").unwrap();
+        }
+        Expr::Try { expr } => {
+            write_expr(out, body, *expr);
+            out.push('?');
+        }
+    }
+}
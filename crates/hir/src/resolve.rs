@@ -0,0 +1,382 @@
+use std::{collections::HashMap, fmt};
+
+use diagnostics::Diagnostic;
+use rowan::TextRange;
+
+use crate::{
+    ArenaMap, Body, BodySourceMap, Def, DefId, Expr, ExprId, Local, LocalId, Module, Name, Scopes,
+    SourceMap,
+};
+
+/// What a name in an expression refers to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    Local(LocalId),
+    Def(DefId),
+}
+
+/// The resolved names of every definition in a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameResolution {
+    bodies: ArenaMap<Def, BodyResolution>,
+}
+
+impl NameResolution {
+    pub fn body(&self, def: DefId) -> &BodyResolution {
+        self.bodies.get(def).expect("every def is resolved")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BodyResolution {
+    scopes: Scopes,
+    exprs: ArenaMap<Expr, Resolution>,
+}
+
+impl BodyResolution {
+    pub fn scopes(&self) -> &Scopes {
+        &self.scopes
+    }
+
+    /// What a name expression refers to, or `None` if it isn't a name or couldn't be resolved.
+    pub fn resolve_expr(&self, expr: ExprId) -> Option<Resolution> {
+        self.exprs.get(expr).copied()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolveDiagnostic {
+    pub kind: ResolveDiagnosticKind,
+    pub span: TextRange,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveDiagnosticKind {
+    UnresolvedName { name: Name },
+    DuplicateParam { name: Name, first: TextRange },
+    UnusedVariable { name: Name },
+}
+
+impl fmt::Display for ResolveDiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnresolvedName { name } => write!(f, "cannot find `{name}` in this scope"),
+            Self::DuplicateParam { name, .. } => {
+                write!(f, "parameter `{name}` is defined more than once")
+            }
+            Self::UnusedVariable { name } => write!(f, "unused variable `{name}`"),
+        }
+    }
+}
+
+impl ResolveDiagnostic {
+    pub fn message(&self) -> String {
+        self.kind.to_string()
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match &self.kind {
+            ResolveDiagnosticKind::UnresolvedName { .. } => {
+                Diagnostic::error(self.message(), self.span)
+                    .with_primary_message("not found in this scope")
+            }
+            ResolveDiagnosticKind::DuplicateParam { first, .. } => {
+                Diagnostic::error(self.message(), self.span)
+                    .with_primary_message("used as a parameter more than once")
+                    .with_label(*first, "first used here")
+            }
+            ResolveDiagnosticKind::UnusedVariable { name } => {
+                Diagnostic::warning(self.message(), self.span).with_help(format!(
+                    "if this is intentional, prefix it with an underscore: `_{name}`"
+                ))
+            }
+        }
+    }
+}
+
+/// Resolves every name expression in a module to the local or definition it refers to.
+///
+/// Locals are looked up first, so a parameter or `let` binding shadows a definition
+/// with the same name. When there are several definitions with the same name, the first wins.
+pub fn resolve(
+    module: &Module,
+    source_map: &SourceMap,
+) -> (NameResolution, Vec<ResolveDiagnostic>) {
+    let mut defs = HashMap::new();
+
+    for (id, def) in module.defs.iter() {
+        if let Some(name) = &def.name {
+            defs.entry(name.clone()).or_insert(id);
+        }
+    }
+
+    let mut bodies = ArenaMap::new();
+    let mut diagnostics = Vec::new();
+
+    for (id, def) in module.defs.iter() {
+        let mut resolver = BodyResolver {
+            body: &def.body,
+            source_map: source_map.body(id),
+            defs: &defs,
+            scopes: Scopes::new(&def.body),
+            exprs: ArenaMap::new(),
+            used: ArenaMap::new(),
+            diagnostics: &mut diagnostics,
+        };
+
+        resolver.resolve_body();
+
+        bodies.insert(
+            id,
+            BodyResolution {
+                scopes: resolver.scopes,
+                exprs: resolver.exprs,
+            },
+        );
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start());
+
+    (NameResolution { bodies }, diagnostics)
+}
+
+struct BodyResolver<'a> {
+    body: &'a Body,
+    source_map: &'a BodySourceMap,
+    defs: &'a HashMap<Name, DefId>,
+    scopes: Scopes,
+    exprs: ArenaMap<Expr, Resolution>,
+    used: ArenaMap<Local, ()>,
+    diagnostics: &'a mut Vec<ResolveDiagnostic>,
+}
+
+impl BodyResolver<'_> {
+    fn resolve_body(&mut self) {
+        let duplicates = self.check_duplicate_params();
+
+        for (id, expr) in self.body.exprs.iter() {
+            if let Expr::Name(name) = expr {
+                self.resolve_name(id, name);
+            }
+        }
+
+        for (local, data) in self.body.locals.iter() {
+            let Some(name) = &data.name else {
+                continue;
+            };
+
+            if self.used.get(local).is_some()
+                || name.as_str().starts_with('_')
+                || duplicates.contains(&local)
+            {
+                continue;
+            }
+
+            if let Some(span) = self.source_map.local_name_range(local) {
+                self.diagnostics.push(ResolveDiagnostic {
+                    kind: ResolveDiagnosticKind::UnusedVariable { name: name.clone() },
+                    span,
+                });
+            }
+        }
+    }
+
+    /// Reports parameters whose name was already used, returning the ones which are shadowed.
+    fn check_duplicate_params(&mut self) -> Vec<LocalId> {
+        let mut seen: HashMap<&Name, LocalId> = HashMap::new();
+        let mut shadowed = Vec::new();
+
+        for &param in &self.body.params {
+            let Some(name) = &self.body.locals[param].name else {
+                continue;
+            };
+
+            let Some(previous) = seen.insert(name, param) else {
+                continue;
+            };
+
+            shadowed.push(previous);
+
+            let span = self.source_map.local_name_range(param);
+            let first = self.source_map.local_name_range(previous);
+
+            if let (Some(span), Some(first)) = (span, first) {
+                self.diagnostics.push(ResolveDiagnostic {
+                    kind: ResolveDiagnosticKind::DuplicateParam {
+                        name: name.clone(),
+                        first,
+                    },
+                    span,
+                });
+            }
+        }
+
+        shadowed
+    }
+
+    fn resolve_name(&mut self, expr: ExprId, name: &Name) {
+        let local = self
+            .scopes
+            .scope_for_expr(expr)
+            .and_then(|scope| self.scopes.resolve_name(scope, name));
+
+        let resolution = match local {
+            Some(local) => {
+                self.used.insert(local, ());
+                Resolution::Local(local)
+            }
+            None => match self.defs.get(name) {
+                Some(&def) => Resolution::Def(def),
+                None => {
+                    if let Some(span) = self.source_map.expr_range(expr) {
+                        self.diagnostics.push(ResolveDiagnostic {
+                            kind: ResolveDiagnosticKind::UnresolvedName { name: name.clone() },
+                            span,
+                        });
+                    }
+                    return;
+                }
+            },
+        };
+
+        self.exprs.insert(expr, resolution);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diagnostics::{render, SourceFile};
+    use expect_test::{expect, Expect};
+    use parser::parse;
+
+    use super::*;
+    use crate::{lower, Stmt};
+
+    fn check_diagnostics(source: &str, expect: Expect) {
+        let (module, source_map) = lower(&parse(source).tree());
+        let (_, diagnostics) = resolve(&module, &source_map);
+
+        let actual = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                format!(
+                    "{} at {}..{}\n",
+                    diagnostic.message(),
+                    u32::from(diagnostic.span.start()),
+                    u32::from(diagnostic.span.end())
+                )
+            })
+            .collect::<String>();
+
+        expect.assert_eq(&actual);
+    }
+
+    /// The resolution of the value of each `let` statement in the first definition.
+    fn let_values(source: &str) -> Vec<Option<Resolution>> {
+        let (module, source_map) = lower(&parse(source).tree());
+        let (resolution, _) = resolve(&module, &source_map);
+        let (def, data) = module.defs.iter().next().unwrap();
+
+        data.body
+            .stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::Let { value, .. } => {
+                    let value = (*value)?;
+                    let value = match &data.body.exprs[value] {
+                        Expr::Binary { lhs, .. } => *lhs,
+                        Expr::Call { callee, .. } => *callee,
+                        _ => value,
+                    };
+                    resolution.body(def).resolve_expr(value)
+                }
+                Stmt::Expr(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shadowing() {
+        let source = "def main(x: Int) {\n    let x = x + 1;\n    let y = x;\n}";
+
+        assert_eq!(
+            let_values(source),
+            [
+                Some(Resolution::Local(LocalId::from_raw(0))),
+                Some(Resolution::Local(LocalId::from_raw(1)))
+            ]
+        );
+
+        check_diagnostics(
+            source,
+            expect![[r#"
+                unused variable `y` at 46..47
+            "#]],
+        );
+    }
+
+    #[test]
+    fn defs_and_unresolved_names() {
+        let source = "def main() {\n    let a = f(b);\n    let _c = a;\n}\ndef f(n: Int) {\n    let _m = n;\n}";
+
+        assert_eq!(
+            let_values(source),
+            [
+                Some(Resolution::Def(DefId::from_raw(1))),
+                Some(Resolution::Local(LocalId::from_raw(0)))
+            ]
+        );
+
+        check_diagnostics(
+            source,
+            expect![[r#"
+                cannot find `b` in this scope at 27..28
+            "#]],
+        );
+    }
+
+    #[test]
+    fn locals_shadow_defs() {
+        let source = "def main(f: Int) {\n    let _a = f;\n}\ndef f() {}";
+
+        assert_eq!(
+            let_values(source),
+            [Some(Resolution::Local(LocalId::from_raw(0)))]
+        );
+    }
+
+    #[test]
+    fn binding_not_in_scope_of_its_value() {
+        check_diagnostics(
+            "def main() {\n    let x = x;\n}",
+            expect![[r#"
+                unused variable `x` at 21..22
+                cannot find `x` in this scope at 25..26
+            "#]],
+        );
+    }
+
+    #[test]
+    fn duplicate_params() {
+        let source = "def f(a: Int, a: Int) {\n    let _b = a;\n}";
+
+        assert_eq!(
+            let_values(source),
+            [Some(Resolution::Local(LocalId::from_raw(1)))]
+        );
+
+        let (module, source_map) = lower(&parse(source).tree());
+        let (_, diagnostics) = resolve(&module, &source_map);
+        let file = SourceFile::new("main.rue", source);
+
+        expect![[r#"
+            error: parameter `a` is defined more than once
+             --> main.rue:1:15
+              |
+            1 | def f(a: Int, a: Int) {
+              |               ^ used as a parameter more than once
+              |       - first used here
+        "#]]
+        .assert_eq(&render(&diagnostics[0].to_diagnostic(), &file));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{Arena, ArenaMap, Body, Expr, ExprId, Idx, LocalId, Name, Stmt};
+
+pub type ScopeId = Idx<ScopeData>;
+
+/// A lexical scope, which introduces the parameters of a function or a single `let` binding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeData {
+    pub parent: Option<ScopeId>,
+    pub entries: Vec<(Name, LocalId)>,
+}
+
+/// The scopes of a function body, and which scope each expression is evaluated in.
+///
+/// Every `let` statement starts a new scope for the statements after it, so a later binding
+/// with the same name shadows an earlier one without affecting expressions before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scopes {
+    scopes: Arena<ScopeData>,
+    expr_scopes: ArenaMap<Expr, ScopeId>,
+}
+
+impl Scopes {
+    pub fn new(body: &Body) -> Self {
+        let mut scopes = Self {
+            scopes: Arena::new(),
+            expr_scopes: ArenaMap::new(),
+        };
+
+        let entries = body
+            .params
+            .iter()
+            .filter_map(|&param| Some((body.locals[param].name.clone()?, param)))
+            .collect();
+
+        let mut scope = scopes.scopes.alloc(ScopeData {
+            parent: None,
+            entries,
+        });
+
+        for stmt in &body.stmts {
+            match stmt {
+                Stmt::Let { local, value } => {
+                    if let Some(value) = value {
+                        scopes.set_scope(body, *value, scope);
+                    }
+
+                    if let Some(name) = body.locals[*local].name.clone() {
+                        scope = scopes.scopes.alloc(ScopeData {
+                            parent: Some(scope),
+                            entries: vec![(name, *local)],
+                        });
+                    }
+                }
+                Stmt::Expr(expr) => scopes.set_scope(body, *expr, scope),
+            }
+        }
+
+        if let Some(tail) = body.tail {
+            scopes.set_scope(body, tail, scope);
+        }
+
+        scopes
+    }
+
+    fn set_scope(&mut self, body: &Body, expr: ExprId, scope: ScopeId) {
+        self.expr_scopes.insert(expr, scope);
+        body.exprs[expr].walk_child_exprs(|child| self.set_scope(body, child, scope));
+    }
+
+    pub fn scope(&self, scope: ScopeId) -> &ScopeData {
+        &self.scopes[scope]
+    }
+
+    pub fn scope_for_expr(&self, expr: ExprId) -> Option<ScopeId> {
+        self.expr_scopes.get(expr).copied()
+    }
+
+    /// The scope and its ancestors, from the innermost to the outermost.
+    pub fn scope_chain(&self, scope: ScopeId) -> impl Iterator<Item = ScopeId> + '_ {
+        std::iter::successors(Some(scope), |&scope| self.scopes[scope].parent)
+    }
+
+    /// Finds the innermost local with the given name which is visible in a scope.
+    pub fn resolve_name(&self, scope: ScopeId, name: &Name) -> Option<LocalId> {
+        self.scope_chain(scope).find_map(|scope| {
+            self.scopes[scope]
+                .entries
+                .iter()
+                .rev()
+                .find(|(entry, _)| entry == name)
+                .map(|(_, local)| *local)
+        })
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use rowan::TextRange;
+use syntax::{SyntaxNode, SyntaxNodePtr};
+
+use crate::{ArenaMap, Def, DefId, Expr, ExprId, Local, LocalId};
+
+/// Maps the lowered definitions back to the syntax they came from, and the other way around.
+///
+/// This is kept apart from the [`Module`](crate::Module), so that the IR itself doesn't change
+/// when only the positions in the source do.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    pub(crate) defs: ArenaMap<Def, SyntaxNodePtr>,
+    pub(crate) def_map: HashMap<SyntaxNodePtr, DefId>,
+    pub(crate) bodies: ArenaMap<Def, BodySourceMap>,
+}
+
+impl SourceMap {
+    pub fn def_syntax(&self, def: DefId) -> Option<SyntaxNodePtr> {
+        self.defs.get(def).cloned()
+    }
+
+    /// The definition a `DefItem` or `ConstItem` node was lowered to.
+    pub fn node_def(&self, node: &SyntaxNode) -> Option<DefId> {
+        self.def_map.get(&SyntaxNodePtr::new(node)).copied()
+    }
+
+    pub fn body(&self, def: DefId) -> &BodySourceMap {
+        self.bodies
+            .get(def)
+            .expect("every def has a body source map")
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BodySourceMap {
+    pub(crate) expr_map: HashMap<SyntaxNodePtr, ExprId>,
+    pub(crate) expr_map_back: ArenaMap<Expr, SyntaxNodePtr>,
+    pub(crate) expr_ranges: ArenaMap<Expr, TextRange>,
+    pub(crate) local_map: HashMap<SyntaxNodePtr, LocalId>,
+    pub(crate) local_map_back: ArenaMap<Local, SyntaxNodePtr>,
+    pub(crate) local_name_ranges: ArenaMap<Local, TextRange>,
+    pub(crate) local_type_ranges: ArenaMap<Local, TextRange>,
+    pub(crate) return_type_range: Option<TextRange>,
+}
+
+impl BodySourceMap {
+    /// The node an expression was lowered from.
+    ///
+    /// Missing expressions point to the `Missing` or `Error` node in their place, if there is one.
+    pub fn expr_syntax(&self, expr: ExprId) -> Option<SyntaxNodePtr> {
+        self.expr_map_back.get(expr).cloned()
+    }
+
+    /// The range of an expression without its surrounding trivia, for pointing at in diagnostics.
+    pub fn expr_range(&self, expr: ExprId) -> Option<TextRange> {
+        self.expr_ranges.get(expr).copied()
+    }
+
+    /// The expression a node was lowered to. Parenthesized expressions map to their contents.
+    pub fn node_expr(&self, node: &SyntaxNode) -> Option<ExprId> {
+        self.expr_map.get(&SyntaxNodePtr::new(node)).copied()
+    }
+
+    /// The local a `Param` or `LetStmt` node was lowered to.
+    pub fn node_local(&self, node: &SyntaxNode) -> Option<LocalId> {
+        self.local_map.get(&SyntaxNodePtr::new(node)).copied()
+    }
+
+    /// The `Param` or `LetStmt` node a local was lowered from.
+    pub fn local_syntax(&self, local: LocalId) -> Option<SyntaxNodePtr> {
+        self.local_map_back.get(local).cloned()
+    }
+
+    /// The range of the name of a local, if it has one.
+    pub fn local_name_range(&self, local: LocalId) -> Option<TextRange> {
+        self.local_name_ranges.get(local).copied()
+    }
+
+    /// The range of the type annotation of a local, if it was written.
+    pub fn local_type_range(&self, local: LocalId) -> Option<TextRange> {
+        self.local_type_ranges.get(local).copied()
+    }
+
+    /// The range of the return type of a function or the type of a constant, if it was written.
+    pub fn return_type_range(&self) -> Option<TextRange> {
+        self.return_type_range
+    }
+}
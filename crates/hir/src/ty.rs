@@ -0,0 +1,98 @@
+use std::fmt;
+
+use crate::{Name, TypeRef};
+
+/// The type of a value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Ty {
+    Int,
+    Bool,
+    String,
+    Bytes,
+    Unit,
+    Fn {
+        params: Vec<Ty>,
+        ret: Box<Ty>,
+    },
+    /// The type of something which already has an error, which is compatible with every type
+    /// so that the error isn't reported again.
+    Unknown,
+}
+
+impl Ty {
+    /// Finds a built-in type by name.
+    pub fn builtin(name: &Name) -> Option<Self> {
+        Some(match name.as_str() {
+            "Int" => Self::Int,
+            "Bool" => Self::Bool,
+            "String" => Self::String,
+            "Bytes" => Self::Bytes,
+            _ => return None,
+        })
+    }
+
+    /// Resolves a written type, or returns `None` if it doesn't exist.
+    ///
+    /// Types which couldn't be parsed are unknown, since an error was already reported for them.
+    pub fn from_type_ref(type_ref: &TypeRef) -> Option<Self> {
+        match type_ref {
+            TypeRef::Name(name) => Self::builtin(name),
+            TypeRef::Missing => Some(Self::Unknown),
+        }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown)
+    }
+
+    /// Whether a value of this type can be used where the other type is expected.
+    ///
+    /// Every string is a sequence of bytes, so strings can be used as bytes but not the other
+    /// way around.
+    pub fn is_assignable_to(&self, expected: &Ty) -> bool {
+        match (self, expected) {
+            (Self::Unknown, _) | (_, Self::Unknown) => true,
+            (Self::String, Self::Bytes) => true,
+            (
+                Self::Fn { params, ret },
+                Self::Fn {
+                    params: expected_params,
+                    ret: expected_ret,
+                },
+            ) => {
+                params.len() == expected_params.len()
+                    && params
+                        .iter()
+                        .zip(expected_params)
+                        .all(|(param, expected)| expected.is_assignable_to(param))
+                    && ret.is_assignable_to(expected_ret)
+            }
+            _ => self == expected,
+        }
+    }
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int => write!(f, "Int"),
+            Self::Bool => write!(f, "Bool"),
+            Self::String => write!(f, "String"),
+            Self::Bytes => write!(f, "Bytes"),
+            Self::Unit => write!(f, "()"),
+            Self::Fn { params, ret } => {
+                write!(f, "fn(")?;
+
+                for (i, param) in params.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{param}")?;
+                }
+
+                write!(f, ") -> {ret}")
+            }
+            Self::Unknown => write!(f, "{{unknown}}"),
+        }
+    }
+}
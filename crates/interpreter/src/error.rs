@@ -0,0 +1,81 @@
+use std::fmt;
+
+use diagnostics::Diagnostic;
+use hir::ArithmeticError;
+use rowan::TextRange;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    /// The expression which failed, if the error happened inside of the program.
+    pub span: Option<TextRange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuntimeErrorKind {
+    Arithmetic(ArithmeticError),
+    StackOverflow,
+    /// The program has an error which was reported before it was run.
+    InvalidProgram,
+    NoMain,
+    ArgCount {
+        expected: usize,
+        found: usize,
+    },
+    InvalidArg {
+        index: usize,
+        expected: String,
+    },
+}
+
+impl fmt::Display for RuntimeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Arithmetic(error) => write!(f, "{error}"),
+            Self::StackOverflow => write!(f, "maximum call depth exceeded"),
+            Self::InvalidProgram => write!(f, "cannot evaluate an expression with errors"),
+            Self::NoMain => write!(f, "no `main` function to run"),
+            Self::ArgCount { expected, found } => write!(
+                f,
+                "`main` takes {expected} argument{} but {found} {} supplied",
+                if *expected == 1 { "" } else { "s" },
+                if *found == 1 { "was" } else { "were" }
+            ),
+            Self::InvalidArg { index, expected } => {
+                write!(f, "argument {} to `main` must be `{expected}`", index + 1)
+            }
+        }
+    }
+}
+
+impl From<ArithmeticError> for RuntimeErrorKind {
+    fn from(error: ArithmeticError) -> Self {
+        Self::Arithmetic(error)
+    }
+}
+
+impl RuntimeError {
+    pub fn message(&self) -> String {
+        self.kind.to_string()
+    }
+
+    /// Converts the error to a diagnostic, or `None` if it didn't happen at a point in the source.
+    pub fn to_diagnostic(&self) -> Option<Diagnostic> {
+        Some(Diagnostic::error(self.message(), self.span?))
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(
+                f,
+                "{} at {}..{}",
+                self.kind,
+                u32::from(span.start()),
+                u32::from(span.end())
+            ),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
@@ -0,0 +1,345 @@
+use hir::{
+    fold_binary, fold_unary, Analysis, ArenaMap, BinaryOp, Body, BodyResolution, BodySourceMap,
+    DefId, DefKind, Expr, ExprId, Literal, Local, Resolution, Stmt,
+};
+
+use crate::{RuntimeError, RuntimeErrorKind, Value};
+
+/// How deeply calls can be nested before the program is stopped, to catch unbounded recursion.
+const MAX_DEPTH: usize = 256;
+
+/// Evaluates functions by walking their HIR.
+///
+/// Programs with errors can still be run, and only fail once an expression with an error
+/// is actually evaluated.
+pub struct Interpreter<'a> {
+    analysis: &'a Analysis,
+    depth: usize,
+}
+
+struct Frame<'a> {
+    body: &'a Body,
+    source_map: &'a BodySourceMap,
+    resolution: &'a BodyResolution,
+    locals: ArenaMap<Local, Value>,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(analysis: &'a Analysis) -> Self {
+        Self { analysis, depth: 0 }
+    }
+
+    /// Calls the `main` function, after checking the arguments against its parameter types.
+    pub fn run_main(&mut self, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let error = |kind| RuntimeError { kind, span: None };
+
+        let (def, _) = self
+            .analysis
+            .module
+            .defs
+            .iter()
+            .find(|(_, def)| {
+                def.name
+                    .as_ref()
+                    .is_some_and(|name| name.as_str() == "main")
+            })
+            .ok_or_else(|| error(RuntimeErrorKind::NoMain))?;
+
+        let signature = self.analysis.types.signature(def);
+
+        if signature.params.len() != args.len() {
+            return Err(error(RuntimeErrorKind::ArgCount {
+                expected: signature.params.len(),
+                found: args.len(),
+            }));
+        }
+
+        for (index, (arg, ty)) in args.iter().zip(&signature.params).enumerate() {
+            if !arg.has_type(ty) {
+                return Err(error(RuntimeErrorKind::InvalidArg {
+                    index,
+                    expected: ty.to_string(),
+                }));
+            }
+        }
+
+        self.call(def, args)
+    }
+
+    /// Calls a function with arguments, which are assumed to match its parameters.
+    pub fn call(&mut self, def: DefId, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(RuntimeError {
+                kind: RuntimeErrorKind::StackOverflow,
+                span: None,
+            });
+        }
+
+        let body = &self.analysis.module.defs[def].body;
+        let mut frame = Frame {
+            body,
+            source_map: self.analysis.source_map.body(def),
+            resolution: self.analysis.resolution.body(def),
+            locals: ArenaMap::new(),
+        };
+
+        for (&param, arg) in body.params.iter().zip(args) {
+            frame.locals.insert(param, arg);
+        }
+
+        self.depth += 1;
+        let result = self.eval_body(&mut frame);
+        self.depth -= 1;
+
+        result
+    }
+
+    /// Uses the value of a constant which was folded at compile time, and otherwise evaluates it
+    /// to find the error which stopped it from being folded.
+    fn eval_const(&mut self, def: DefId) -> Result<Value, RuntimeError> {
+        let analysis = self.analysis;
+
+        match analysis.consts.const_value(&analysis.module, def) {
+            Some(value) => Ok(value.clone().into()),
+            None => self.call(def, Vec::new()),
+        }
+    }
+
+    fn eval_body(&mut self, frame: &mut Frame) -> Result<Value, RuntimeError> {
+        for stmt in &frame.body.stmts {
+            match stmt {
+                Stmt::Let { local, value } => {
+                    if let Some(value) = value {
+                        let value = self.eval(frame, *value)?;
+                        frame.locals.insert(*local, value);
+                    }
+                }
+                Stmt::Expr(expr) => {
+                    self.eval(frame, *expr)?;
+                }
+            }
+        }
+
+        match frame.body.tail {
+            Some(tail) => self.eval(frame, tail),
+            None => Ok(Value::Unit),
+        }
+    }
+
+    fn eval(&mut self, frame: &mut Frame, expr: ExprId) -> Result<Value, RuntimeError> {
+        let error = |kind| RuntimeError {
+            kind,
+            span: frame.source_map.expr_range(expr),
+        };
+
+        match &frame.body.exprs[expr] {
+            Expr::Literal(literal) => match literal {
+                Literal::Bool(value) => Ok(Value::Bool(*value)),
+                Literal::Int { value, .. } => Ok(Value::Int(value.clone())),
+                Literal::String(value) => Ok(Value::from(value.as_str())),
+            },
+            Expr::Name(_) => match frame.resolution.resolve_expr(expr) {
+                Some(Resolution::Local(local)) => frame
+                    .locals
+                    .get(local)
+                    .cloned()
+                    .ok_or_else(|| error(RuntimeErrorKind::InvalidProgram)),
+                Some(Resolution::Def(def)) => match self.analysis.module.defs[def].kind {
+                    DefKind::Fn => Ok(Value::Fn(def)),
+                    DefKind::Const => self.eval_const(def),
+                },
+                None => Err(error(RuntimeErrorKind::InvalidProgram)),
+            },
+            Expr::Binary { op, lhs, rhs } => {
+                let (op, rhs) = (*op, *rhs);
+                let lhs = self.eval(frame, *lhs)?;
+
+                match (op, &lhs) {
+                    (BinaryOp::LazyAnd, Value::Bool(false)) => return Ok(lhs),
+                    (BinaryOp::LazyOr, Value::Bool(true)) => return Ok(lhs),
+                    _ => {}
+                }
+
+                let rhs = self.eval(frame, rhs)?;
+                binary(op, lhs, rhs).map_err(error)
+            }
+            Expr::Unary { op, expr: inner } => {
+                let op = *op;
+                let value = self.eval(frame, *inner)?;
+
+                value
+                    .to_const()
+                    .and_then(|value| fold_unary(op, &value))
+                    .map(Value::from)
+                    .ok_or_else(|| error(RuntimeErrorKind::InvalidProgram))
+            }
+            Expr::Call { callee, args } => {
+                let callee = self.eval(frame, *callee)?;
+
+                let args = args
+                    .iter()
+                    .map(|&arg| self.eval(frame, arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                match callee {
+                    Value::Fn(def) => self.call(def, args).map_err(|mut call_error| {
+                        // Errors from entering the function point at the call which caused them.
+                        if call_error.span.is_none() {
+                            call_error.span = frame.source_map.expr_range(expr);
+                        }
+                        call_error
+                    }),
+                    _ => Err(error(RuntimeErrorKind::InvalidProgram)),
+                }
+            }
+            Expr::Missing | Expr::Field { .. } | Expr::Try { .. } => {
+                Err(error(RuntimeErrorKind::InvalidProgram))
+            }
+        }
+    }
+}
+
+fn binary(op: BinaryOp, lhs: Value, rhs: Value) -> Result<Value, RuntimeErrorKind> {
+    let (Some(lhs), Some(rhs)) = (lhs.to_const(), rhs.to_const()) else {
+        return Err(RuntimeErrorKind::InvalidProgram);
+    };
+
+    match fold_binary(op, &lhs, &rhs)? {
+        Some(value) => Ok(value.into()),
+        None => Err(RuntimeErrorKind::InvalidProgram),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diagnostics::{render, SourceFile};
+    use expect_test::expect;
+    use parser::parse;
+
+    use super::*;
+
+    fn run(source: &str, args: Vec<Value>) -> String {
+        let analysis = Analysis::new(&parse(source).tree());
+
+        match Interpreter::new(&analysis).run_main(args) {
+            Ok(value) => value.to_string(),
+            Err(error) => match error.span {
+                Some(span) => format!("{} at `{}`", error.kind, &source[span]),
+                None => error.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn calls_and_arithmetic() {
+        let source =
+            "def main(a: Int, b: Int) -> Int {\n    let sum = add(a, b);\n    sum * 2 - 7 / 2\n}\n\
+                      def add(x: Int, y: Int) -> Int {\n    x + y\n}";
+
+        assert_eq!(run(source, vec![Value::from(3), Value::from(4)]), "11");
+    }
+
+    #[test]
+    fn big_integers() {
+        let source = "def main() -> Int {\n    (1 << 100) + 0xff - ~0\n}";
+        assert_eq!(run(source, vec![]), "1267650600228229401496703205632");
+    }
+
+    #[test]
+    fn division_rounds_down() {
+        let source = "def main(a: Int, b: Int) -> Bool {\n    a / b == -4 && a % b == 1\n}";
+        assert_eq!(run(source, vec![Value::from(-7), Value::from(2)]), "true");
+    }
+
+    #[test]
+    fn strings_and_booleans() {
+        let source = "def main(name: String) -> Bool {\n    let greeting = 'hello, ' + name;\n    (greeting == 'hello, world') & !false ^ false\n}";
+        assert_eq!(run(source, vec![Value::from("world")]), "true");
+    }
+
+    #[test]
+    fn short_circuit_recursion() {
+        let source = "def main(n: Int) -> Bool {\n    count(n)\n}\n\
+                      def count(n: Int) -> Bool {\n    n == 0 || count(n - 1)\n}";
+
+        assert_eq!(run(source, vec![Value::from(100)]), "true");
+        assert_eq!(
+            run(source, vec![Value::from(1000)]),
+            "maximum call depth exceeded at `count(n - 1)`"
+        );
+    }
+
+    #[test]
+    fn runtime_errors() {
+        let source = "def main(a: Int, b: Int) -> Int {\n    let x = a / b;\n    x << 70000\n}";
+
+        assert_eq!(
+            run(source, vec![Value::from(1), Value::from(0)]),
+            "attempt to divide by zero at `a / b`"
+        );
+        assert_eq!(
+            run(source, vec![Value::from(1), Value::from(1)]),
+            "attempt to shift by 70000, which is out of range at `x << 70000`"
+        );
+        assert_eq!(
+            run("def main() -> Int {\n    -1 >>> 1\n}", vec![]),
+            "attempt to apply `>>>` to a negative number at `-1 >>> 1`"
+        );
+    }
+
+    #[test]
+    fn main_arguments() {
+        let source = "def main(a: Int, s: String) {}";
+
+        assert_eq!(
+            run(source, vec![Value::from(1)]),
+            "`main` takes 2 arguments but 1 was supplied"
+        );
+        assert_eq!(
+            run(source, vec![Value::from(true), Value::from("s")]),
+            "argument 1 to `main` must be `Int`"
+        );
+        assert_eq!(run(source, vec![Value::from(1), Value::from("s")]), "()");
+        assert_eq!(run("def other() {}", vec![]), "no `main` function to run");
+    }
+
+    #[test]
+    fn consts() {
+        let source = "const BASE: Int = 1000;\nconst GREETING: String = 'hello';\n\
+                      def main(x: Int) -> Bool {\n    x * BASE > LIMIT && GREETING == 'hello'\n}\n\
+                      const LIMIT: Int = BASE * BASE;";
+
+        assert_eq!(run(source, vec![Value::from(999)]), "false");
+        assert_eq!(run(source, vec![Value::from(1001)]), "true");
+    }
+
+    #[test]
+    fn errors_are_only_reported_when_evaluated() {
+        let source = "def main(a: Bool) -> Int {\n    let _x = a || missing;\n    1\n}";
+
+        assert_eq!(run(source, vec![Value::from(true)]), "1");
+        assert_eq!(
+            run(source, vec![Value::from(false)]),
+            "cannot evaluate an expression with errors at `missing`"
+        );
+    }
+
+    #[test]
+    fn render_runtime_error() {
+        let source = "def main() -> Int {\n    let zero = 0;\n    10 % zero\n}";
+        let analysis = Analysis::new(&parse(source).tree());
+        let error = Interpreter::new(&analysis).run_main(vec![]).unwrap_err();
+
+        expect![[r#"
+            error: attempt to divide by zero
+             --> main.rue:3:5
+              |
+            3 |     10 % zero
+              |     ^^^^^^^^^
+        "#]]
+        .assert_eq(&render(
+            &error.to_diagnostic().unwrap(),
+            &SourceFile::new("main.rue", source),
+        ));
+    }
+}
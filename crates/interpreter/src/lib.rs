@@ -0,0 +1,7 @@
+mod error;
+mod interpreter;
+mod value;
+
+pub use error::{RuntimeError, RuntimeErrorKind};
+pub use interpreter::Interpreter;
+pub use value::Value;
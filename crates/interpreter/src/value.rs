@@ -0,0 +1,87 @@
+use std::fmt;
+
+use hir::{ConstValue, DefId, Ty};
+use num_bigint::BigInt;
+
+/// A value produced at runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Int(BigInt),
+    Bool(bool),
+    /// Both strings and bytes, since every string is a sequence of bytes.
+    Bytes(Vec<u8>),
+    Unit,
+    Fn(DefId),
+}
+
+impl Value {
+    /// Whether the value can be used where the type is expected.
+    pub fn has_type(&self, ty: &Ty) -> bool {
+        match (self, ty) {
+            (_, Ty::Unknown) => true,
+            (Self::Int(_), Ty::Int) | (Self::Bool(_), Ty::Bool) | (Self::Unit, Ty::Unit) => true,
+            (Self::Bytes(bytes), Ty::String) => std::str::from_utf8(bytes).is_ok(),
+            (Self::Bytes(_), Ty::Bytes) => true,
+            (Self::Fn(_), Ty::Fn { .. }) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Value {
+    /// The value as a constant, if it's one which can be known at compile time.
+    pub fn to_const(&self) -> Option<ConstValue> {
+        match self {
+            Self::Int(value) => Some(ConstValue::Int(value.clone())),
+            Self::Bool(value) => Some(ConstValue::Bool(*value)),
+            Self::Bytes(bytes) => Some(ConstValue::Bytes(bytes.clone())),
+            Self::Unit | Self::Fn(_) => None,
+        }
+    }
+}
+
+impl From<ConstValue> for Value {
+    fn from(value: ConstValue) -> Self {
+        match value {
+            ConstValue::Int(value) => Self::Int(value),
+            ConstValue::Bool(value) => Self::Bool(value),
+            ConstValue::Bytes(bytes) => Self::Bytes(bytes),
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Self::Int(value.into())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::Bytes(value.as_bytes().to_vec())
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Bytes(bytes) => match std::str::from_utf8(bytes) {
+                Ok(text) => write!(f, "{text:?}"),
+                Err(_) => {
+                    write!(f, "0x")?;
+                    bytes.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+                }
+            },
+            Self::Unit => write!(f, "()"),
+            Self::Fn(def) => write!(f, "<fn {}>", def.into_raw()),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{Token, TokenKind};
+
+/// An operator made up of several single character tokens written without any space between them.
+///
+/// The lexer emits these as separate tokens, so that the parser is free to split them
+/// where needed, but the parts can be glued back together with [`Glued::at`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Glued {
+    LazyAnd,
+    LazyOr,
+    EqualTo,
+    NotEqual,
+    LessThanEquals,
+    GreaterThanEquals,
+    LeftShift,
+    RightShift,
+    UnsignedRightShift,
+}
+
+impl Glued {
+    /// Every glued operator, longest first so that the first match is the longest one.
+    pub const ALL: [Glued; 9] = [
+        Self::UnsignedRightShift,
+        Self::LazyAnd,
+        Self::LazyOr,
+        Self::EqualTo,
+        Self::NotEqual,
+        Self::LessThanEquals,
+        Self::GreaterThanEquals,
+        Self::LeftShift,
+        Self::RightShift,
+    ];
+
+    pub fn parts(self) -> &'static [TokenKind] {
+        use TokenKind::*;
+
+        match self {
+            Self::LazyAnd => &[And, And],
+            Self::LazyOr => &[Or, Or],
+            Self::EqualTo => &[Equals, Equals],
+            Self::NotEqual => &[Exclamation, Equals],
+            Self::LessThanEquals => &[LessThan, Equals],
+            Self::GreaterThanEquals => &[GreaterThan, Equals],
+            Self::LeftShift => &[LessThan, LessThan],
+            Self::RightShift => &[GreaterThan, GreaterThan],
+            Self::UnsignedRightShift => &[GreaterThan, GreaterThan, GreaterThan],
+        }
+    }
+
+    pub fn text(self) -> &'static str {
+        match self {
+            Self::LazyAnd => "&&",
+            Self::LazyOr => "||",
+            Self::EqualTo => "==",
+            Self::NotEqual => "!=",
+            Self::LessThanEquals => "<=",
+            Self::GreaterThanEquals => ">=",
+            Self::LeftShift => "<<",
+            Self::RightShift => ">>",
+            Self::UnsignedRightShift => ">>>",
+        }
+    }
+
+    /// Finds the longest glued operator at the start of the tokens, if there is one.
+    pub fn at(tokens: &[Token]) -> Option<Glued> {
+        Self::ALL.into_iter().find(|glued| glued.matches(tokens))
+    }
+
+    fn matches(self, tokens: &[Token]) -> bool {
+        let parts = self.parts();
+
+        if tokens.len() < parts.len() {
+            return false;
+        }
+
+        parts
+            .iter()
+            .zip(tokens)
+            .enumerate()
+            .all(|(i, (&kind, token))| {
+                token.kind == kind && (i == parts.len() - 1 || token.spacing.is_joint())
+            })
+    }
+}
@@ -3,7 +3,7 @@ use std::{iter::Peekable, str::Chars};
 use crate::{
     base::Base,
     chars::{is_ident_continue, is_ident_start, is_whitespace},
-    Token, TokenKind,
+    Spacing, Token, TokenKind,
 };
 
 pub struct Lexer<'a> {
@@ -64,10 +64,12 @@ impl<'a> Lexer<'a> {
             ',' => TokenKind::Comma,
             '.' => TokenKind::Dot,
             '!' => TokenKind::Exclamation,
+            '~' => TokenKind::Tilde,
+            '?' => TokenKind::Question,
             '=' => TokenKind::Equals,
             '"' => self.string('"'),
             '\'' => self.string('\''),
-            c @ '0'..='9' => self.integer(c),
+            c @ '0'..='9' => self.number(start, c),
             c if is_ident_start(c) => self.ident(start),
             c if is_whitespace(c) => self.whitespace(),
             _ => TokenKind::Error,
@@ -77,6 +79,18 @@ impl<'a> Lexer<'a> {
             text: &self.source[start..self.cursor],
             kind,
             span: start..self.cursor,
+            spacing: self.spacing(),
+        }
+    }
+
+    fn spacing(&self) -> Spacing {
+        let rest = &self.source[self.cursor..];
+
+        match rest.chars().next() {
+            None => Spacing::Alone,
+            Some(c) if is_whitespace(c) => Spacing::Alone,
+            Some(_) if rest.starts_with("//") || rest.starts_with("/*") => Spacing::Alone,
+            Some(_) => Spacing::Joint,
         }
     }
 
@@ -91,7 +105,7 @@ impl<'a> Lexer<'a> {
         TokenKind::String { is_terminated }
     }
 
-    fn integer(&mut self, digit: char) -> TokenKind {
+    fn number(&mut self, start: usize, digit: char) -> TokenKind {
         let mut base = Base::Decimal;
 
         let has_digits = if digit == '0' {
@@ -121,12 +135,62 @@ impl<'a> Lexer<'a> {
             true
         };
 
-        TokenKind::Integer {
-            base,
-            is_empty: !has_digits,
+        if base != Base::Decimal {
+            return TokenKind::Integer {
+                base,
+                is_empty: !has_digits,
+                suffix_start: self.suffix(start),
+            };
+        }
+
+        let mut is_float = false;
+        let mut is_empty_exponent = false;
+
+        if self.char() == '.' && self.second().is_ascii_digit() {
+            self.bump();
+            self.eat_decimal_digits();
+            is_float = true;
+        }
+
+        if matches!(self.char(), 'e' | 'E') {
+            self.bump();
+            if matches!(self.char(), '+' | '-') {
+                self.bump();
+            }
+            is_empty_exponent = !self.eat_decimal_digits();
+            is_float = true;
+        }
+
+        let suffix_start = self.suffix(start);
+
+        if is_float {
+            TokenKind::Float {
+                is_empty_exponent,
+                suffix_start,
+            }
+        } else {
+            TokenKind::Integer {
+                base,
+                is_empty: false,
+                suffix_start,
+            }
         }
     }
 
+    fn suffix(&mut self, start: usize) -> Option<u32> {
+        if !is_ident_start(self.char()) {
+            return None;
+        }
+
+        let suffix_start = self.cursor - start;
+
+        while is_ident_continue(self.char()) {
+            self.bump();
+        }
+
+        Some(suffix_start as u32)
+    }
+
     fn eat_decimal_digits(&mut self) -> bool {
         let mut has_digits = false;
         loop {
@@ -168,6 +232,7 @@ impl<'a> Lexer<'a> {
 
         match &self.source[start..self.cursor] {
             "def" => TokenKind::DefKw,
+            "const" => TokenKind::ConstKw,
             "let" => TokenKind::LetKw,
             "true" => TokenKind::TrueKw,
             "false" => TokenKind::FalseKw,
@@ -217,6 +282,10 @@ impl<'a> Lexer<'a> {
         *self.chars.peek().unwrap_or(&'\0')
     }
 
+    fn second(&self) -> char {
+        self.source[self.cursor..].chars().nth(1).unwrap_or('\0')
+    }
+
     fn bump(&mut self) -> char {
         match self.chars.next() {
             Some(c) => {
@@ -245,6 +314,7 @@ impl<'a> Iterator for Lexer<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Glued;
 
     fn lex(source: &str) -> Vec<TokenKind> {
         Lexer::new(source)
@@ -273,16 +343,102 @@ mod tests {
             lex("42"),
             &[TokenKind::Integer {
                 base: Base::Decimal,
-                is_empty: false
+                is_empty: false,
+                suffix_start: None
+            }]
+        )
+    }
+
+    #[test]
+    fn integer_suffix() {
+        assert_eq!(
+            lex("100u64"),
+            &[TokenKind::Integer {
+                base: Base::Decimal,
+                is_empty: false,
+                suffix_start: Some(3)
+            }]
+        )
+    }
+
+    #[test]
+    fn hexadecimal_suffix() {
+        assert_eq!(
+            lex("0xffu8"),
+            &[TokenKind::Integer {
+                base: Base::Hexadecimal,
+                is_empty: false,
+                suffix_start: Some(4)
             }]
         )
     }
 
+    #[test]
+    fn float() {
+        assert_eq!(
+            lex("1.5"),
+            &[TokenKind::Float {
+                is_empty_exponent: false,
+                suffix_start: None
+            }]
+        )
+    }
+
+    #[test]
+    fn float_exponent() {
+        assert_eq!(
+            lex("2e10"),
+            &[TokenKind::Float {
+                is_empty_exponent: false,
+                suffix_start: None
+            }]
+        );
+        assert_eq!(
+            lex("1.5E-3f64"),
+            &[TokenKind::Float {
+                is_empty_exponent: false,
+                suffix_start: Some(6)
+            }]
+        );
+    }
+
+    #[test]
+    fn float_empty_exponent() {
+        assert_eq!(
+            lex("2e+"),
+            &[TokenKind::Float {
+                is_empty_exponent: true,
+                suffix_start: None
+            }]
+        )
+    }
+
+    #[test]
+    fn integer_then_field() {
+        assert_eq!(
+            lex("1.foo"),
+            &[
+                TokenKind::Integer {
+                    base: Base::Decimal,
+                    is_empty: false,
+                    suffix_start: None
+                },
+                TokenKind::Dot,
+                TokenKind::Ident
+            ]
+        )
+    }
+
     #[test]
     fn def_kw() {
         assert_eq!(lex("def"), &[TokenKind::DefKw])
     }
 
+    #[test]
+    fn const_kw() {
+        assert_eq!(lex("const"), &[TokenKind::ConstKw])
+    }
+
     #[test]
     fn let_kw() {
         assert_eq!(lex("let"), &[TokenKind::LetKw])
@@ -368,6 +524,16 @@ mod tests {
         assert_eq!(lex("."), &[TokenKind::Dot])
     }
 
+    #[test]
+    fn tilde() {
+        assert_eq!(lex("~"), &[TokenKind::Tilde])
+    }
+
+    #[test]
+    fn question() {
+        assert_eq!(lex("?"), &[TokenKind::Question])
+    }
+
     #[test]
     fn whitespace() {
         assert_eq!(lex("    "), &[TokenKind::Whitespace])
@@ -387,4 +553,50 @@ mod tests {
             }]
         )
     }
+
+    #[test]
+    fn spacing() {
+        let spacing = Lexer::new("a+b - c/**/d")
+            .map(|token| token.spacing)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            spacing,
+            &[
+                Spacing::Joint,
+                Spacing::Joint,
+                Spacing::Alone,
+                Spacing::Joint,
+                Spacing::Alone,
+                Spacing::Joint,
+                Spacing::Alone,
+                Spacing::Joint,
+                Spacing::Alone,
+            ]
+        )
+    }
+
+    fn glued(source: &str) -> Option<Glued> {
+        Glued::at(&Lexer::new(source).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn glued_operators() {
+        for glued_op in Glued::ALL {
+            assert_eq!(glued(glued_op.text()), Some(glued_op));
+        }
+    }
+
+    #[test]
+    fn glued_longest_match() {
+        assert_eq!(glued(">>>"), Some(Glued::UnsignedRightShift));
+        assert_eq!(glued(">> >"), Some(Glued::RightShift));
+    }
+
+    #[test]
+    fn glued_requires_joint() {
+        assert_eq!(glued("& &"), None);
+        assert_eq!(glued("=/**/="), None);
+        assert_eq!(glued("&"), None);
+    }
 }
@@ -1,10 +1,16 @@
 mod base;
 mod chars;
+mod glued;
 mod lexer;
+mod relex;
+mod spacing;
 mod token;
 mod token_kind;
 
 pub use crate::lexer::Lexer;
 pub use base::Base;
+pub use glued::Glued;
+pub use relex::Relex;
+pub use spacing::Spacing;
 pub use token::Token;
 pub use token_kind::TokenKind;
@@ -0,0 +1,113 @@
+use crate::{Lexer, TokenKind};
+
+/// The result of relexing a single token after its text was edited.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Relex {
+    pub kind: TokenKind,
+    pub is_kind_changed: bool,
+}
+
+impl<'a> Lexer<'a> {
+    /// Relexes a single token after an edit, along with the tokens right next to it.
+    ///
+    /// `before` and `after` must be the text of the neighboring tokens only, rather than the
+    /// rest of the source, since they're lexed again together with the edited text. That's
+    /// needed to check that the edit doesn't merge with them, such as typing `/` right after
+    /// a `/`. Returns `None` if the edited text no longer lexes as exactly one token in between
+    /// its neighbors, in which case the surrounding text must be relexed as well.
+    pub fn relex(before: &str, old: &str, edited: &str, after: &str) -> Option<Relex> {
+        let old_kind = Lexer::new(old).next()?.kind;
+
+        let source = format!("{before}{edited}{after}");
+        let start = before.len();
+        let end = start + edited.len();
+
+        let tokens = Lexer::new(&source).collect::<Vec<_>>();
+        let index = tokens.iter().position(|token| token.span.start == start)?;
+        let token = &tokens[index];
+
+        if token.span.end != end {
+            return None;
+        }
+
+        // The tokens before the edit can still change kind without moving a boundary,
+        // since the lexer looks ahead past the end of a token.
+        let before_kinds = Lexer::new(before).map(|token| token.kind);
+        if !before_kinds.eq(tokens[..index].iter().map(|token| token.kind)) {
+            return None;
+        }
+
+        Some(Relex {
+            kind: token.kind,
+            is_kind_changed: token.kind != old_kind,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relex_ident() {
+        assert_eq!(
+            Lexer::relex("(", "value", "values", ")"),
+            Some(Relex {
+                kind: TokenKind::Ident,
+                is_kind_changed: false,
+            })
+        );
+    }
+
+    #[test]
+    fn relex_keyword() {
+        assert_eq!(
+            Lexer::relex(" ", "lets", "let", " "),
+            Some(Relex {
+                kind: TokenKind::LetKw,
+                is_kind_changed: true,
+            })
+        );
+    }
+
+    #[test]
+    fn relex_string_and_comment() {
+        assert_eq!(
+            Lexer::relex("= ", "'abc'", "'ab c'", ";"),
+            Some(Relex {
+                kind: TokenKind::String {
+                    is_terminated: true
+                },
+                is_kind_changed: false,
+            })
+        );
+        assert_eq!(
+            Lexer::relex("", "// hello", "// hello world", "\n"),
+            Some(Relex {
+                kind: TokenKind::LineComment,
+                is_kind_changed: false,
+            })
+        );
+    }
+
+    #[test]
+    fn relex_unterminated() {
+        assert_eq!(
+            Lexer::relex("= ", "'abc'", "'abc", ";"),
+            None,
+            "the string should continue past the semicolon"
+        );
+        assert_eq!(
+            Lexer::relex("", "/* a */", "/* a ", " b"),
+            None,
+            "the comment should continue past its end"
+        );
+    }
+
+    #[test]
+    fn relex_merges_with_neighbors() {
+        assert_eq!(Lexer::relex("/", "+", "/", "x"), None);
+        assert_eq!(Lexer::relex("x", " ", "", "y"), None);
+        assert_eq!(Lexer::relex("", "x", "x y", ""), None);
+    }
+}
@@ -0,0 +1,12 @@
+/// Whether a token is immediately followed by the next one, with no trivia in between.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Spacing {
+    Alone,
+    Joint,
+}
+
+impl Spacing {
+    pub fn is_joint(self) -> bool {
+        self == Self::Joint
+    }
+}
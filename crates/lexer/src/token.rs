@@ -1,10 +1,17 @@
 use std::ops::Range;
 
-use crate::TokenKind;
+use crate::{Spacing, TokenKind};
 
 #[derive(Debug)]
 pub struct Token<'a> {
     pub text: &'a str,
     pub kind: TokenKind,
     pub span: Range<usize>,
+    pub spacing: Spacing,
+}
+
+impl<'a> Token<'a> {
+    pub fn is_joint(&self) -> bool {
+        self.spacing.is_joint()
+    }
 }
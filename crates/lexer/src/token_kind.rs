@@ -3,9 +3,20 @@ use crate::base::Base;
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TokenKind {
     Ident,
-    String { is_terminated: bool },
-    Integer { base: Base, is_empty: bool },
+    String {
+        is_terminated: bool,
+    },
+    Integer {
+        base: Base,
+        is_empty: bool,
+        suffix_start: Option<u32>,
+    },
+    Float {
+        is_empty_exponent: bool,
+        suffix_start: Option<u32>,
+    },
     DefKw,
+    ConstKw,
     LetKw,
     TrueKw,
     FalseKw,
@@ -29,9 +40,13 @@ pub enum TokenKind {
     Comma,
     Dot,
     Exclamation,
+    Tilde,
+    Question,
     Equals,
     Whitespace,
-    BlockComment { is_terminated: bool },
+    BlockComment {
+        is_terminated: bool,
+    },
     LineComment,
     Error,
 }
@@ -43,4 +58,14 @@ impl TokenKind {
             Self::Whitespace | Self::LineComment | Self::BlockComment { .. }
         )
     }
+
+    /// The offset of the type suffix from the start of a number literal, if it has one.
+    pub fn suffix_start(self) -> Option<usize> {
+        match self {
+            Self::Integer { suffix_start, .. } | Self::Float { suffix_start, .. } => {
+                suffix_start.map(|start| start as usize)
+            }
+            _ => None,
+        }
+    }
 }
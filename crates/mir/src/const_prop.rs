@@ -0,0 +1,182 @@
+use std::collections::{HashMap, VecDeque};
+
+use hir::{fold_binary, fold_unary, ConstValue};
+
+use crate::{Block, Module, Operand, Pass, Rvalue, Stmt, VarId};
+
+/// Replaces variables which are copies of constants or other variables with what they copy,
+/// and folds operations on constants.
+///
+/// An `if` on a constant is replaced by the block it picks. Operations which would fail, such as
+/// dividing by zero, are left alone so they still fail at runtime.
+pub struct ConstPropagation;
+
+impl Pass for ConstPropagation {
+    fn name(&self) -> &'static str {
+        "const-propagation"
+    }
+
+    fn run(&self, module: &mut Module) -> bool {
+        let mut changed = false;
+
+        for function in &mut module.fns {
+            let before = function.body.clone();
+            propagate(&mut function.body, &mut HashMap::new());
+            changed |= function.body != before;
+        }
+
+        changed
+    }
+}
+
+/// Propagates values through a block, given the copies in the blocks it's nested in.
+fn propagate(block: &mut Block, copies: &mut HashMap<VarId, Operand>) {
+    let mut stmts = Vec::new();
+    let mut queue = block.stmts.drain(..).collect::<VecDeque<_>>();
+
+    while let Some(mut stmt) = queue.pop_front() {
+        if let Rvalue::If {
+            condition,
+            then_block,
+            else_block,
+        } = &mut stmt.value
+        {
+            substitute(condition, copies);
+
+            // The picked block runs in place of the `if`, followed by a copy of its result.
+            if let Operand::Const(ConstValue::Bool(value)) = condition {
+                let picked = std::mem::replace(
+                    if *value { then_block } else { else_block },
+                    Block {
+                        stmts: Vec::new(),
+                        result: Operand::Unit,
+                    },
+                );
+
+                queue.push_front(Stmt {
+                    var: stmt.var,
+                    value: Rvalue::Use(picked.result),
+                });
+                for stmt in picked.stmts.into_iter().rev() {
+                    queue.push_front(stmt);
+                }
+
+                continue;
+            }
+
+            propagate(then_block, &mut copies.clone());
+            propagate(else_block, &mut copies.clone());
+        } else {
+            stmt.value
+                .walk_operands_mut(&mut |operand| substitute(operand, copies));
+        }
+
+        if let Some(value) = fold(&stmt.value) {
+            stmt.value = Rvalue::Use(Operand::Const(value));
+        }
+
+        if let Rvalue::Use(operand) = &stmt.value {
+            copies.insert(stmt.var, operand.clone());
+        }
+
+        stmts.push(stmt);
+    }
+
+    substitute(&mut block.result, copies);
+    block.stmts = stmts;
+}
+
+fn substitute(operand: &mut Operand, copies: &HashMap<VarId, Operand>) {
+    if let Operand::Var(var) = operand {
+        if let Some(value) = copies.get(var) {
+            *operand = value.clone();
+        }
+    }
+}
+
+fn fold(value: &Rvalue) -> Option<ConstValue> {
+    match value {
+        Rvalue::Binary {
+            op,
+            lhs: Operand::Const(lhs),
+            rhs: Operand::Const(rhs),
+        } => fold_binary(*op, lhs, rhs).ok().flatten(),
+        Rvalue::Unary {
+            op,
+            operand: Operand::Const(value),
+        } => fold_unary(*op, value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use hir::Analysis;
+    use parser::parse;
+
+    use super::*;
+    use crate::lower;
+
+    fn check(source: &str, expect: Expect) {
+        let mut module = lower(&Analysis::new(&parse(source).tree()));
+        ConstPropagation.run(&mut module);
+        expect.assert_eq(&module.debug_mir());
+    }
+
+    #[test]
+    fn fold_through_lets() {
+        check(
+            "def main(a: Int) -> Int {\n    let x = 2;\n    let y = x * 3 + 1;\n    let z = y;\n    a + z - -y\n}",
+            expect![[r#"
+                def main(a#0) {
+                    let x#1 = 2;
+                    let t#2 = 6;
+                    let y#3 = 7;
+                    let z#4 = 7;
+                    let t#5 = a#0 + 7;
+                    let t#6 = -7;
+                    let t#7 = t#5 - -7;
+                    t#7
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn pick_branch() {
+        check(
+            "const DEBUG: Bool = false;\n\
+             def main(a: Int) -> Bool {\n    DEBUG && a > 1 || a == 2 * 3\n}",
+            expect![[r#"
+                def main(a#0) {
+                    let t#2 = false;
+                    let t#3 = 6;
+                    let t#4 = a#0 == 6;
+                    let t#5 = t#4;
+                    t#4
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn keep_failing_operations() {
+        check(
+            "def main() -> Int {\n    let zero = 0;\n    (1 / zero) + (1 << -1) + (-4 >>> 1)\n}",
+            expect![[r#"
+                def main() {
+                    let zero#0 = 0;
+                    let t#1 = 1 / 0;
+                    let t#2 = -1;
+                    let t#3 = 1 << -1;
+                    let t#4 = t#1 + t#3;
+                    let t#5 = -4;
+                    let t#6 = -4 >>> 1;
+                    let t#7 = t#4 + t#6;
+                    t#7
+                }
+            "#]],
+        );
+    }
+}
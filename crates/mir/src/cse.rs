@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use crate::{Block, Module, Operand, Pass, Rvalue, VarId};
+
+/// Replaces operations which were already computed with the variable that holds their result.
+///
+/// Values are available in the rest of the block they're computed in and the blocks nested in
+/// it. Calls are included, since functions have no side effects, and if the first call failed
+/// the second would never be reached.
+pub struct CommonSubexpressions;
+
+impl Pass for CommonSubexpressions {
+    fn name(&self) -> &'static str {
+        "common-subexpressions"
+    }
+
+    fn run(&self, module: &mut Module) -> bool {
+        let mut changed = false;
+
+        for function in &mut module.fns {
+            changed |= eliminate(&mut function.body, &mut HashMap::new());
+        }
+
+        changed
+    }
+}
+
+fn eliminate(block: &mut Block, available: &mut HashMap<Rvalue, VarId>) -> bool {
+    let mut changed = false;
+
+    for stmt in &mut block.stmts {
+        match &mut stmt.value {
+            Rvalue::Use(_) => {}
+            Rvalue::If {
+                then_block,
+                else_block,
+                ..
+            } => {
+                changed |= eliminate(then_block, &mut available.clone());
+                changed |= eliminate(else_block, &mut available.clone());
+            }
+            value => match available.get(value) {
+                Some(&var) => {
+                    *value = Rvalue::Use(Operand::Var(var));
+                    changed = true;
+                }
+                None => {
+                    available.insert(value.clone(), stmt.var);
+                }
+            },
+        }
+    }
+
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use hir::Analysis;
+    use parser::parse;
+
+    use super::*;
+    use crate::lower;
+
+    fn check(source: &str, expect: Expect) {
+        let mut module = lower(&Analysis::new(&parse(source).tree()));
+        CommonSubexpressions.run(&mut module);
+        expect.assert_eq(&module.debug_mir());
+    }
+
+    #[test]
+    fn repeated_operations() {
+        check(
+            "def main(a: Int, b: Int) -> Int {\n    let x = a * b + 1;\n    let y = a * b + 1;\n    \
+             let z = b * a;\n    x + y + z + square(a) + square(a)\n}\n\
+             def square(n: Int) -> Int {\n    n * n\n}",
+            expect![[r#"
+                def main(a#0, b#1) {
+                    let t#2 = a#0 * b#1;
+                    let x#3 = t#2 + 1;
+                    let t#4 = t#2;
+                    let y#5 = t#4 + 1;
+                    let z#6 = b#1 * a#0;
+                    let t#7 = x#3 + y#5;
+                    let t#8 = t#7 + z#6;
+                    let t#9 = square(a#0);
+                    let t#10 = t#8 + t#9;
+                    let t#11 = t#9;
+                    let t#12 = t#10 + t#11;
+                    t#12
+                }
+                def square(n#0) {
+                    let t#1 = n#0 * n#0;
+                    t#1
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn nested_blocks() {
+        check(
+            "def main(a: Int, b: Int) -> Bool {\n    \
+             let x = a / b;\n    (a > 0 && a / b > 1) || (a > 0 && -a / b > 1)\n}",
+            expect![[r#"
+                def main(a#0, b#1) {
+                    let x#2 = a#0 / b#1;
+                    let t#3 = a#0 > 0;
+                    let t#6 = if t#3 {
+                        let t#4 = x#2;
+                        let t#5 = t#4 > 1;
+                        t#5
+                    } else {
+                        false
+                    };
+                    let t#12 = if t#6 {
+                        true
+                    } else {
+                        let t#7 = t#3;
+                        let t#11 = if t#7 {
+                            let t#8 = -a#0;
+                            let t#9 = t#8 / b#1;
+                            let t#10 = t#9 > 1;
+                            t#10
+                        } else {
+                            false
+                        };
+                        t#11
+                    };
+                    t#12
+                }
+            "#]],
+        );
+    }
+}
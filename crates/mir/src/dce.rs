@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+
+use crate::{Block, Function, Module, Operand, Pass, Rvalue, VarId};
+
+/// Removes statements whose values are never used and can't fail, and functions which can't be
+/// reached from `main`.
+///
+/// Every function is kept if there's no `main`, since there's no way to know which are used.
+pub struct DeadCodeElimination;
+
+impl Pass for DeadCodeElimination {
+    fn name(&self) -> &'static str {
+        "dead-code-elimination"
+    }
+
+    fn run(&self, module: &mut Module) -> bool {
+        let mut changed = remove_unreachable(module);
+
+        for function in &mut module.fns {
+            let before = function.body.clone();
+
+            // Removing a statement can make the ones it uses dead too.
+            loop {
+                let used = used_vars(function);
+
+                if !remove_unused(&mut function.body, &used) {
+                    break;
+                }
+            }
+
+            changed |= function.body != before;
+        }
+
+        changed
+    }
+}
+
+fn remove_unreachable(module: &mut Module) -> bool {
+    let Some(main) = module.fns.iter().find(|function| {
+        function
+            .name
+            .as_ref()
+            .is_some_and(|name| name.as_str() == "main")
+    }) else {
+        return false;
+    };
+
+    let mut reachable = HashSet::from([main.def]);
+    let mut queue = vec![main.def];
+
+    while let Some(def) = queue.pop() {
+        let Some(function) = module.function(def) else {
+            continue;
+        };
+
+        function.body.walk_operands(&mut |operand| {
+            if let Operand::Fn(def) = operand {
+                if reachable.insert(*def) {
+                    queue.push(*def);
+                }
+            }
+        });
+    }
+
+    let len = module.fns.len();
+    module
+        .fns
+        .retain(|function| reachable.contains(&function.def));
+    module.fns.len() != len
+}
+
+fn used_vars(function: &Function) -> HashSet<VarId> {
+    let mut used = HashSet::new();
+
+    function.body.walk_operands(&mut |operand| {
+        if let Operand::Var(var) = operand {
+            used.insert(*var);
+        }
+    });
+
+    used
+}
+
+fn remove_unused(block: &mut Block, used: &HashSet<VarId>) -> bool {
+    let len = block.stmts.len();
+    block
+        .stmts
+        .retain(|stmt| used.contains(&stmt.var) || !stmt.value.is_pure());
+
+    let mut changed = block.stmts.len() != len;
+
+    for stmt in &mut block.stmts {
+        if let Rvalue::If {
+            then_block,
+            else_block,
+            ..
+        } = &mut stmt.value
+        {
+            changed |= remove_unused(then_block, used);
+            changed |= remove_unused(else_block, used);
+        }
+    }
+
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use hir::Analysis;
+    use parser::parse;
+
+    use super::*;
+    use crate::lower;
+
+    fn check(source: &str, expect: Expect) {
+        let mut module = lower(&Analysis::new(&parse(source).tree()));
+        DeadCodeElimination.run(&mut module);
+        expect.assert_eq(&module.debug_mir());
+    }
+
+    #[test]
+    fn unused_values() {
+        check(
+            "def main(a: Int, b: Int) -> Int {\n    let unused = a * b + 1;\n    a + 1;\n    \
+             a / b;\n    a / 2;\n    a == 1 && b == 2;\n    a != b && b / a > 1;\n    a\n}",
+            expect![[r#"
+                def main(a#0, b#1) {
+                    let t#5 = a#0 / b#1;
+                    let t#10 = a#0 != b#1;
+                    let t#13 = if t#10 {
+                        let t#11 = b#1 / a#0;
+                        let t#12 = t#11 > 1;
+                        t#12
+                    } else {
+                        false
+                    };
+                    a#0
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unreachable_functions() {
+        check(
+            "def main() -> Int {\n    let f = used;\n    f(1)\n}\n\
+             def used(n: Int) -> Int {\n    n + helper()\n}\n\
+             def helper() -> Int {\n    1\n}\n\
+             def unused() -> Int {\n    helper()\n}",
+            expect![[r#"
+                def main() {
+                    let f#0 = used;
+                    let t#1 = f#0(1);
+                    t#1
+                }
+                def used(n#0) {
+                    let t#1 = helper();
+                    let t#2 = n#0 + t#1;
+                    t#2
+                }
+                def helper() {
+                    1
+                }
+            "#]],
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use hir::{Arena, DefId};
+
+use crate::{Block, Function, Module, Operand, Pass, Rvalue, Stmt, Var, VarId};
+
+/// Replaces direct calls to small functions with a copy of their body.
+///
+/// Only functions which don't call anything are inlined, so inlining always terminates, even
+/// with recursion. The arguments are bound to copies of the parameters, which constant
+/// propagation can remove afterwards.
+pub struct Inline;
+
+impl Inline {
+    /// The most statements a function can have to be inlined, including nested ones.
+    pub const MAX_SIZE: usize = 8;
+}
+
+impl Pass for Inline {
+    fn name(&self) -> &'static str {
+        "inline"
+    }
+
+    fn run(&self, module: &mut Module) -> bool {
+        let candidates = module
+            .fns
+            .iter()
+            .filter(|function| function.body.size() <= Self::MAX_SIZE && !has_calls(&function.body))
+            .map(|function| (function.def, function.clone()))
+            .collect::<HashMap<_, _>>();
+
+        let mut changed = false;
+
+        for function in &mut module.fns {
+            changed |= inline_calls(&mut function.body, &mut function.vars, &candidates);
+        }
+
+        changed
+    }
+}
+
+fn has_calls(block: &Block) -> bool {
+    block.stmts.iter().any(|stmt| match &stmt.value {
+        Rvalue::Call { .. } => true,
+        Rvalue::If {
+            then_block,
+            else_block,
+            ..
+        } => has_calls(then_block) || has_calls(else_block),
+        _ => false,
+    })
+}
+
+fn inline_calls(
+    block: &mut Block,
+    vars: &mut Arena<Var>,
+    candidates: &HashMap<DefId, Function>,
+) -> bool {
+    let mut changed = false;
+    let mut stmts = Vec::new();
+
+    for mut stmt in block.stmts.drain(..) {
+        match &mut stmt.value {
+            Rvalue::Call {
+                callee: Operand::Fn(def),
+                args,
+            } => match candidates.get(def) {
+                Some(callee) if callee.params.len() == args.len() => {
+                    let body = instantiate(callee, args, vars);
+                    stmts.extend(body.stmts);
+                    stmts.push(Stmt {
+                        var: stmt.var,
+                        value: Rvalue::Use(body.result),
+                    });
+                    changed = true;
+                    continue;
+                }
+                _ => {}
+            },
+            Rvalue::If {
+                then_block,
+                else_block,
+                ..
+            } => {
+                changed |= inline_calls(then_block, vars, candidates);
+                changed |= inline_calls(else_block, vars, candidates);
+            }
+            _ => {}
+        }
+
+        stmts.push(stmt);
+    }
+
+    block.stmts = stmts;
+    changed
+}
+
+/// Copies the body of a function into another, with new variables and the parameters bound to
+/// the arguments.
+fn instantiate(callee: &Function, args: &[Operand], vars: &mut Arena<Var>) -> Block {
+    let renames = callee
+        .vars
+        .iter()
+        .map(|(id, var)| (id, vars.alloc(var.clone())))
+        .collect::<HashMap<_, _>>();
+
+    let mut body = callee.body.clone();
+    rename(&mut body, &renames);
+
+    let params = callee.params.iter().zip(args).map(|(param, arg)| Stmt {
+        var: renames[param],
+        value: Rvalue::Use(arg.clone()),
+    });
+    body.stmts.splice(0..0, params);
+
+    body
+}
+
+fn rename(block: &mut Block, renames: &HashMap<VarId, VarId>) {
+    rename_stmts(block, renames);
+
+    block.walk_operands_mut(&mut |operand| {
+        if let Operand::Var(var) = operand {
+            *var = renames[var];
+        }
+    });
+}
+
+/// Renames the variable each statement assigns, including in nested blocks.
+fn rename_stmts(block: &mut Block, renames: &HashMap<VarId, VarId>) {
+    for stmt in &mut block.stmts {
+        stmt.var = renames[&stmt.var];
+
+        if let Rvalue::If {
+            then_block,
+            else_block,
+            ..
+        } = &mut stmt.value
+        {
+            rename_stmts(then_block, renames);
+            rename_stmts(else_block, renames);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use hir::Analysis;
+    use parser::parse;
+
+    use super::*;
+    use crate::lower;
+
+    fn check(source: &str, expect: Expect) {
+        let mut module = lower(&Analysis::new(&parse(source).tree()));
+        Inline.run(&mut module);
+        expect.assert_eq(&module.debug_mir());
+    }
+
+    #[test]
+    fn inline_leaf_functions() {
+        check(
+            "def main(x: Int) -> Int {\n    let y = double(x) + 1;\n    x > 0 && is_big(y)\n}\n\
+             def double(n: Int) -> Int {\n    n + n\n}\n\
+             def is_big(n: Int) -> Bool {\n    let limit = 100;\n    n > limit\n}",
+            expect![[r#"
+                def main(x#0) {
+                    let n#6 = x#0;
+                    let t#7 = n#6 + n#6;
+                    let t#1 = t#7;
+                    let y#2 = t#1 + 1;
+                    let t#3 = x#0 > 0;
+                    let t#5 = if t#3 {
+                        let n#8 = y#2;
+                        let limit#9 = 100;
+                        let t#10 = n#8 > limit#9;
+                        let t#4 = t#10;
+                        t#4
+                    } else {
+                        false
+                    };
+                    t#5
+                }
+                def double(n#0) {
+                    let t#1 = n#0 + n#0;
+                    t#1
+                }
+                def is_big(n#0) {
+                    let limit#1 = 100;
+                    let t#2 = n#0 > limit#1;
+                    t#2
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn keep_recursive_and_indirect_calls() {
+        check(
+            "def main(n: Int) -> Bool {\n    let f = is_zero;\n    f(n) && is_even(n)\n}\n\
+             def is_zero(n: Int) -> Bool {\n    n == 0\n}\n\
+             def is_even(n: Int) -> Bool {\n    n == 0 || !is_even(n - 1)\n}",
+            expect![[r#"
+                def main(n#0) {
+                    let f#1 = is_zero;
+                    let t#2 = f#1(n#0);
+                    let t#4 = if t#2 {
+                        let t#3 = is_even(n#0);
+                        t#3
+                    } else {
+                        false
+                    };
+                    t#4
+                }
+                def is_zero(n#0) {
+                    let t#1 = n#0 == 0;
+                    t#1
+                }
+                def is_even(n#0) {
+                    let t#1 = n#0 == 0;
+                    let t#5 = if t#1 {
+                        true
+                    } else {
+                        let t#2 = n#0 - 1;
+                        let t#3 = is_even(t#2);
+                        let t#4 = !t#3;
+                        t#4
+                    };
+                    t#5
+                }
+            "#]],
+        );
+    }
+}
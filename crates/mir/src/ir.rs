@@ -0,0 +1,195 @@
+use hir::{Arena, BinaryOp, ConstValue, DefId, Idx, Name, Ty, UnaryOp};
+
+use crate::LowerError;
+
+pub type VarId = Idx<Var>;
+
+/// Every function in a module, in A-normal form.
+///
+/// Each intermediate value is bound to a variable, and every operation only takes constants and
+/// variables as operands, so passes can work on one statement at a time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Module {
+    pub fns: Vec<Function>,
+    /// The first error in each function which couldn't be lowered.
+    pub errors: Vec<LowerError>,
+}
+
+impl Module {
+    pub fn function(&self, def: DefId) -> Option<&Function> {
+        self.fns.iter().find(|function| function.def == def)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Function {
+    pub def: DefId,
+    pub name: Option<Name>,
+    /// Every variable in the function, including the ones in nested blocks.
+    pub vars: Arena<Var>,
+    pub params: Vec<VarId>,
+    pub body: Block,
+}
+
+/// A parameter, `let` binding or intermediate value. Each variable is assigned exactly once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Var {
+    /// The name of the local in the source, or `None` for an intermediate value.
+    pub name: Option<Name>,
+    pub ty: Ty,
+}
+
+/// A sequence of statements, followed by the value of the block.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Block {
+    pub stmts: Vec<Stmt>,
+    pub result: Operand,
+}
+
+impl Block {
+    /// Calls the function with every operand in the block and its nested blocks.
+    pub fn walk_operands(&self, f: &mut impl FnMut(&Operand)) {
+        for stmt in &self.stmts {
+            stmt.value.walk_operands(f);
+        }
+        f(&self.result);
+    }
+
+    pub fn walk_operands_mut(&mut self, f: &mut impl FnMut(&mut Operand)) {
+        for stmt in &mut self.stmts {
+            stmt.value.walk_operands_mut(f);
+        }
+        f(&mut self.result);
+    }
+
+    /// The number of statements, including the ones in nested blocks.
+    pub fn size(&self) -> usize {
+        self.stmts
+            .iter()
+            .map(|stmt| match &stmt.value {
+                Rvalue::If {
+                    then_block,
+                    else_block,
+                    ..
+                } => 1 + then_block.size() + else_block.size(),
+                _ => 1,
+            })
+            .sum()
+    }
+}
+
+/// `let var = value;`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stmt {
+    pub var: VarId,
+    pub value: Rvalue,
+}
+
+/// A value which is already computed, and can be used any number of times.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Operand {
+    Var(VarId),
+    Const(ConstValue),
+    /// A reference to a function, which can be called or passed around.
+    Fn(DefId),
+    Unit,
+}
+
+/// An operation whose result is bound to a variable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Rvalue {
+    Use(Operand),
+    /// Any binary operator except `&&` and `||`, which are lowered to [`Rvalue::If`].
+    Binary {
+        op: BinaryOp,
+        lhs: Operand,
+        rhs: Operand,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Operand,
+    },
+    Call {
+        callee: Operand,
+        args: Vec<Operand>,
+    },
+    /// Runs one of the blocks, depending on whether the condition is true.
+    If {
+        condition: Operand,
+        then_block: Block,
+        else_block: Block,
+    },
+}
+
+impl Rvalue {
+    /// Calls the function with each operand, including the ones in nested blocks.
+    pub fn walk_operands(&self, f: &mut impl FnMut(&Operand)) {
+        match self {
+            Self::Use(operand) | Self::Unary { operand, .. } => f(operand),
+            Self::Binary { lhs, rhs, .. } => {
+                f(lhs);
+                f(rhs);
+            }
+            Self::Call { callee, args } => {
+                f(callee);
+                args.iter().for_each(f);
+            }
+            Self::If {
+                condition,
+                then_block,
+                else_block,
+            } => {
+                f(condition);
+                then_block.walk_operands(f);
+                else_block.walk_operands(f);
+            }
+        }
+    }
+
+    pub fn walk_operands_mut(&mut self, f: &mut impl FnMut(&mut Operand)) {
+        match self {
+            Self::Use(operand) | Self::Unary { operand, .. } => f(operand),
+            Self::Binary { lhs, rhs, .. } => {
+                f(lhs);
+                f(rhs);
+            }
+            Self::Call { callee, args } => {
+                f(callee);
+                args.iter_mut().for_each(f);
+            }
+            Self::If {
+                condition,
+                then_block,
+                else_block,
+            } => {
+                f(condition);
+                then_block.walk_operands_mut(f);
+                else_block.walk_operands_mut(f);
+            }
+        }
+    }
+
+    /// Whether evaluating the value can't fail, so it can be removed if it isn't used.
+    ///
+    /// Calls are never pure, since they can fail or recurse forever.
+    pub fn is_pure(&self) -> bool {
+        match self {
+            Self::Use(_) | Self::Unary { .. } => true,
+            Self::Binary { op, rhs, .. } => match op {
+                BinaryOp::Div | BinaryOp::Rem => {
+                    matches!(rhs, Operand::Const(ConstValue::Int(value)) if *value != 0.into())
+                }
+                BinaryOp::Shl | BinaryOp::Shr | BinaryOp::UnsignedShr => false,
+                _ => true,
+            },
+            Self::Call { .. } => false,
+            Self::If {
+                then_block,
+                else_block,
+                ..
+            } => [then_block, else_block]
+                .iter()
+                .all(|block| block.stmts.iter().all(|stmt| stmt.value.is_pure())),
+        }
+    }
+}
@@ -0,0 +1,16 @@
+mod const_prop;
+mod cse;
+mod dce;
+mod inline;
+mod ir;
+mod lower;
+mod pass;
+mod pretty;
+
+pub use const_prop::*;
+pub use cse::*;
+pub use dce::*;
+pub use inline::*;
+pub use ir::*;
+pub use lower::*;
+pub use pass::*;
@@ -0,0 +1,368 @@
+use hir::{
+    Analysis, ArenaMap, BinaryOp, Body, BodyResolution, BodySourceMap, BodyTypes, ConstValue,
+    DefId, DefKind, Expr, ExprId, Literal, Local, LocalId, Resolution, Stmt as HirStmt,
+};
+use rowan::TextRange;
+
+use crate::{Block, Function, Module, Operand, Rvalue, Stmt, Var, VarId};
+
+/// Lowers every function in a module to the IR.
+///
+/// Functions with errors in their bodies are left out, since they can't be compiled anyway, and
+/// where the error is is kept instead. Constants are replaced by their values, and `&&` and `||`
+/// become `if` so the right hand side is only evaluated when it's needed.
+pub fn lower(analysis: &Analysis) -> Module {
+    let mut module = Module::default();
+
+    for (def, _) in analysis.module.defs.iter() {
+        if analysis.module.defs[def].kind != DefKind::Fn {
+            continue;
+        }
+
+        match FnLowering::new(analysis, def).lower() {
+            Ok(function) => module.fns.push(function),
+            Err(error) => module.errors.push(error),
+        }
+    }
+
+    module
+}
+
+/// A function which couldn't be lowered, because of an error which was reported before.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowerError {
+    pub def: DefId,
+    /// The expression or `let` statement with the error, if it's at a point in the source.
+    pub span: Option<TextRange>,
+}
+
+struct FnLowering<'a> {
+    analysis: &'a Analysis,
+    def: DefId,
+    body: &'a Body,
+    source_map: &'a BodySourceMap,
+    resolution: &'a BodyResolution,
+    types: &'a BodyTypes,
+    function: Function,
+    locals: ArenaMap<Local, VarId>,
+    /// The statements of the block which is being lowered.
+    stmts: Vec<Stmt>,
+}
+
+impl<'a> FnLowering<'a> {
+    fn new(analysis: &'a Analysis, def: DefId) -> Self {
+        let def_data = &analysis.module.defs[def];
+
+        Self {
+            analysis,
+            def,
+            body: &def_data.body,
+            source_map: analysis.source_map.body(def),
+            resolution: analysis.resolution.body(def),
+            types: analysis.types.body(def),
+            function: Function {
+                def,
+                name: def_data.name.clone(),
+                vars: Default::default(),
+                params: Vec::new(),
+                body: Block {
+                    stmts: Vec::new(),
+                    result: Operand::Unit,
+                },
+            },
+            locals: ArenaMap::new(),
+            stmts: Vec::new(),
+        }
+    }
+
+    fn lower(mut self) -> Result<Function, LowerError> {
+        for &param in &self.body.params {
+            let var = self.local_var(param);
+            self.function.params.push(var);
+        }
+
+        for stmt in &self.body.stmts {
+            match stmt {
+                HirStmt::Let { local, value } => {
+                    let value = value.ok_or_else(|| LowerError {
+                        def: self.def,
+                        span: self.source_map.local_name_range(*local),
+                    })?;
+                    let value = self.expr(value)?;
+                    self.bind_local(*local, value);
+                }
+                // The value is still computed, in case it fails. It's removed later if it can't.
+                HirStmt::Expr(expr) => {
+                    self.expr(*expr)?;
+                }
+            }
+        }
+
+        let result = match self.body.tail {
+            Some(tail) => self.expr(tail)?,
+            None => Operand::Unit,
+        };
+
+        self.function.body = Block {
+            stmts: self.stmts,
+            result,
+        };
+
+        Ok(self.function)
+    }
+
+    fn local_var(&mut self, local: LocalId) -> VarId {
+        let var = self.function.vars.alloc(Var {
+            name: self.body.locals[local].name.clone(),
+            ty: self.types.local_ty(local).clone(),
+        });
+        self.locals.insert(local, var);
+        var
+    }
+
+    /// Binds a `let` statement's value to its local. If the value was just computed, its
+    /// variable is reused instead of copying it.
+    fn bind_local(&mut self, local: LocalId, value: Operand) {
+        if let (Operand::Var(var), Some(last)) = (&value, self.stmts.last()) {
+            if last.var == *var && self.function.vars[*var].name.is_none() {
+                self.function.vars[*var].name = self.body.locals[local].name.clone();
+                self.locals.insert(local, *var);
+                return;
+            }
+        }
+
+        let var = self.local_var(local);
+        self.stmts.push(Stmt {
+            var,
+            value: Rvalue::Use(value),
+        });
+    }
+
+    /// Binds a value to a new variable, with the type of the expression it came from.
+    fn push(&mut self, expr: ExprId, value: Rvalue) -> Operand {
+        let var = self.function.vars.alloc(Var {
+            name: None,
+            ty: self.types.expr_ty(expr).clone(),
+        });
+        self.stmts.push(Stmt { var, value });
+        Operand::Var(var)
+    }
+
+    /// Lowers an expression into a new block, rather than the current one.
+    fn block(&mut self, expr: ExprId) -> Result<Block, LowerError> {
+        let outer = std::mem::take(&mut self.stmts);
+        let result = self.expr(expr);
+        let stmts = std::mem::replace(&mut self.stmts, outer);
+
+        Ok(Block {
+            stmts,
+            result: result?,
+        })
+    }
+
+    /// Lowers an expression, or fails at the first error in it. Operators whose types are
+    /// wrong are errors too, since the backends need the types to pick an operation.
+    fn expr(&mut self, expr: ExprId) -> Result<Operand, LowerError> {
+        let error = {
+            let (def, span) = (self.def, self.source_map.expr_range(expr));
+            move || LowerError { def, span }
+        };
+
+        match &self.body.exprs[expr] {
+            Expr::Literal(literal) => Ok(Operand::Const(match literal {
+                Literal::Bool(value) => ConstValue::Bool(*value),
+                Literal::Int { value, .. } => ConstValue::Int(value.clone()),
+                Literal::String(value) => ConstValue::Bytes(value.clone().into_bytes()),
+            })),
+            Expr::Name(_) => match self.resolution.resolve_expr(expr).ok_or_else(error)? {
+                Resolution::Local(local) => self
+                    .locals
+                    .get(local)
+                    .copied()
+                    .map(Operand::Var)
+                    .ok_or_else(error),
+                Resolution::Def(def) => match self.analysis.module.defs[def].kind {
+                    DefKind::Fn => Ok(Operand::Fn(def)),
+                    DefKind::Const => self
+                        .analysis
+                        .consts
+                        .const_value(&self.analysis.module, def)
+                        .cloned()
+                        .map(Operand::Const)
+                        .ok_or_else(error),
+                },
+            },
+            Expr::Binary { op, lhs, rhs } => {
+                let lhs = self.expr(*lhs)?;
+
+                // a && b => if a { b } else { false }
+                // a || b => if a { true } else { b }
+                let value = match op {
+                    BinaryOp::LazyAnd => Rvalue::If {
+                        condition: lhs,
+                        then_block: self.block(*rhs)?,
+                        else_block: constant(ConstValue::Bool(false)),
+                    },
+                    BinaryOp::LazyOr => Rvalue::If {
+                        condition: lhs,
+                        then_block: constant(ConstValue::Bool(true)),
+                        else_block: self.block(*rhs)?,
+                    },
+                    op => Rvalue::Binary {
+                        op: *op,
+                        lhs,
+                        rhs: self.expr(*rhs)?,
+                    },
+                };
+
+                if self.types.expr_ty(expr).is_unknown() {
+                    return Err(error());
+                }
+
+                Ok(self.push(expr, value))
+            }
+            Expr::Unary { op, expr: inner } => {
+                let operand = self.expr(*inner)?;
+
+                if self.types.expr_ty(expr).is_unknown() {
+                    return Err(error());
+                }
+
+                Ok(self.push(expr, Rvalue::Unary { op: *op, operand }))
+            }
+            Expr::Call { callee, args } => {
+                let callee = self.expr(*callee)?;
+                let args = args
+                    .iter()
+                    .map(|&arg| self.expr(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(self.push(expr, Rvalue::Call { callee, args }))
+            }
+            Expr::Missing | Expr::Field { .. } | Expr::Try { .. } => Err(error()),
+        }
+    }
+}
+
+fn constant(value: ConstValue) -> Block {
+    Block {
+        stmts: Vec::new(),
+        result: Operand::Const(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use parser::parse;
+
+    use super::*;
+
+    fn check(source: &str, expect: Expect) {
+        let module = lower(&Analysis::new(&parse(source).tree()));
+        expect.assert_eq(&module.debug_mir());
+    }
+
+    #[test]
+    fn lower_lets_and_calls() {
+        check(
+            "def main(x: Int) -> Int {\n    let y = x * 2;\n    let z = y;\n    double(z) + 1\n}\n\
+             def double(n: Int) -> Int {\n    n + n\n}\n\
+             def nothing() {\n    double(1);\n}",
+            expect![[r#"
+                def main(x#0) {
+                    let y#1 = x#0 * 2;
+                    let z#2 = y#1;
+                    let t#3 = double(z#2);
+                    let t#4 = t#3 + 1;
+                    t#4
+                }
+                def double(n#0) {
+                    let t#1 = n#0 + n#0;
+                    t#1
+                }
+                def nothing() {
+                    let t#0 = double(1);
+                    ()
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lower_lazy_operators() {
+        check(
+            "def main(a: Int, b: Bool) -> Bool {\n    a > 0 && b || !b && a / 2 == 1\n}",
+            expect![[r#"
+                def main(a#0, b#1) {
+                    let t#2 = a#0 > 0;
+                    let t#3 = if t#2 {
+                        b#1
+                    } else {
+                        false
+                    };
+                    let t#8 = if t#3 {
+                        true
+                    } else {
+                        let t#4 = !b#1;
+                        let t#7 = if t#4 {
+                            let t#5 = a#0 / 2;
+                            let t#6 = t#5 == 1;
+                            t#6
+                        } else {
+                            false
+                        };
+                        t#7
+                    };
+                    t#8
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn lower_consts_and_literals() {
+        check(
+            "const NAME: String = 'rue';\nconst LIMIT: Int = 1 << 8;\n\
+             def main(s: String) -> Bool {\n    s + NAME == 'hi' && LIMIT > 0x10 == true\n}",
+            expect![[r#"
+                def main(s#0) {
+                    let t#1 = s#0 + "rue";
+                    let t#2 = t#1 == "hi";
+                    let t#5 = if t#2 {
+                        let t#3 = 256 > 16;
+                        let t#4 = t#3 == true;
+                        t#4
+                    } else {
+                        false
+                    };
+                    t#5
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn skip_functions_with_errors() {
+        let source = "def main() -> Int {\n    1\n}\ndef broken() -> Int {\n    missing + 1\n}\n\
+                      def float() {\n    let _x = 1.5;\n}\n\
+                      def uninitialized() -> Int {\n    let x: Int;\n    x\n}";
+
+        check(
+            source,
+            expect![[r#"
+                def main() {
+                    1
+                }
+            "#]],
+        );
+
+        let module = lower(&Analysis::new(&parse(source).tree()));
+        let errors = module
+            .errors
+            .iter()
+            .map(|error| &source[error.span.unwrap()])
+            .collect::<Vec<_>>();
+        assert_eq!(errors, ["missing", "1.5", "x"]);
+    }
+}
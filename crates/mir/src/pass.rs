@@ -0,0 +1,177 @@
+use crate::{CommonSubexpressions, ConstPropagation, DeadCodeElimination, Inline, Module};
+
+/// A transformation of the IR, which keeps the behavior of the program the same.
+pub trait Pass {
+    fn name(&self) -> &'static str;
+
+    /// Runs the pass over every function, and returns whether anything changed.
+    fn run(&self, module: &mut Module) -> bool;
+}
+
+/// Runs a list of passes in order, repeating them until none of them change anything.
+///
+/// Passes often make work for each other, such as inlining a function whose arguments can then
+/// be propagated into its body, so a single run in a fixed order would miss things.
+pub struct PassManager {
+    passes: Vec<Box<dyn Pass>>,
+    max_rounds: usize,
+}
+
+impl PassManager {
+    /// How many times the passes are repeated at most, in case they keep undoing each other.
+    pub const DEFAULT_MAX_ROUNDS: usize = 8;
+
+    pub fn new() -> Self {
+        Self {
+            passes: Vec::new(),
+            max_rounds: Self::DEFAULT_MAX_ROUNDS,
+        }
+    }
+
+    /// Every optimization, in the order they work best in.
+    pub fn optimize() -> Self {
+        Self::new()
+            .with(Inline)
+            .with(ConstPropagation)
+            .with(CommonSubexpressions)
+            .with(DeadCodeElimination)
+    }
+
+    pub fn with(mut self, pass: impl Pass + 'static) -> Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    pub fn max_rounds(mut self, max_rounds: usize) -> Self {
+        self.max_rounds = max_rounds;
+        self
+    }
+
+    pub fn passes(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.passes.iter().map(|pass| pass.name())
+    }
+
+    /// Runs the passes, and returns the names of the ones which changed something in each round.
+    pub fn run(&self, module: &mut Module) -> Vec<Vec<&'static str>> {
+        let mut rounds = Vec::new();
+
+        for _ in 0..self.max_rounds {
+            let changed = self
+                .passes
+                .iter()
+                .filter(|pass| pass.run(module))
+                .map(|pass| pass.name())
+                .collect::<Vec<_>>();
+
+            if changed.is_empty() {
+                break;
+            }
+
+            rounds.push(changed);
+        }
+
+        rounds
+    }
+}
+
+impl Default for PassManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use hir::Analysis;
+    use parser::parse;
+
+    use super::*;
+    use crate::lower;
+
+    fn check(source: &str, expect: Expect) {
+        let mut module = lower(&Analysis::new(&parse(source).tree()));
+        let rounds = PassManager::optimize().run(&mut module);
+
+        let rounds = rounds
+            .iter()
+            .enumerate()
+            .map(|(i, changed)| format!("round {}: {}\n", i + 1, changed.join(", ")))
+            .collect::<String>();
+        expect.assert_eq(&format!("{rounds}\n{}", module.debug_mir()));
+    }
+
+    #[test]
+    fn optimize() {
+        check(
+            "const SCALE: Int = 10;\n\
+             def main(x: Int) -> Int {\n    let y = scale(x) + scale(x);\n    \
+             let unused = y * 2;\n    clamp(y, 2 * SCALE)\n}\n\
+             def scale(n: Int) -> Int {\n    n * SCALE\n}\n\
+             def clamp(n: Int, max: Int) -> Int {\n    let over = n > max;\n    \
+             over && over || false;\n    n - max\n}",
+            expect![[r#"
+                round 1: inline, const-propagation, common-subexpressions, dead-code-elimination
+                round 2: const-propagation, dead-code-elimination
+
+                def main(x#0) {
+                    let t#8 = x#0 * 10;
+                    let y#3 = t#8 + t#8;
+                    let t#16 = y#3 - 20;
+                    t#16
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn recursion_is_kept() {
+        check(
+            "def main(n: Int) -> Int {\n    sum(n, 0)\n}\n\
+             def sum(n: Int, total: Int) -> Int {\n    \
+             let done = n == 0;\n    done && total == total || sum(n - 1, total + n) > 0;\n    total\n}",
+            expect![[r#"
+
+                def main(n#0) {
+                    let t#1 = sum(n#0, 0);
+                    t#1
+                }
+                def sum(n#0, total#1) {
+                    let done#2 = n#0 == 0;
+                    let t#4 = if done#2 {
+                        let t#3 = total#1 == total#1;
+                        t#3
+                    } else {
+                        false
+                    };
+                    let t#9 = if t#4 {
+                        true
+                    } else {
+                        let t#5 = n#0 - 1;
+                        let t#6 = total#1 + n#0;
+                        let t#7 = sum(t#5, t#6);
+                        let t#8 = t#7 > 0;
+                        t#8
+                    };
+                    total#1
+                }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn order_and_limits() {
+        let manager = PassManager::new()
+            .with(DeadCodeElimination)
+            .with(ConstPropagation)
+            .max_rounds(1);
+        assert_eq!(
+            manager.passes().collect::<Vec<_>>(),
+            ["dead-code-elimination", "const-propagation"]
+        );
+
+        let source = "def main() -> Int {\n    let a = 1;\n    let b = a + 1;\n    b\n}";
+        let mut module = lower(&Analysis::new(&parse(source).tree()));
+        assert_eq!(manager.run(&mut module), [["const-propagation"]]);
+    }
+}
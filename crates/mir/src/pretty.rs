@@ -0,0 +1,113 @@
+use std::fmt::Write;
+
+use crate::{Block, Function, Module, Operand, Rvalue, VarId};
+
+impl Module {
+    /// Prints the IR in a source-like form for debugging, with every variable labeled by its ID.
+    pub fn debug_mir(&self) -> String {
+        let mut out = String::new();
+
+        for function in &self.fns {
+            self.write_function(&mut out, function);
+        }
+
+        out
+    }
+
+    fn write_function(&self, out: &mut String, function: &Function) {
+        let name = function
+            .name
+            .as_ref()
+            .map_or("<missing>", |name| name.as_str());
+        write!(out, "def {name}(").unwrap();
+
+        for (i, &param) in function.params.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            write_var(out, function, param);
+        }
+
+        out.push_str(") ");
+        self.write_block(out, function, &function.body, 0);
+        out.push('\n');
+    }
+
+    fn write_block(&self, out: &mut String, function: &Function, block: &Block, indent: usize) {
+        out.push_str("{\n");
+
+        for stmt in &block.stmts {
+            write!(out, "{:width$}let ", "", width = indent + 4).unwrap();
+            write_var(out, function, stmt.var);
+            out.push_str(" = ");
+            self.write_rvalue(out, function, &stmt.value, indent + 4);
+            out.push_str(";\n");
+        }
+
+        write!(out, "{:width$}", "", width = indent + 4).unwrap();
+        self.write_operand(out, function, &block.result);
+        write!(out, "\n{:indent$}}}", "").unwrap();
+    }
+
+    fn write_rvalue(&self, out: &mut String, function: &Function, value: &Rvalue, indent: usize) {
+        match value {
+            Rvalue::Use(operand) => self.write_operand(out, function, operand),
+            Rvalue::Binary { op, lhs, rhs } => {
+                self.write_operand(out, function, lhs);
+                write!(out, " {} ", op.text()).unwrap();
+                self.write_operand(out, function, rhs);
+            }
+            Rvalue::Unary { op, operand } => {
+                out.push_str(op.text());
+                self.write_operand(out, function, operand);
+            }
+            Rvalue::Call { callee, args } => {
+                self.write_operand(out, function, callee);
+                out.push('(');
+
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    self.write_operand(out, function, arg);
+                }
+
+                out.push(')');
+            }
+            Rvalue::If {
+                condition,
+                then_block,
+                else_block,
+            } => {
+                out.push_str("if ");
+                self.write_operand(out, function, condition);
+                out.push(' ');
+                self.write_block(out, function, then_block, indent);
+                out.push_str(" else ");
+                self.write_block(out, function, else_block, indent);
+            }
+        }
+    }
+
+    fn write_operand(&self, out: &mut String, function: &Function, operand: &Operand) {
+        match operand {
+            Operand::Var(var) => write_var(out, function, *var),
+            Operand::Const(value) => write!(out, "{value}").unwrap(),
+            Operand::Fn(def) => match self.function(*def).and_then(|f| f.name.as_ref()) {
+                Some(name) => out.push_str(name.as_str()),
+                None => write!(out, "<def{def:?}>").unwrap(),
+            },
+            Operand::Unit => out.push_str("()"),
+        }
+    }
+}
+
+/// Named variables are printed like locals in the HIR, and intermediate values as `t` and
+/// their ID.
+fn write_var(out: &mut String, function: &Function, var: VarId) {
+    let name = function.vars[var]
+        .name
+        .as_ref()
+        .map_or("t", |name| name.as_str());
+    write!(out, "{name}{var:?}").unwrap();
+}
@@ -0,0 +1,131 @@
+/// Long descriptions of every parse error code, in the spirit of `rustc --explain`.
+const ERROR_CODES: &[(&str, &str)] = &[
+    (
+        "E0001",
+        "A specific token was required here, but a different one was found.
+
+For example, a `let` statement must be terminated by a semicolon:
+
+    let x = 5
+    let y = 6;
+
+Add the missing token, or remove the unexpected one.",
+    ),
+    (
+        "E0002",
+        "Only items, such as `def` functions and `const` items, can appear at the top level of a file.
+
+    let x = 5;
+
+Move the statement into the body of a function.",
+    ),
+    (
+        "E0003",
+        "Only statements, such as `let` bindings and expressions followed by a semicolon, can \
+appear inside of a block.
+
+    def main() {
+        = 5;
+    }",
+    ),
+    (
+        "E0004",
+        "An expression was required here, such as a literal, a name or an operator applied to \
+other expressions.
+
+    let x = ;",
+    ),
+    (
+        "E0005",
+        "A type was required here, such as the name of a built-in type.
+
+    def main(x: ) {}",
+    ),
+    (
+        "E0006",
+        "Parameters must be written as a name followed by a colon and a type.
+
+    def main(5) {}",
+    ),
+    (
+        "E0007",
+        "A string literal was opened but never closed with a matching quote.
+
+    let x = \"hello;",
+    ),
+    (
+        "E0008",
+        "An integer literal with a base prefix (`0b`, `0o` or `0x`) must have at least one digit.
+
+    let x = 0x;",
+    ),
+    (
+        "E0009",
+        "A digit in an integer literal is not valid for its base.
+
+    let x = 0b102;
+
+Binary literals can only contain `0` and `1`, and octal literals `0` to `7`.",
+    ),
+    (
+        "E0010",
+        "A number literal has an unknown type suffix.
+
+    let x = 5q;
+
+Integers accept `i8` to `i128` and `u8` to `u128`, and decimals accept `f32` and `f64`.",
+    ),
+    (
+        "E0011",
+        "The exponent of a decimal literal has no digits.
+
+    let x = 1e;
+
+Add digits after the `e`, such as `1e3`.",
+    ),
+    (
+        "E0012",
+        "Comparison operators are not associative, so they can't be chained together.
+
+    let ordered = a < b < c;
+
+Unlike in mathematics, this doesn't check that `b` is between `a` and `c`. Split it into two \
+comparisons joined with `&&` instead:
+
+    let ordered = a < b && b < c;",
+    ),
+    (
+        "E0013",
+        "Decimal literals are recognized, but there is no type for them yet, so they can't be used.
+
+    let x = 1.5;
+
+Use an integer instead, scaled so that it doesn't need a fraction, such as `15` tenths.",
+    ),
+];
+
+/// Looks up the long description of an error code, such as `E0001`.
+pub fn explain(code: &str) -> Option<&'static str> {
+    ERROR_CODES
+        .iter()
+        .find(|(name, _)| *name == code)
+        .map(|(_, description)| *description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_unique_and_ordered() {
+        for (i, (code, _)) in ERROR_CODES.iter().enumerate() {
+            assert_eq!(*code, format!("E{:04}", i + 1));
+        }
+    }
+
+    #[test]
+    fn explain_unknown() {
+        assert!(explain("E0001").is_some());
+        assert_eq!(explain("E9999"), None);
+    }
+}
@@ -1,5 +1,8 @@
+use rowan::TextRange;
 use syntax::SyntaxKind;
 
+use crate::ParseErrorKind;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Event {
     StartNode {
@@ -10,7 +13,16 @@ pub enum Event {
         kind: SyntaxKind,
         token_count: usize,
     },
-    Error(String),
+    Error {
+        kind: ParseErrorKind,
+        span: TextRange,
+    },
+    ChainedComparison {
+        op: TextRange,
+        first: TextRange,
+        middle: TextRange,
+        rest: TextRange,
+    },
     FinishNode,
 }
 
@@ -1,6 +1,6 @@
-use syntax::SyntaxKind;
+use syntax::{Set, SyntaxKind, EMPTY_SET};
 
-use crate::{parser::CompletedMarker, Parser};
+use crate::{parser::CompletedMarker, ParseErrorKind, Parser};
 
 mod exprs;
 mod items;
@@ -10,7 +10,56 @@ mod types;
 pub(super) fn root(p: &mut Parser) -> CompletedMarker {
     let m = p.start();
 
+    while !p.at(SyntaxKind::Eof) {
+        items::item(p);
+    }
+
+    m.complete(p, SyntaxKind::Root)
+}
+
+pub(super) fn expr_fragment(p: &mut Parser) -> CompletedMarker {
+    fragment(p, |p| {
+        exprs::expr(p);
+    })
+}
+
+pub(super) fn type_fragment(p: &mut Parser) -> CompletedMarker {
+    fragment(p, types::type_)
+}
+
+pub(super) fn stmt_fragment(p: &mut Parser) -> CompletedMarker {
+    fragment(p, stmts::stmt)
+}
+
+pub(super) fn item_fragment(p: &mut Parser) -> CompletedMarker {
+    fragment(p, items::item)
+}
+
+/// Parses a block on its own, without a `Root` node, for incremental reparsing.
+pub(super) fn reparse_block(p: &mut Parser) {
+    items::block(p);
+}
+
+/// Parses an item on its own, without a `Root` node, for incremental reparsing.
+pub(super) fn reparse_item(p: &mut Parser) {
     items::item(p);
+}
+
+/// Parses a single piece of syntax inside of a `Root` node, with anything after it as an error.
+fn fragment(p: &mut Parser, f: impl FnOnce(&mut Parser)) -> CompletedMarker {
+    let m = p.start();
+
+    f(p);
+
+    if !p.at(SyntaxKind::Eof) {
+        p.err_recover_until(
+            ParseErrorKind::ExpectedToken {
+                expected: Set::new(&[SyntaxKind::Eof]),
+                found: p.peek(),
+            },
+            EMPTY_SET,
+        );
+    }
 
     m.complete(p, SyntaxKind::Root)
 }
@@ -31,4 +80,331 @@ where
 }
 
 #[cfg(test)]
-mod tests {}
+fn parse_errors(source: &str) -> Vec<(String, std::ops::Range<u32>)> {
+    use lexer::Lexer;
+
+    let tokens = Lexer::new(source).collect::<Vec<_>>();
+
+    Parser::parse_tokens(&tokens)
+        .errors
+        .into_iter()
+        .map(|error| {
+            let span = u32::from(error.span.start())..u32::from(error.span.end());
+            (error.message(), span)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    use super::*;
+
+    #[test]
+    fn missing_token_points_after_previous_token() {
+        assert_eq!(
+            parse_errors("def main() {\n    let x = 5\n}"),
+            &[("expected `;`, found `}`".to_string(), 26..26)]
+        );
+    }
+
+    #[test]
+    fn missing_token_at_end_of_file() {
+        assert_eq!(
+            parse_errors("def main() {  "),
+            &[("expected `}`, found end of file".to_string(), 12..12)]
+        );
+    }
+
+    #[test]
+    fn unexpected_token_covers_error_node() {
+        assert_eq!(
+            parse_errors("def main() { let x = @; }"),
+            &[(
+                "expected an expression, found unknown token".to_string(),
+                21..22
+            )]
+        );
+    }
+
+    #[test]
+    fn empty_file() {
+        assert_eq!(parse_errors("  "), &[]);
+    }
+
+    #[test]
+    fn item_recovery_skips_to_next_def() {
+        assert_eq!(
+            parse_errors("let x = 5; } def main() {}"),
+            &[("expected an item, found `let`".to_string(), 0..12)]
+        );
+    }
+
+    #[test]
+    fn stmt_recovery_skips_balanced_delimiters() {
+        assert_eq!(
+            parse_errors("def main() { = foo(1, { 2; }); let y = 7; }"),
+            &[("expected a statement, found `=`".to_string(), 13..29)]
+        );
+    }
+
+    #[test]
+    fn stmt_recovery_skips_to_semicolon() {
+        assert_eq!(
+            parse_errors("def main() { let x = 5 6; let y = 7; }"),
+            &[("expected `;`, found integer literal".to_string(), 22..22)]
+        );
+    }
+
+    #[test]
+    fn follow_on_errors_are_suppressed() {
+        assert_eq!(
+            parse_errors("def main() { let x = (1 + ; }"),
+            &[("expected an expression, found `;`".to_string(), 26..27)]
+        );
+        assert_eq!(
+            parse_errors("def main x"),
+            &[
+                ("expected `(`, found identifier".to_string(), 8..8),
+                ("expected `:`, found end of file".to_string(), 10..10),
+            ]
+        );
+    }
+
+    #[test]
+    fn number_literal_errors() {
+        assert_eq!(
+            parse_errors("def main() { 1.5; 2e10f64; 1e; 2.5q; 7u8; }"),
+            &[
+                ("float literals are not supported yet".to_string(), 13..16),
+                ("float literals are not supported yet".to_string(), 18..25),
+                (
+                    "expected at least one digit in the exponent".to_string(),
+                    27..29
+                ),
+                ("invalid suffix `q` for number literal".to_string(), 34..35),
+            ]
+        );
+    }
+
+    #[test]
+    fn param_recovery() {
+        assert_eq!(
+            parse_errors("def main(5, x: Int) {}"),
+            &[(
+                "expected a parameter, found integer literal".to_string(),
+                9..10
+            )]
+        );
+    }
+
+    #[test]
+    fn const_item() {
+        expect![[r#"
+            ConstItem@0..24
+              ConstKw@0..5 "const"
+              Whitespace@5..6 " "
+              Ident@6..9 "MAX"
+              Colon@9..10 ":"
+              Whitespace@10..11 " "
+              NameType@11..15
+                Ident@11..14 "Int"
+                Whitespace@14..15 " "
+              Equals@15..16 "="
+              Whitespace@16..17 " "
+              BinaryExpr@17..23
+                Literal@17..19
+                  Integer@17..18 "1"
+                  Whitespace@18..19 " "
+                LeftShift@19..21 "<<"
+                Whitespace@21..22 " "
+                Literal@22..23
+                  Integer@22..23 "8"
+              Semicolon@23..24 ";""#]]
+        .assert_eq(&parse("const MAX: Int = 1 << 8;", items::item));
+    }
+
+    #[test]
+    fn const_item_recovery() {
+        assert_eq!(
+            parse_errors("const A: Int = ;\nconst B = 2;\ndef main() {}"),
+            &[
+                ("expected an expression, found `;`".to_string(), 15..16),
+                ("expected `:`, found `=`".to_string(), 24..24),
+            ]
+        );
+    }
+
+    #[test]
+    fn expr_stmt_and_tail_expr() {
+        expect![[r#"
+            Block@0..11
+              OpenBrace@0..1 "{"
+              Whitespace@1..2 " "
+              ExprStmt@2..8
+                CallExpr@2..6
+                  NameRef@2..3
+                    Ident@2..3 "f"
+                  ArgList@3..6
+                    OpenParen@3..4 "("
+                    Literal@4..5
+                      Integer@4..5 "1"
+                    CloseParen@5..6 ")"
+                Semicolon@6..7 ";"
+                Whitespace@7..8 " "
+              NameRef@8..10
+                Ident@8..9 "x"
+                Whitespace@9..10 " "
+              CloseBrace@10..11 "}""#]]
+        .assert_eq(&parse("{ f(1); x }", items::block));
+    }
+
+    #[test]
+    fn expr_stmt_missing_semicolon() {
+        assert_eq!(
+            parse_errors("def main() { f(1) g(2) }"),
+            &[(
+                "expected one of `}` or `;`, found identifier".to_string(),
+                17..17
+            )]
+        );
+    }
+
+    #[test]
+    fn missing_semicolon_is_inserted() {
+        expect![[r#"
+            LetStmt@0..9
+              LetKw@0..3 "let"
+              Whitespace@3..4 " "
+              Ident@4..5 "x"
+              Whitespace@5..6 " "
+              Equals@6..7 "="
+              Whitespace@7..8 " "
+              Literal@8..9
+                Integer@8..9 "5"
+              Missing@9..9
+                Semicolon@9..9 """#]]
+        .assert_eq(&parse("let x = 5", stmts::stmt));
+    }
+
+    #[test]
+    fn missing_comma_is_inserted() {
+        expect![[r#"
+            DefItem@0..23
+              DefKw@0..3 "def"
+              Whitespace@3..4 " "
+              Ident@4..5 "f"
+              ParamList@5..21
+                OpenParen@5..6 "("
+                Param@6..13
+                  Ident@6..7 "a"
+                  Colon@7..8 ":"
+                  Whitespace@8..9 " "
+                  NameType@9..13
+                    Ident@9..12 "Int"
+                    Whitespace@12..13 " "
+                Missing@13..13
+                  Comma@13..13 ""
+                Param@13..19
+                  Ident@13..14 "b"
+                  Colon@14..15 ":"
+                  Whitespace@15..16 " "
+                  NameType@16..19
+                    Ident@16..19 "Int"
+                CloseParen@19..20 ")"
+                Whitespace@20..21 " "
+              Block@21..23
+                OpenBrace@21..22 "{"
+                CloseBrace@22..23 "}""#]]
+        .assert_eq(&parse("def f(a: Int b: Int) {}", items::item));
+    }
+
+    fn parse_fragment(source: &str, f: fn(&[lexer::Token]) -> crate::Output) -> String {
+        let tokens = lexer::Lexer::new(source).collect::<Vec<_>>();
+        let output = f(&tokens);
+        let mut result = output.debug_tree();
+
+        for error in output.errors {
+            result.push_str(&format!("\n{error}"));
+        }
+
+        result
+    }
+
+    #[test]
+    fn parse_expr_fragment() {
+        expect![[r#"
+            Root@0..5
+              BinaryExpr@0..5
+                Literal@0..2
+                  Integer@0..1 "1"
+                  Whitespace@1..2 " "
+                Plus@2..3 "+"
+                Whitespace@3..4 " "
+                NameRef@4..5
+                  Ident@4..5 "x""#]]
+        .assert_eq(&parse_fragment("1 + x", Parser::parse_expr));
+    }
+
+    #[test]
+    fn parse_type_fragment() {
+        expect![[r#"
+            Root@0..3
+              NameType@0..3
+                Ident@0..3 "Int""#]]
+        .assert_eq(&parse_fragment("Int", Parser::parse_type));
+    }
+
+    #[test]
+    fn parse_stmt_fragment() {
+        expect![[r#"
+            Root@0..10
+              LetStmt@0..10
+                LetKw@0..3 "let"
+                Whitespace@3..4 " "
+                Ident@4..5 "x"
+                Whitespace@5..6 " "
+                Equals@6..7 "="
+                Whitespace@7..8 " "
+                Literal@8..9
+                  Integer@8..9 "5"
+                Semicolon@9..10 ";""#]]
+        .assert_eq(&parse_fragment("let x = 5;", Parser::parse_stmt));
+    }
+
+    #[test]
+    fn parse_item_fragment() {
+        expect![[r#"
+            Root@0..10
+              DefItem@0..10
+                DefKw@0..3 "def"
+                Whitespace@3..4 " "
+                Ident@4..5 "f"
+                ParamList@5..8
+                  OpenParen@5..6 "("
+                  CloseParen@6..7 ")"
+                  Whitespace@7..8 " "
+                Block@8..10
+                  OpenBrace@8..9 "{"
+                  CloseBrace@9..10 "}""#]]
+        .assert_eq(&parse_fragment("def f() {}", Parser::parse_item));
+    }
+
+    #[test]
+    fn fragment_with_trailing_tokens() {
+        expect![[r#"
+            Root@0..7
+              NameRef@0..2
+                Ident@0..1 "a"
+                Whitespace@1..2 " "
+              Error@2..7
+                Ident@2..3 "b"
+                Whitespace@3..4 " "
+                OpenParen@4..5 "("
+                Ident@5..6 "c"
+                CloseParen@6..7 ")"
+            expected end of file, found identifier at 2..7"#]]
+        .assert_eq(&parse_fragment("a b (c)", Parser::parse_expr));
+    }
+}
@@ -2,15 +2,20 @@ use syntax::{Set, SyntaxKind, T};
 
 use crate::{
     parser::{CompletedMarker, Marker},
-    Parser,
+    ParseErrorKind, Parser,
 };
 
 pub(super) fn expr(p: &mut Parser) -> Option<CompletedMarker> {
     expr_bp(p, None, 1)
 }
 
-const LITERAL_START: Set =
-    Set::new(&[T![true], T![false], SyntaxKind::String, SyntaxKind::Integer]);
+const LITERAL_START: Set = Set::new(&[
+    T![true],
+    T![false],
+    SyntaxKind::String,
+    SyntaxKind::Integer,
+    SyntaxKind::Float,
+]);
 
 fn literal(p: &mut Parser) -> Option<CompletedMarker> {
     if !p.at_set(LITERAL_START) {
@@ -22,10 +27,11 @@ fn literal(p: &mut Parser) -> Option<CompletedMarker> {
     Some(m.complete(p, SyntaxKind::Literal))
 }
 
-#[allow(unused)]
-const ATOM_START: Set = LITERAL_START.union(Set::new(&[T!['(']]));
+const ATOM_START: Set = LITERAL_START.union(Set::new(&[T!['('], SyntaxKind::Ident]));
 
-const EXPR_RECOVERY_SET: Set = Set::new(&[T![let]]);
+pub(super) const EXPR_START: Set = ATOM_START.union(Set::new(&[T![-], T![+], T![!], T![~]]));
+
+const EXPR_RECOVERY_SET: Set = Set::new(&[T![let], T![def], T![const], T![;], T![')'], T![,]]);
 
 fn atom_expr(p: &mut Parser) -> Option<CompletedMarker> {
     if let Some(cm) = literal(p) {
@@ -40,7 +46,10 @@ fn atom_expr(p: &mut Parser) -> Option<CompletedMarker> {
             m.complete(p, SyntaxKind::NameRef)
         }
         _ => {
-            p.err_recover("Expected an expression", EXPR_RECOVERY_SET);
+            p.err_recover(
+                ParseErrorKind::ExpectedExpr { found: p.peek() },
+                EXPR_RECOVERY_SET,
+            );
             return None;
         }
     };
@@ -56,34 +65,63 @@ fn paren_expr(p: &mut Parser) -> CompletedMarker {
     m.complete(p, SyntaxKind::ParenExpr)
 }
 
+/// How operators with the same binding power group together.
+///
+/// Both kinds parse their right operand at a higher binding power, so `a - b - c` and
+/// `a < b < c` are both grouped to the left. Chaining non-associative operators is reported as
+/// an error afterwards, so that the rest of the expression still parses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Assoc {
+    Left,
+    /// Operators which can't be chained together, such as comparisons.
+    NonAssoc,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct BinaryOp {
+    kind: SyntaxKind,
+    bp: u8,
+    assoc: Assoc,
+}
+
+const fn op(kind: SyntaxKind, bp: u8, assoc: Assoc) -> BinaryOp {
+    BinaryOp { kind, bp, assoc }
+}
+
+/// Every binary operator, from the lowest binding power to the highest.
+///
+/// Operators made up of several tokens must come before any operator which is a prefix of them,
+/// since the first one that matches is used.
 #[rustfmt::skip]
-fn current_op(p: &Parser) -> (u8, SyntaxKind) {
-    match p.peek() {
-        T![|] if p.at(T![||])   => (1, T![||]),
-        T![|]                   => (5, T![|]),
-        T![^]                   => (6, T![|]),
-        T![&] if p.at(T![&&])   => (2, T![&&]),
-        T![|]                   => (7, T![|]),
-        T![<] if p.at(T![<=])   => (3, T![<=]),
-        T![<] if p.at(T![<<])   => (4, T![<<]),
-        T![<]                   => (3, T![<]),
-        T![>] if p.at(T![>>>])  => (4, T![>>>]),
-        T![>] if p.at(T![>>])   => (4, T![>>]),
-        T![>] if p.at(T![>=])   => (3, T![>=]),
-        T![>]                   => (3, T![>]),
-        T![=] if p.at(T![==])   => (3, T![==]),
-        T![!] if p.at(T![!=])   => (3, T![!=]),
-        T![+]                   => (8, T![+]),
-        T![-]                   => (8, T![-]),
-        T![*]                   => (9, T![*]),
-        T![/]                   => (9, T![/]),
-        T![%]                   => (9, T![%]),
-        _                       => (0, SyntaxKind::Error),
-    }
+const BINARY_OPS: &[BinaryOp] = &[
+    op(T![||],  1, Assoc::Left),
+    op(T![&&],  2, Assoc::Left),
+    op(T![==],  3, Assoc::NonAssoc),
+    op(T![!=],  3, Assoc::NonAssoc),
+    op(T![<=],  3, Assoc::NonAssoc),
+    op(T![>=],  3, Assoc::NonAssoc),
+    op(T![<<],  7, Assoc::Left),
+    op(T![>>>], 7, Assoc::Left),
+    op(T![>>],  7, Assoc::Left),
+    op(T![<],   3, Assoc::NonAssoc),
+    op(T![>],   3, Assoc::NonAssoc),
+    op(T![|],   4, Assoc::Left),
+    op(T![^],   5, Assoc::Left),
+    op(T![&],   6, Assoc::Left),
+    op(T![+],   8, Assoc::Left),
+    op(T![-],   8, Assoc::Left),
+    op(T![*],   9, Assoc::Left),
+    op(T![/],   9, Assoc::Left),
+    op(T![%],   9, Assoc::Left),
+];
+
+fn current_op(p: &Parser) -> Option<BinaryOp> {
+    BINARY_OPS.iter().copied().find(|op| p.nth_at(0, op.kind))
 }
 
 fn expr_bp(p: &mut Parser, m: Option<Marker>, bp: u8) -> Option<CompletedMarker> {
     let m = m.unwrap_or_else(|| p.start());
+    let start = p.pos();
 
     let mut lhs = match lhs(p) {
         Some(lhs) => lhs.extend_to(p, m),
@@ -93,37 +131,146 @@ fn expr_bp(p: &mut Parser, m: Option<Marker>, bp: u8) -> Option<CompletedMarker>
         }
     };
 
-    loop {
-        let (op_bp, op) = current_op(p);
+    // The start of the left operand and the range of the right operand of the last
+    // non-associative operator, if it was the last operator parsed.
+    let mut previous: Option<(u8, usize, usize, usize)> = None;
 
-        if op_bp < bp {
+    while let Some(op) = current_op(p) {
+        if op.bp < bp {
             break;
         }
 
+        let op_start = p.pos();
         let m = lhs.precede(p);
-        p.bump(op);
+        p.bump(op.kind);
 
-        expr_bp(p, None, op_bp + 1);
+        let rhs_start = p.pos();
+        expr_bp(p, None, op.bp + 1);
         lhs = m.complete(p, SyntaxKind::BinaryExpr);
+
+        let rhs_end = p.pos();
+
+        if op.assoc != Assoc::NonAssoc {
+            previous = None;
+            continue;
+        }
+
+        if let Some((previous_bp, lhs_start, middle_start, middle_end)) = previous {
+            if previous_bp == op.bp {
+                p.chained_comparison(
+                    p.range_between(op_start, rhs_start),
+                    p.range_between(lhs_start, middle_end),
+                    p.range_between(middle_start, middle_end),
+                    p.range_between(op_start, rhs_end),
+                );
+            }
+        }
+
+        let lhs_start = match previous {
+            Some((_, _, middle_start, _)) => middle_start,
+            None => start,
+        };
+
+        previous = Some((op.bp, lhs_start, rhs_start, rhs_end));
     }
 
     Some(lhs)
 }
 
+#[derive(Debug, Clone, Copy)]
+struct PrefixOp {
+    kind: SyntaxKind,
+    bp: u8,
+}
+
+/// Prefix operators bind tighter than every binary operator, but looser than postfix operators,
+/// so `-a.b?` is parsed as `-((a.b)?)`.
+#[rustfmt::skip]
+const PREFIX_OPS: &[PrefixOp] = &[
+    PrefixOp { kind: T![-], bp: 10 },
+    PrefixOp { kind: T![+], bp: 10 },
+    PrefixOp { kind: T![!], bp: 10 },
+    PrefixOp { kind: T![~], bp: 10 },
+];
+
+#[derive(Debug, Clone, Copy)]
+struct PostfixOp {
+    kind: SyntaxKind,
+    node: SyntaxKind,
+}
+
+/// Postfix operators which are a single token, applied alongside calls and field access.
+#[rustfmt::skip]
+const POSTFIX_OPS: &[PostfixOp] = &[
+    PostfixOp { kind: T![?], node: SyntaxKind::TryExpr },
+];
+
 fn lhs(p: &mut Parser) -> Option<CompletedMarker> {
-    let m;
-    let kind = match p.peek() {
-        T![-] | T![!] => {
-            m = p.start();
-            p.bump_any();
-            SyntaxKind::PrefixExpr
-        }
-        _ => return atom_expr(p),
+    let Some(op) = PREFIX_OPS.iter().find(|op| p.nth_at(0, op.kind)) else {
+        let cm = atom_expr(p)?;
+        return Some(postfix_expr(p, cm));
     };
 
-    expr_bp(p, None, 255);
-    let cm = m.complete(p, kind);
-    Some(cm)
+    let m = p.start();
+    p.bump(op.kind);
+    expr_bp(p, None, op.bp);
+    Some(m.complete(p, SyntaxKind::PrefixExpr))
+}
+
+fn postfix_expr(p: &mut Parser, mut lhs: CompletedMarker) -> CompletedMarker {
+    loop {
+        lhs = match p.peek() {
+            T!['('] => call_expr(p, lhs),
+            T![.] => field_expr(p, lhs),
+            _ => match POSTFIX_OPS.iter().find(|op| p.nth_at(0, op.kind)) {
+                Some(op) => {
+                    let m = lhs.precede(p);
+                    p.bump(op.kind);
+                    m.complete(p, op.node)
+                }
+                None => return lhs,
+            },
+        };
+    }
+}
+
+fn call_expr(p: &mut Parser, lhs: CompletedMarker) -> CompletedMarker {
+    let m = lhs.precede(p);
+    arg_list(p);
+    m.complete(p, SyntaxKind::CallExpr)
+}
+
+fn arg_list(p: &mut Parser) {
+    let m = p.start();
+
+    p.bump(T!['(']);
+
+    while !p.at(T![')']) && !p.at(SyntaxKind::Eof) {
+        if expr(p).is_none() && !p.at(T![,]) {
+            break;
+        }
+
+        if p.at(T![')']) || p.eat(T![,]) {
+            continue;
+        }
+
+        if !p.at_set(EXPR_START) {
+            break;
+        }
+
+        p.expect(T![,]);
+    }
+
+    p.expect(T![')']);
+
+    m.complete(p, SyntaxKind::ArgList);
+}
+
+fn field_expr(p: &mut Parser, lhs: CompletedMarker) -> CompletedMarker {
+    let m = lhs.precede(p);
+    p.bump(T![.]);
+    p.expect(SyntaxKind::Ident);
+    m.complete(p, SyntaxKind::FieldExpr)
 }
 
 #[cfg(test)]
@@ -169,4 +316,321 @@ mod tests {
             atom_expr(p);
         }));
     }
+
+    #[test]
+    fn parse_float() {
+        expect![[r#"Literal@0..6
+  Float@0..6 "1.5e10""#]]
+        .assert_eq(&parse("1.5e10", |p| {
+            atom_expr(p);
+        }));
+    }
+
+    fn parse_expr(source: &str) -> String {
+        parse(source, |p| {
+            expr(p);
+        })
+    }
+
+    #[test]
+    fn every_binary_op() {
+        for op in BINARY_OPS {
+            let source = format!("a {} b", op.kind.display_name().trim_matches('`'));
+            let tree = parse_expr(&source);
+
+            assert!(tree.starts_with("BinaryExpr@0.."), "{source}: {tree}");
+            assert!(
+                tree.contains(&format!("{:?}@2..", op.kind)),
+                "{source}: {tree}"
+            );
+        }
+    }
+
+    #[test]
+    fn binary_ops_are_left_associative() {
+        for op in BINARY_OPS.iter().filter(|op| op.assoc == Assoc::Left) {
+            let text = op.kind.display_name().trim_matches('`');
+            let source = format!("a{text}b{text}c");
+            let tree = parse_expr(&source);
+
+            assert!(
+                tree.lines()
+                    .nth(1)
+                    .unwrap()
+                    .trim()
+                    .starts_with("BinaryExpr"),
+                "{source}: {tree}"
+            );
+        }
+    }
+
+    #[test]
+    fn lazy_or_below_lazy_and() {
+        expect![[r#"
+            BinaryExpr@0..7
+              NameRef@0..1
+                Ident@0..1 "a"
+              LazyOr@1..3 "||"
+              BinaryExpr@3..7
+                NameRef@3..4
+                  Ident@3..4 "b"
+                LazyAnd@4..6 "&&"
+                NameRef@6..7
+                  Ident@6..7 "c""#]]
+        .assert_eq(&parse_expr("a||b&&c"));
+    }
+
+    #[test]
+    fn lazy_and_below_comparison() {
+        expect![[r#"
+            BinaryExpr@0..7
+              NameRef@0..1
+                Ident@0..1 "a"
+              LazyAnd@1..3 "&&"
+              BinaryExpr@3..7
+                NameRef@3..4
+                  Ident@3..4 "b"
+                EqualTo@4..6 "=="
+                NameRef@6..7
+                  Ident@6..7 "c""#]]
+        .assert_eq(&parse_expr("a&&b==c"));
+    }
+
+    #[test]
+    fn comparison_below_bitwise_or() {
+        expect![[r#"
+            BinaryExpr@0..5
+              NameRef@0..1
+                Ident@0..1 "a"
+              LessThan@1..2 "<"
+              BinaryExpr@2..5
+                NameRef@2..3
+                  Ident@2..3 "b"
+                Or@3..4 "|"
+                NameRef@4..5
+                  Ident@4..5 "c""#]]
+        .assert_eq(&parse_expr("a<b|c"));
+    }
+
+    #[test]
+    fn bitwise_or_below_bitwise_xor() {
+        expect![[r#"
+            BinaryExpr@0..5
+              NameRef@0..1
+                Ident@0..1 "a"
+              Or@1..2 "|"
+              BinaryExpr@2..5
+                NameRef@2..3
+                  Ident@2..3 "b"
+                Xor@3..4 "^"
+                NameRef@4..5
+                  Ident@4..5 "c""#]]
+        .assert_eq(&parse_expr("a|b^c"));
+    }
+
+    #[test]
+    fn bitwise_xor_below_bitwise_and() {
+        expect![[r#"
+            BinaryExpr@0..5
+              NameRef@0..1
+                Ident@0..1 "a"
+              Xor@1..2 "^"
+              BinaryExpr@2..5
+                NameRef@2..3
+                  Ident@2..3 "b"
+                And@3..4 "&"
+                NameRef@4..5
+                  Ident@4..5 "c""#]]
+        .assert_eq(&parse_expr("a^b&c"));
+    }
+
+    #[test]
+    fn bitwise_and_below_shift() {
+        expect![[r#"
+            BinaryExpr@0..7
+              NameRef@0..1
+                Ident@0..1 "a"
+              And@1..2 "&"
+              BinaryExpr@2..7
+                NameRef@2..3
+                  Ident@2..3 "b"
+                UnsignedRightShift@3..6 ">>>"
+                NameRef@6..7
+                  Ident@6..7 "c""#]]
+        .assert_eq(&parse_expr("a&b>>>c"));
+    }
+
+    #[test]
+    fn shift_below_additive() {
+        expect![[r#"
+            BinaryExpr@0..6
+              NameRef@0..1
+                Ident@0..1 "a"
+              LeftShift@1..3 "<<"
+              BinaryExpr@3..6
+                NameRef@3..4
+                  Ident@3..4 "b"
+                Plus@4..5 "+"
+                NameRef@5..6
+                  Ident@5..6 "c""#]]
+        .assert_eq(&parse_expr("a<<b+c"));
+    }
+
+    #[test]
+    fn additive_below_multiplicative() {
+        expect![[r#"
+            BinaryExpr@0..5
+              NameRef@0..1
+                Ident@0..1 "a"
+              Minus@1..2 "-"
+              BinaryExpr@2..5
+                NameRef@2..3
+                  Ident@2..3 "b"
+                Percent@3..4 "%"
+                NameRef@4..5
+                  Ident@4..5 "c""#]]
+        .assert_eq(&parse_expr("a-b%c"));
+    }
+
+    #[test]
+    fn spaced_tokens_are_not_glued() {
+        expect![[r#"
+            BinaryExpr@0..4
+              NameRef@0..1
+                Ident@0..1 "a"
+              LessThan@1..2 "<"
+              Whitespace@2..3 " "
+              Error@3..4
+                Equals@3..4 "=""#]]
+        .assert_eq(&parse_expr("a< =b"));
+    }
+
+    #[test]
+    fn every_prefix_op() {
+        for op in PREFIX_OPS {
+            let source = format!("{}a", op.kind.display_name().trim_matches('`'));
+            let tree = parse_expr(&source);
+
+            assert!(tree.starts_with("PrefixExpr@0..2"), "{source}: {tree}");
+        }
+    }
+
+    #[test]
+    fn prefix_binds_tighter_than_binary() {
+        expect![[r#"
+            BinaryExpr@0..4
+              PrefixExpr@0..2
+                Tilde@0..1 "~"
+                NameRef@1..2
+                  Ident@1..2 "a"
+              Star@2..3 "*"
+              NameRef@3..4
+                Ident@3..4 "b""#]]
+        .assert_eq(&parse_expr("~a*b"));
+    }
+
+    #[test]
+    fn postfix_binds_tighter_than_prefix() {
+        expect![[r#"
+            PrefixExpr@0..5
+              Minus@0..1 "-"
+              TryExpr@1..5
+                FieldExpr@1..4
+                  NameRef@1..2
+                    Ident@1..2 "a"
+                  Dot@2..3 "."
+                  Ident@3..4 "b"
+                Question@4..5 "?""#]]
+        .assert_eq(&parse_expr("-a.b?"));
+    }
+
+    #[test]
+    fn call_with_args() {
+        expect![[r#"
+            TryExpr@0..9
+              CallExpr@0..8
+                NameRef@0..1
+                  Ident@0..1 "f"
+                ArgList@1..8
+                  OpenParen@1..2 "("
+                  NameRef@2..3
+                    Ident@2..3 "a"
+                  Comma@3..4 ","
+                  BinaryExpr@4..7
+                    NameRef@4..5
+                      Ident@4..5 "b"
+                    Plus@5..6 "+"
+                    Literal@6..7
+                      Integer@6..7 "1"
+                  CloseParen@7..8 ")"
+              Question@8..9 "?""#]]
+        .assert_eq(&parse_expr("f(a,b+1)?"));
+    }
+
+    #[test]
+    fn field_access_on_call() {
+        expect![[r#"
+            CallExpr@0..8
+              FieldExpr@0..5
+                CallExpr@0..3
+                  NameRef@0..1
+                    Ident@0..1 "f"
+                  ArgList@1..3
+                    OpenParen@1..2 "("
+                    CloseParen@2..3 ")"
+                Dot@3..4 "."
+                Ident@4..5 "x"
+              ArgList@5..8
+                OpenParen@5..6 "("
+                NameRef@6..7
+                  Ident@6..7 "y"
+                CloseParen@7..8 ")""#]]
+        .assert_eq(&parse_expr("f().x(y)"));
+    }
+
+    fn chained_suggestions(source: &str) -> Vec<String> {
+        let tokens = lexer::Lexer::new(source).collect::<Vec<_>>();
+
+        Parser::parse_tokens(&tokens)
+            .errors
+            .into_iter()
+            .map(|error| match error.kind {
+                ParseErrorKind::ChainedComparison { suggestion } => suggestion,
+                kind => panic!("unexpected error: {kind}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chained_comparison() {
+        assert_eq!(
+            chained_suggestions("def f() { let x = a < b < c; }"),
+            &["a < b && b < c"]
+        );
+        assert_eq!(
+            chained_suggestions("def f() { let x = a == b + 1 != c <= d; }"),
+            &["a == b + 1 && b + 1 != c", "b + 1 != c && c <= d"]
+        );
+    }
+
+    #[test]
+    fn separate_comparisons_are_not_chained() {
+        assert!(chained_suggestions("def f() { let x = a < b && (b < c) == d; }").is_empty());
+    }
+
+    #[test]
+    fn chained_comparison_tree() {
+        expect![[r#"
+            BinaryExpr@0..6
+              BinaryExpr@0..3
+                NameRef@0..1
+                  Ident@0..1 "a"
+                LessThan@1..2 "<"
+                NameRef@2..3
+                  Ident@2..3 "b"
+              GreaterThanEquals@3..5 ">="
+              NameRef@5..6
+                Ident@5..6 "c""#]]
+        .assert_eq(&parse_expr("a<b>=c"));
+    }
 }
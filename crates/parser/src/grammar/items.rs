@@ -1,16 +1,24 @@
-use syntax::{Set, SyntaxKind, EMPTY_SET, T};
+use syntax::{Set, SyntaxKind, T};
 
-use crate::Parser;
+use crate::{ParseErrorKind, Parser};
 
-use super::{stmts, types};
+use super::{exprs, stmts, types};
 
 const PARAM_START: Set = Set::new(&[SyntaxKind::Ident]).union(types::TYPE_START);
 
+const ITEM_RECOVERY_SET: Set = Set::new(&[T![def], T![const]]);
+
+const PARAM_RECOVERY_SET: Set = Set::new(&[T![')'], T![,], T!['{'], T![->], T![def], T![const]]);
+
 pub fn item(p: &mut Parser) {
     match p.peek() {
         T![def] => def_item(p),
+        T![const] => const_item(p),
         _ => {
-            p.err_recover("Expected an item", EMPTY_SET);
+            p.err_recover_until(
+                ParseErrorKind::ExpectedItem { found: p.peek() },
+                ITEM_RECOVERY_SET,
+            );
         }
     }
 }
@@ -31,23 +39,48 @@ fn def_item(p: &mut Parser) {
     m.complete(p, SyntaxKind::DefItem);
 }
 
+fn const_item(p: &mut Parser) {
+    let m = p.start();
+
+    p.bump(T![const]);
+    p.expect(SyntaxKind::Ident);
+    p.expect(T![:]);
+    types::type_(p);
+    p.expect(T![=]);
+    exprs::expr(p);
+    p.expect(T![;]);
+
+    m.complete(p, SyntaxKind::ConstItem);
+}
+
 fn param_list(p: &mut Parser) {
     let m = p.start();
 
     p.expect(T!['(']);
 
     while !p.at(T![')']) && !p.at(SyntaxKind::Eof) {
-        if !p.at_set(PARAM_START) {
-            p.error("Expected parameter");
-            m.abandon(p);
-            return;
+        if p.at_set(PARAM_START) {
+            param(p);
+        } else {
+            p.err_recover_until(
+                ParseErrorKind::ExpectedParam { found: p.peek() },
+                PARAM_RECOVERY_SET,
+            );
+
+            if matches!(p.peek(), T!['{'] | T![->] | T![def] | T![const]) {
+                break;
+            }
         }
 
-        param(p);
+        if p.at(T![')']) || p.eat(T![,]) {
+            continue;
+        }
 
-        if !p.at(T![')']) {
-            p.expect(T![,]);
+        if !p.at_set(PARAM_START) {
+            break;
         }
+
+        p.expect(T![,]);
     }
 
     p.expect(T![')']);
@@ -65,12 +98,12 @@ fn param(p: &mut Parser) {
     m.complete(p, SyntaxKind::Param);
 }
 
-fn block(p: &mut Parser) {
+pub(super) fn block(p: &mut Parser) {
     let m = p.start();
 
     p.expect(T!['{']);
 
-    while !p.at(T!['}']) && !p.at(SyntaxKind::Eof) {
+    while !matches!(p.peek(), T!['}'] | T![def] | T![const] | SyntaxKind::Eof) {
         stmts::stmt(p);
     }
 
@@ -1,18 +1,28 @@
-use syntax::{SyntaxKind, EMPTY_SET, T};
+use syntax::{Set, SyntaxKind, T};
 
-use crate::Parser;
+use crate::{ParseErrorKind, Parser};
 
 use super::{exprs, types};
 
+const STMT_RECOVERY_SET: Set = Set::new(&[T![;], T!['}'], T![let], T![def], T![const]]);
+
 pub fn stmt(p: &mut Parser) {
     match p.peek() {
         T![let] => let_stmt(p),
-        _ => {
-            p.err_recover("Expected a statement", EMPTY_SET);
-        }
+        _ if p.at_set(exprs::EXPR_START) => expr_stmt(p),
+        _ => recover(p),
     }
 }
 
+/// Skips to the end of the current statement, so that the next one can be parsed cleanly.
+fn recover(p: &mut Parser) {
+    p.err_recover_until(
+        ParseErrorKind::ExpectedStmt { found: p.peek() },
+        STMT_RECOVERY_SET,
+    );
+    p.eat(T![;]);
+}
+
 fn let_stmt(p: &mut Parser) {
     let m = p.start();
 
@@ -28,7 +38,27 @@ fn let_stmt(p: &mut Parser) {
         exprs::expr(p);
     }
 
-    p.expect(T![;]);
+    if !p.expect(T![;]) {
+        recover(p);
+    }
 
     m.complete(p, SyntaxKind::LetStmt);
 }
+
+fn expr_stmt(p: &mut Parser) {
+    let m = p.start();
+
+    exprs::expr(p);
+
+    // An expression at the end of a block without a semicolon is the value of the block.
+    if p.at(T!['}']) {
+        m.abandon(p);
+        return;
+    }
+
+    if !p.expect(T![;]) {
+        recover(p);
+    }
+
+    m.complete(p, SyntaxKind::ExprStmt);
+}
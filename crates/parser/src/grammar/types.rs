@@ -1,10 +1,10 @@
 use syntax::{Set, SyntaxKind, T};
 
-use crate::Parser;
+use crate::{ParseErrorKind, Parser};
 
 pub const TYPE_START: Set = Set::new(&[SyntaxKind::Ident]);
 
-const TYPE_RECOVERY_SET: Set = Set::new(&[T![')'], T![,]]);
+const TYPE_RECOVERY_SET: Set = Set::new(&[T![')'], T![,], T![=], T![;], T![def], T![const]]);
 
 pub(super) fn type_(p: &mut Parser) {
     match p.peek() {
@@ -14,7 +14,10 @@ pub(super) fn type_(p: &mut Parser) {
             m.complete(p, SyntaxKind::NameType);
         }
         _ => {
-            p.err_recover("Expected type", TYPE_RECOVERY_SET);
+            p.err_recover(
+                ParseErrorKind::ExpectedType { found: p.peek() },
+                TYPE_RECOVERY_SET,
+            );
         }
     }
 }
@@ -1,37 +1,44 @@
 use lexer::Token;
+use rowan::{TextRange, TextSize};
 use syntax::SyntaxKind;
 
 #[derive(Default)]
 pub struct Input {
     kinds: Vec<SyntaxKind>,
     joint: Vec<bool>,
+    ranges: Vec<TextRange>,
+    len: TextSize,
 }
 
 impl Input {
     pub fn from_tokens(tokens: &[Token]) -> Self {
         let mut result = Self::default();
-        let mut joint = false;
-
-        for token in tokens {
-            if token.kind.is_trivia() {
-                joint = false;
-            } else {
-                if joint {
-                    result.was_joint();
-                } else {
-                    joint = true;
-                }
-
-                result.push(token.kind.into());
+
+        for token in tokens.iter().filter(|token| !token.kind.is_trivia()) {
+            let range = TextRange::new(
+                TextSize::from(token.span.start as u32),
+                TextSize::from(token.span.end as u32),
+            );
+
+            result.push(token.kind.into(), range);
+
+            if token.is_joint() {
+                result.was_joint();
             }
         }
 
+        if let Some(last) = tokens.last() {
+            result.len = TextSize::from(last.span.end as u32);
+        }
+
         result
     }
 
-    pub fn push(&mut self, kind: SyntaxKind) {
+    pub fn push(&mut self, kind: SyntaxKind, range: TextRange) {
         self.kinds.push(kind);
         self.joint.push(false);
+        self.ranges.push(range);
+        self.len = self.len.max(range.end());
     }
 
     pub fn was_joint(&mut self) {
@@ -47,4 +54,12 @@ impl Input {
     pub fn is_joint(&self, index: usize) -> bool {
         self.joint.get(index).copied().unwrap_or(false)
     }
+
+    /// The range of a token, or an empty range at the end of the source past the last token.
+    pub fn range(&self, index: usize) -> TextRange {
+        self.ranges
+            .get(index)
+            .copied()
+            .unwrap_or_else(|| TextRange::empty(self.len))
+    }
 }
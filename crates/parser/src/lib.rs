@@ -1,12 +1,18 @@
+mod error_codes;
 mod event;
 mod grammar;
 mod input;
 mod output;
+mod parse;
 mod parse_error;
 mod parser;
+mod reparse;
 mod sink;
 
 pub use crate::parser::Parser;
+pub use error_codes::explain;
 pub use input::Input;
 pub use output::Output;
-pub use parse_error::ParseError;
+pub use parse::{parse, Parse};
+pub use parse_error::{DisplayParseError, ParseError, ParseErrorKind};
+pub use reparse::TextEdit;
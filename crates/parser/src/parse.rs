@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use lexer::Lexer;
+use rowan::GreenNode;
+use syntax::{
+    ast::{AstNode, Root},
+    SyntaxNode,
+};
+
+use crate::{Output, ParseError, Parser};
+
+/// The result of parsing a source file.
+///
+/// Only the immutable green tree is stored, so this is cheap to clone and can be sent
+/// between threads. The typed tree is created on demand with [`Parse::tree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parse {
+    green_node: GreenNode,
+    errors: Arc<[ParseError]>,
+}
+
+impl Parse {
+    pub(crate) fn new(green_node: GreenNode, errors: Vec<ParseError>) -> Self {
+        Self {
+            green_node,
+            errors: errors.into(),
+        }
+    }
+
+    pub fn green_node(&self) -> &GreenNode {
+        &self.green_node
+    }
+
+    pub fn syntax_node(&self) -> SyntaxNode {
+        SyntaxNode::new_root(self.green_node.clone())
+    }
+
+    pub fn tree(&self) -> Root {
+        Root::cast(self.syntax_node()).unwrap()
+    }
+
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
+
+    pub fn debug_tree(&self) -> String {
+        let formatted = format!("{:#?}", self.syntax_node());
+        formatted[0..formatted.len() - 1].to_string()
+    }
+}
+
+impl From<Output> for Parse {
+    fn from(output: Output) -> Self {
+        Self {
+            green_node: output.green_node,
+            errors: output.errors.into(),
+        }
+    }
+}
+
+/// Lexes and parses a source file in one step.
+pub fn parse(source: &str) -> Parse {
+    let tokens = Lexer::new(source).collect::<Vec<_>>();
+    Parser::parse_tokens(&tokens).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use syntax::ast::{Expr, Item, Stmt};
+
+    use super::*;
+
+    #[test]
+    fn parse_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Parse>();
+    }
+
+    #[test]
+    fn parse_typed_root() {
+        let parse = parse("def main(x: Int) -> Int {\n    let y = x + 1;\n}");
+        assert!(parse.errors().is_empty());
+
+        let Some(Item::DefItem(def)) = parse.tree().items().next() else {
+            panic!("expected a def item");
+        };
+        assert_eq!(def.name().unwrap().text(), "main");
+        assert_eq!(def.param_list().unwrap().params().count(), 1);
+        assert!(def.return_type().is_some());
+
+        let Some(Stmt::LetStmt(let_stmt)) = def.body().unwrap().stmts().next() else {
+            panic!("expected a let statement");
+        };
+        let Some(Expr::BinaryExpr(binary)) = let_stmt.value() else {
+            panic!("expected a binary expression");
+        };
+        assert_eq!(binary.op().unwrap().text(), "+");
+        assert!(matches!(binary.rhs(), Some(Expr::Literal(_))));
+    }
+
+    #[test]
+    fn parse_is_shared_across_threads() {
+        let parse = parse("def main() {} def other() {}");
+        let clone = parse.clone();
+
+        let count = std::thread::spawn(move || clone.tree().items().count())
+            .join()
+            .unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(parse.tree().items().count(), 2);
+    }
+}
@@ -1,19 +1,220 @@
+use std::fmt;
+
+use diagnostics::Diagnostic;
+use lexer::Base;
 use rowan::TextRange;
+use syntax::{LineIndex, Set, SyntaxKind};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ParseError {
-    pub message: String,
+    pub kind: ParseErrorKind,
     pub span: TextRange,
 }
 
-impl std::fmt::Display for ParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    ExpectedToken { expected: Set, found: SyntaxKind },
+    ExpectedItem { found: SyntaxKind },
+    ExpectedStmt { found: SyntaxKind },
+    ExpectedExpr { found: SyntaxKind },
+    ExpectedType { found: SyntaxKind },
+    ExpectedParam { found: SyntaxKind },
+    UnterminatedString,
+    MissingDigits,
+    InvalidDigit { base: Base },
+    InvalidSuffix { suffix: String },
+    EmptyExponent,
+    ChainedComparison { suggestion: String },
+    UnsupportedFloat,
+}
+
+impl ParseErrorKind {
+    /// The stable code of the error, which can be looked up with [`crate::explain`].
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::ExpectedToken { .. } => "E0001",
+            Self::ExpectedItem { .. } => "E0002",
+            Self::ExpectedStmt { .. } => "E0003",
+            Self::ExpectedExpr { .. } => "E0004",
+            Self::ExpectedType { .. } => "E0005",
+            Self::ExpectedParam { .. } => "E0006",
+            Self::UnterminatedString => "E0007",
+            Self::MissingDigits => "E0008",
+            Self::InvalidDigit { .. } => "E0009",
+            Self::InvalidSuffix { .. } => "E0010",
+            Self::EmptyExponent => "E0011",
+            Self::ChainedComparison { .. } => "E0012",
+            Self::UnsupportedFloat => "E0013",
+        }
+    }
+
+    /// The kind of token which was found instead of what the parser expected.
+    pub fn found(&self) -> Option<SyntaxKind> {
+        match self {
+            Self::ExpectedToken { found, .. }
+            | Self::ExpectedItem { found }
+            | Self::ExpectedStmt { found }
+            | Self::ExpectedExpr { found }
+            | Self::ExpectedType { found }
+            | Self::ExpectedParam { found } => Some(*found),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ExpectedToken { expected, found } => {
+                write!(
+                    f,
+                    "expected {}, found {}",
+                    one_of(*expected),
+                    found.display_name()
+                )
+            }
+            Self::ExpectedItem { found } => {
+                write!(f, "expected an item, found {}", found.display_name())
+            }
+            Self::ExpectedStmt { found } => {
+                write!(f, "expected a statement, found {}", found.display_name())
+            }
+            Self::ExpectedExpr { found } => {
+                write!(f, "expected an expression, found {}", found.display_name())
+            }
+            Self::ExpectedType { found } => {
+                write!(f, "expected a type, found {}", found.display_name())
+            }
+            Self::ExpectedParam { found } => {
+                write!(f, "expected a parameter, found {}", found.display_name())
+            }
+            Self::UnterminatedString => write!(f, "unterminated string literal"),
+            Self::MissingDigits => write!(f, "expected digits after the integer base prefix"),
+            Self::InvalidDigit { base } => write!(f, "invalid base {} digit", *base as u32),
+            Self::InvalidSuffix { suffix } => {
+                write!(f, "invalid suffix `{suffix}` for number literal")
+            }
+            Self::EmptyExponent => write!(f, "expected at least one digit in the exponent"),
+            Self::ChainedComparison { .. } => write!(f, "comparison operators cannot be chained"),
+            Self::UnsupportedFloat => write!(f, "float literals are not supported yet"),
+        }
+    }
+}
+
+fn one_of(expected: Set) -> String {
+    let names = expected
+        .iter()
+        .map(SyntaxKind::display_name)
+        .collect::<Vec<_>>();
+
+    match names.as_slice() {
+        [] => "nothing".to_string(),
+        [name] => name.to_string(),
+        [rest @ .., last] => format!("one of {} or {}", rest.join(", "), last),
+    }
+}
+
+impl ParseError {
+    pub fn message(&self) -> String {
+        self.kind.to_string()
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let diagnostic = Diagnostic::error(self.message(), self.span).with_code(self.code());
+
+        match &self.kind {
+            ParseErrorKind::ChainedComparison { suggestion } => {
+                diagnostic.with_help(format!("split the comparison into two: `{suggestion}`"))
+            }
+            _ => diagnostic,
+        }
+    }
+
+    /// Displays the error with one based line and column numbers instead of byte offsets.
+    pub fn display<'a>(&'a self, line_index: &'a LineIndex) -> DisplayParseError<'a> {
+        DisplayParseError {
+            error: self,
+            line_index,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
             "{} at {}..{}",
-            self.message,
+            self.kind,
             u32::from(self.span.start()),
             u32::from(self.span.end()),
         )
     }
 }
+
+pub struct DisplayParseError<'a> {
+    error: &'a ParseError,
+    line_index: &'a LineIndex,
+}
+
+impl<'a> fmt::Display for DisplayParseError<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let start = self.line_index.line_col(self.error.span.start());
+        let end = self.line_index.line_col(self.error.span.end());
+
+        write!(
+            f,
+            "{} at {}:{}..{}:{}",
+            self.error.kind,
+            start.line + 1,
+            start.col + 1,
+            end.line + 1,
+            end.col + 1,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syntax::T;
+
+    use super::*;
+
+    #[test]
+    fn display_line_col() {
+        let line_index = LineIndex::new("def main() {\n    let = 5;\n}");
+        let error = ParseError {
+            kind: ParseErrorKind::ExpectedToken {
+                expected: Set::new(&[SyntaxKind::Ident]),
+                found: T![=],
+            },
+            span: TextRange::new(21.into(), 22.into()),
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "expected identifier, found `=` at 21..22"
+        );
+        assert_eq!(
+            error.display(&line_index).to_string(),
+            "expected identifier, found `=` at 2:9..2:10"
+        );
+    }
+
+    #[test]
+    fn expected_one_of() {
+        let kind = ParseErrorKind::ExpectedToken {
+            expected: Set::new(&[T![,], T![')'], SyntaxKind::Ident]),
+            found: SyntaxKind::Eof,
+        };
+
+        assert_eq!(
+            kind.to_string(),
+            "expected one of identifier, `)` or `,`, found end of file"
+        );
+        assert_eq!(kind.code(), "E0001");
+    }
+}
@@ -1,7 +1,17 @@
+use std::cell::Cell;
+
 use lexer::Token;
+use rowan::{TextRange, TextSize};
 use syntax::{Set, SyntaxKind, T};
 
-use crate::{event::Event, grammar::root, input::Input, output::Output, sink::Sink};
+use crate::{
+    event::Event,
+    grammar::{self, root},
+    input::Input,
+    output::Output,
+    sink::Sink,
+    ParseErrorKind,
+};
 
 mod completed_marker;
 mod marker;
@@ -13,6 +23,8 @@ pub struct Parser {
     input: Input,
     cursor: usize,
     events: Vec<Event>,
+    expected: Cell<Set>,
+    last_error: Option<usize>,
 }
 
 impl Parser {
@@ -21,12 +33,55 @@ impl Parser {
             input,
             cursor: 0,
             events: Vec::new(),
+            expected: Cell::new(Set::EMPTY),
+            last_error: None,
         }
     }
 
     pub fn parse_tokens(tokens: &[Token]) -> Output {
+        Self::parse_with(tokens, root)
+    }
+
+    /// Parses a single expression, such as `a + f(b)`.
+    pub fn parse_expr(tokens: &[Token]) -> Output {
+        Self::parse_with(tokens, grammar::expr_fragment)
+    }
+
+    /// Parses a single type, such as `Int`.
+    pub fn parse_type(tokens: &[Token]) -> Output {
+        Self::parse_with(tokens, grammar::type_fragment)
+    }
+
+    /// Parses a single statement, such as `let x = 5;`.
+    pub fn parse_stmt(tokens: &[Token]) -> Output {
+        Self::parse_with(tokens, grammar::stmt_fragment)
+    }
+
+    /// Parses a single item, such as a `def` function.
+    pub fn parse_item(tokens: &[Token]) -> Output {
+        Self::parse_with(tokens, grammar::item_fragment)
+    }
+
+    /// Parses a block or item on its own, as long as it makes up every token.
+    pub(crate) fn reparse_tokens(tokens: &[Token], kind: SyntaxKind) -> Option<Output> {
+        let mut parser = Self::new(Input::from_tokens(tokens));
+
+        match kind {
+            SyntaxKind::Block => grammar::reparse_block(&mut parser),
+            SyntaxKind::DefItem => grammar::reparse_item(&mut parser),
+            _ => return None,
+        }
+
+        if parser.peek() != SyntaxKind::Eof {
+            return None;
+        }
+
+        Some(parser.parse(tokens))
+    }
+
+    fn parse_with(tokens: &[Token], f: fn(&mut Parser) -> CompletedMarker) -> Output {
         let mut parser = Self::new(Input::from_tokens(tokens));
-        root(&mut parser);
+        f(&mut parser);
         parser.parse(tokens)
     }
 
@@ -53,58 +108,36 @@ impl Parser {
     }
 
     pub(crate) fn at(&self, kind: SyntaxKind) -> bool {
+        // Checking for the end of file is how loops terminate, so it's never worth suggesting.
+        if kind != SyntaxKind::Eof {
+            self.expected.set(self.expected.get().with(kind));
+        }
         self.nth_at(0, kind)
     }
 
     pub(crate) fn nth_at(&self, n: usize, kind: SyntaxKind) -> bool {
-        match kind {
-            T![&&] => self.at_composite2(n, T![&], T![&]),
-            T![||] => self.at_composite2(n, T![|], T![|]),
-            T![<=] => self.at_composite2(n, T![<], T![=]),
-            T![>=] => self.at_composite2(n, T![>], T![=]),
-            T![==] => self.at_composite2(n, T![=], T![=]),
-            T![!=] => self.at_composite2(n, T![!], T![=]),
-            T![<<] => self.at_composite2(n, T![<], T![<]),
-            T![>>] => self.at_composite2(n, T![>], T![>]),
-            T![>>>] => self.at_composite3(n, T![>], T![>], T![>]),
-            _ => self.input.kind(self.cursor + n) == kind,
-        }
+        let Some(glued) = kind.glued() else {
+            return self.nth(n) == kind;
+        };
+
+        // Every part but the last has to be joined to the next one.
+        let parts = glued.parts();
+        parts.iter().enumerate().all(|(i, &part)| {
+            self.nth(n + i) == part.into()
+                && (i == parts.len() - 1 || self.input.is_joint(self.cursor + n + i))
+        })
     }
 
     pub(crate) fn at_set(&self, kinds: Set) -> bool {
         kinds.contains(self.peek())
     }
 
-    fn at_composite2(&self, n: usize, a: SyntaxKind, b: SyntaxKind) -> bool {
-        self.nth(n) == a && self.nth(n + 1) == b && self.input.is_joint(self.cursor + n)
-    }
-
-    fn at_composite3(&self, n: usize, a: SyntaxKind, b: SyntaxKind, c: SyntaxKind) -> bool {
-        self.nth(n) == a
-            && self.nth(n + 1) == b
-            && self.nth(n + 2) == c
-            && self.input.is_joint(self.cursor + n)
-            && self.input.is_joint(self.cursor + n + 1)
-    }
-
     pub(crate) fn eat(&mut self, kind: SyntaxKind) -> bool {
         if !self.at(kind) {
             return false;
         }
 
-        let token_count = match kind {
-            T![&&] => 2,
-            T![||] => 2,
-            T![<=] => 2,
-            T![>=] => 2,
-            T![==] => 2,
-            T![!=] => 2,
-            T![<<] => 2,
-            T![>>] => 2,
-            T![>>>] => 3,
-            _ => 1,
-        };
-
+        let token_count = kind.glued().map_or(1, |glued| glued.parts().len());
         self.add_token(kind, token_count);
         true
     }
@@ -121,43 +154,159 @@ impl Parser {
         self.add_token(kind, 1);
     }
 
-    pub(crate) fn error<T>(&mut self, message: T)
-    where
-        T: Into<String>,
-    {
-        self.events.push(Event::Error(message.into()));
+    /// Reports an error at the current token.
+    pub(crate) fn error(&mut self, kind: ParseErrorKind) {
+        let span = self.input.range(self.cursor);
+        self.error_at(kind, span);
+    }
+
+    pub(crate) fn error_at(&mut self, kind: ParseErrorKind, span: TextRange) {
+        self.push_error(kind, span, self.cursor);
+    }
+
+    /// Only the first error at a given token is kept, since any others are usually caused by it.
+    fn push_error(&mut self, kind: ParseErrorKind, span: TextRange, pos: usize) {
+        if self.last_error == Some(pos) {
+            return;
+        }
+        self.last_error = Some(pos);
+        self.events.push(Event::Error { kind, span });
+    }
+
+    /// An empty range directly after the previous token, where a missing token would be inserted.
+    pub(crate) fn insertion_point(&self) -> TextRange {
+        match self.cursor.checked_sub(1) {
+            Some(previous) => TextRange::empty(self.input.range(previous).end()),
+            None => TextRange::empty(TextSize::from(0)),
+        }
     }
 
     pub(crate) fn expect(&mut self, kind: SyntaxKind) -> bool {
         if self.eat(kind) {
             return true;
         }
-        self.error(format!("expected {:?}", kind));
+        let span = self.insertion_point();
+        self.error_at(
+            ParseErrorKind::ExpectedToken {
+                expected: self.expected.get(),
+                found: self.peek(),
+            },
+            span,
+        );
+        self.missing(kind);
         false
     }
 
-    pub(crate) fn err_recover(&mut self, message: &str, recovery: Set) {
+    /// Inserts a zero-width token of the given kind, wrapped in a `Missing` node.
+    ///
+    /// This keeps the shape of the tree the same as if the token had been written,
+    /// while still making it clear that it wasn't.
+    fn missing(&mut self, kind: SyntaxKind) {
+        let m = self.start();
+        self.events.push(Event::AddToken {
+            kind,
+            token_count: 0,
+        });
+        m.complete(self, SyntaxKind::Missing);
+    }
+
+    pub(crate) fn err_recover(&mut self, kind: ParseErrorKind, recovery: Set) {
         match self.peek() {
             T!['{'] | T!['}'] => {
-                self.error(message);
+                self.error(kind);
                 return;
             }
             _ => (),
         }
 
         if self.at_set(recovery) {
-            self.error(message);
+            self.error(kind);
             return;
         }
 
+        let start = self.cursor;
         let m = self.start();
-        self.error(message);
         self.bump_any();
         m.complete(self, SyntaxKind::Error);
+        self.push_error(kind, self.range_since(start), start);
+    }
+
+    /// Reports an error and wraps every token up to the synchronisation set in an `Error` node.
+    ///
+    /// Delimited groups are skipped as a whole, so a set containing `}` won't stop inside
+    /// of a nested block.
+    pub(crate) fn err_recover_until(&mut self, kind: ParseErrorKind, sync: Set) {
+        if self.peek() == SyntaxKind::Eof || sync.contains(self.peek()) {
+            self.error(kind);
+            return;
+        }
+
+        let start = self.cursor;
+        let m = self.start();
+        let mut depth = 0usize;
+
+        loop {
+            let current = self.peek();
+
+            if current == SyntaxKind::Eof || (depth == 0 && sync.contains(current)) {
+                break;
+            }
+
+            match current {
+                T!['('] | T!['{'] => depth += 1,
+                T![')'] | T!['}'] => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+
+            self.bump_any();
+        }
+
+        m.complete(self, SyntaxKind::Error);
+        self.push_error(kind, self.range_since(start), start);
+    }
+
+    /// The range covering every token from the start index up to the cursor.
+    pub(crate) fn range_since(&self, start: usize) -> TextRange {
+        self.range_between(start, self.cursor)
+    }
+
+    /// The range covering every token from the start index up to, but excluding, the end index.
+    pub(crate) fn range_between(&self, start: usize, end: usize) -> TextRange {
+        let first = self.input.range(start);
+
+        match end.checked_sub(1) {
+            Some(last) if last >= start => first.cover(self.input.range(last)),
+            _ => TextRange::empty(first.start()),
+        }
+    }
+
+    /// The index of the current token, for use with [`Parser::range_between`].
+    pub(crate) fn pos(&self) -> usize {
+        self.cursor
+    }
+
+    /// Reports comparisons which were chained together, such as `a < b < c`.
+    ///
+    /// The message needs the source text of each part to suggest a fix, so only the
+    /// ranges are recorded here, and the text is filled in by the sink.
+    pub(crate) fn chained_comparison(
+        &mut self,
+        op: TextRange,
+        first: TextRange,
+        middle: TextRange,
+        rest: TextRange,
+    ) {
+        self.events.push(Event::ChainedComparison {
+            op,
+            first,
+            middle,
+            rest,
+        });
     }
 
     pub(crate) fn add_token(&mut self, kind: SyntaxKind, token_count: usize) {
         self.cursor += token_count;
+        self.expected.set(Set::EMPTY);
         self.events.push(Event::AddToken { kind, token_count })
     }
 }
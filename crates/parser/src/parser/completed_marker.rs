@@ -13,7 +13,7 @@ impl CompletedMarker {
 
     pub(crate) fn precede(self, p: &mut Parser) -> Marker {
         let new_pos = p.start();
-        let idx = self.pos as usize;
+        let idx = self.pos;
         match &mut p.events[idx] {
             Event::StartNode { forward_parent, .. } => {
                 *forward_parent = Some(new_pos.pos - self.pos);
@@ -25,7 +25,7 @@ impl CompletedMarker {
 
     pub(crate) fn extend_to(self, p: &mut Parser, mut m: Marker) -> CompletedMarker {
         m.bomb.defuse();
-        let idx = m.pos as usize;
+        let idx = m.pos;
         match &mut p.events[idx] {
             Event::StartNode { forward_parent, .. } => {
                 *forward_parent = Some(self.pos - m.pos);
@@ -20,7 +20,7 @@ impl Marker {
 
     pub(crate) fn complete(mut self, p: &mut Parser, kind: SyntaxKind) -> CompletedMarker {
         self.bomb.defuse();
-        let idx = self.pos as usize;
+        let idx = self.pos;
         match &mut p.events[idx] {
             Event::StartNode { kind: slot, .. } => {
                 *slot = kind;
@@ -33,7 +33,7 @@ impl Marker {
 
     pub(crate) fn abandon(mut self, p: &mut Parser) {
         self.bomb.defuse();
-        let idx = self.pos as usize;
+        let idx = self.pos;
         if idx == p.events.len() - 1 {
             match p.events.pop() {
                 Some(Event::StartNode {
@@ -0,0 +1,458 @@
+use lexer::{Lexer, Token, TokenKind};
+use rowan::{GreenToken, Language, NodeOrToken, TextRange, TextSize};
+use syntax::{RueLanguage, SyntaxKind};
+
+use crate::{parse, Parse, ParseError, Parser};
+
+/// A change to the source text, replacing a range with new text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub delete: TextRange,
+    pub insert: String,
+}
+
+impl TextEdit {
+    pub fn replace(delete: TextRange, insert: impl Into<String>) -> Self {
+        Self {
+            delete,
+            insert: insert.into(),
+        }
+    }
+
+    pub fn insert(offset: TextSize, insert: impl Into<String>) -> Self {
+        Self::replace(TextRange::empty(offset), insert)
+    }
+
+    pub fn delete(delete: TextRange) -> Self {
+        Self::replace(delete, "")
+    }
+
+    pub fn apply(&self, text: &mut String) {
+        text.replace_range(std::ops::Range::<usize>::from(self.delete), &self.insert);
+    }
+}
+
+impl Parse {
+    /// Applies an edit to the source text, reusing as much of the tree as possible.
+    ///
+    /// Edits inside of a single identifier, string, comment or whitespace token which don't
+    /// change its kind are patched into the tree directly. Otherwise, only the smallest block
+    /// or item surrounding the edit is relexed and reparsed, and spliced back into the tree in
+    /// place of the old one. If that isn't possible either, such as when the edit changes
+    /// which braces match or the node's end depends on what comes after it, the whole text is
+    /// parsed again.
+    pub fn reparse(&self, edit: &TextEdit) -> Parse {
+        if let Some(parse) = self.reparse_token(edit) {
+            return parse;
+        }
+
+        if let Some(parse) = self.reparse_node(edit) {
+            return parse;
+        }
+
+        let mut text = self.syntax_node().to_string();
+        edit.apply(&mut text);
+        parse(&text)
+    }
+
+    fn reparse_token(&self, edit: &TextEdit) -> Option<Parse> {
+        let root = self.syntax_node();
+
+        if edit.delete.end() > root.text_range().end() {
+            return None;
+        }
+
+        let token = root.covering_element(edit.delete).into_token()?;
+        let token_range = token.text_range();
+
+        // The parser doesn't look inside of these tokens, so the tree stays the same as long
+        // as their kind does. Number literals are excluded, since their digits are validated.
+        if !matches!(
+            token.kind(),
+            SyntaxKind::Ident
+                | SyntaxKind::String
+                | SyntaxKind::Whitespace
+                | SyntaxKind::LineComment
+                | SyntaxKind::BlockComment
+        ) {
+            return None;
+        }
+
+        if self.errors().iter().any(|error| {
+            error.span.start() < token_range.end() && error.span.end() > token_range.start()
+        }) {
+            return None;
+        }
+
+        let mut text = token.text().to_string();
+        TextEdit::replace(edit.delete - token_range.start(), edit.insert.clone()).apply(&mut text);
+
+        let before = token.prev_token().map(|token| token.text().to_string());
+        let after = token.next_token().map(|token| token.text().to_string());
+
+        let relex = Lexer::relex(
+            before.as_deref().unwrap_or_default(),
+            token.text(),
+            &text,
+            after.as_deref().unwrap_or_default(),
+        )?;
+
+        if relex.is_kind_changed {
+            return None;
+        }
+
+        let green_token = GreenToken::new(RueLanguage::kind_to_raw(token.kind()), &text);
+        let green_node = token.replace_with(green_token);
+
+        let errors = shift_errors(self.errors(), token_range, edit);
+
+        Some(Parse::new(green_node, errors))
+    }
+
+    fn reparse_node(&self, edit: &TextEdit) -> Option<Parse> {
+        let root = self.syntax_node();
+
+        if edit.delete.end() > root.text_range().end() {
+            return None;
+        }
+
+        let covering = match root.covering_element(edit.delete) {
+            NodeOrToken::Node(node) => node,
+            NodeOrToken::Token(token) => token.parent()?,
+        };
+
+        // Edits at the very end of the node could join up with the tokens after it.
+        let node = covering.ancestors().find(|node| {
+            matches!(node.kind(), SyntaxKind::Block | SyntaxKind::DefItem)
+                && edit.delete.start() > node.text_range().start()
+                && edit.delete.end() < node.text_range().end()
+        })?;
+
+        let node_range = node.text_range();
+
+        // Errors at the very edges of the node may depend on the parser state
+        // around it, so they can't be reproduced by parsing the node on its own.
+        if self.errors().iter().any(|error| {
+            error.span.start() <= node_range.start() + TextSize::from(1)
+                && error.span.end() >= node_range.start()
+                || error.span.start() <= node_range.end() && error.span.end() >= node_range.end()
+        }) {
+            return None;
+        }
+
+        let mut text = node.to_string();
+        TextEdit::replace(edit.delete - node_range.start(), edit.insert.clone()).apply(&mut text);
+
+        let tokens = Lexer::new(&text).collect::<Vec<_>>();
+
+        if !can_reparse(&tokens, node.kind()) {
+            return None;
+        }
+
+        let output = Parser::reparse_tokens(&tokens, node.kind())?;
+
+        // The end of the node is only the end of the file when parsing it on its own, so
+        // anything found there in context would be the next token instead.
+        if output
+            .errors
+            .iter()
+            .any(|error| error.kind.found() == Some(SyntaxKind::Eof))
+        {
+            return None;
+        }
+
+        let green_node = node.replace_with(output.green_node);
+
+        let mut errors = shift_errors(self.errors(), node_range, edit);
+
+        errors.extend(output.errors.into_iter().map(|error| ParseError {
+            span: error.span + node_range.start(),
+            ..error
+        }));
+        errors.sort_by_key(|error| error.span.start());
+
+        Some(Parse::new(green_node, errors))
+    }
+}
+
+/// Keeps the errors outside of the edited range, moving those after it to account for the edit.
+fn shift_errors(errors: &[ParseError], range: TextRange, edit: &TextEdit) -> Vec<ParseError> {
+    let delta = TextSize::of(&edit.insert).checked_sub(edit.delete.len());
+    let mut shifted = Vec::new();
+
+    for error in errors {
+        if error.span.end() <= range.start() {
+            shifted.push(error.clone());
+        } else if error.span.start() >= range.end() {
+            let span = match delta {
+                Some(delta) => error.span + delta,
+                None => error.span - (edit.delete.len() - TextSize::of(&edit.insert)),
+            };
+            shifted.push(ParseError {
+                kind: error.kind.clone(),
+                span,
+            });
+        }
+    }
+
+    shifted
+}
+
+/// Whether the tokens can be parsed on their own and produce the same tree as in context.
+fn can_reparse(tokens: &[Token], kind: SyntaxKind) -> bool {
+    let Some(first) = tokens.first() else {
+        return false;
+    };
+
+    let expected_first = match kind {
+        SyntaxKind::Block => TokenKind::OpenBrace,
+        SyntaxKind::DefItem => TokenKind::DefKw,
+        _ => return false,
+    };
+
+    // Unterminated tokens would have carried on past the end of the node.
+    let is_unterminated = tokens.iter().any(|token| {
+        matches!(
+            token.kind,
+            TokenKind::String {
+                is_terminated: false
+            } | TokenKind::BlockComment {
+                is_terminated: false
+            }
+        )
+    });
+
+    // So would a line comment at the very end, since there's no newline to stop it.
+    let ends_in_comment = tokens.last().map(|token| token.kind) == Some(TokenKind::LineComment);
+
+    first.kind == expected_first
+        && !is_unterminated
+        && !ends_in_comment
+        && is_balanced(tokens, kind)
+}
+
+/// Whether the delimiters are balanced and the last token is the closing brace.
+///
+/// Otherwise, error recovery could have skipped past the end of the node when parsing in context.
+/// The opening brace of a block must also be the one closed by its last token.
+fn is_balanced(tokens: &[Token], kind: SyntaxKind) -> bool {
+    let tokens = tokens
+        .iter()
+        .filter(|token| !token.kind.is_trivia())
+        .collect::<Vec<_>>();
+    let mut depth = 0usize;
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::OpenBrace | TokenKind::OpenParen => depth += 1,
+            TokenKind::CloseBrace | TokenKind::CloseParen => {
+                let Some(new_depth) = depth.checked_sub(1) else {
+                    return false;
+                };
+                depth = new_depth;
+
+                if depth == 0 && kind == SyntaxKind::Block && i != tokens.len() - 1 {
+                    return false;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    depth == 0 && tokens.last().map(|token| token.kind) == Some(TokenKind::CloseBrace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str =
+        "def main(x: Int) {\n    let y = x + 1;\n}\n\ndef other() {\n    let z = 2;\n}\n";
+
+    fn check(source: &str, edit: TextEdit, is_incremental: bool) {
+        let old = parse(source);
+
+        let mut text = source.to_string();
+        edit.apply(&mut text);
+        let full = parse(&text);
+
+        assert_eq!(old.reparse_node(&edit).is_some(), is_incremental);
+
+        let incremental = old.reparse(&edit);
+        assert_eq!(incremental.debug_tree(), full.debug_tree());
+        assert_eq!(incremental.errors(), full.errors());
+        assert_eq!(incremental, full);
+    }
+
+    fn check_token(source: &str, edit: TextEdit, is_patched: bool) {
+        let old = parse(source);
+
+        let mut text = source.to_string();
+        edit.apply(&mut text);
+        let full = parse(&text);
+
+        assert_eq!(old.reparse_token(&edit).is_some(), is_patched);
+        assert_eq!(old.reparse(&edit), full);
+    }
+
+    fn range(start: u32, end: u32) -> TextRange {
+        TextRange::new(start.into(), end.into())
+    }
+
+    #[test]
+    fn reparse_block() {
+        check(SOURCE, TextEdit::replace(range(35, 36), "42"), true);
+    }
+
+    #[test]
+    fn reparse_block_with_new_error() {
+        check(SOURCE, TextEdit::delete(range(36, 37)), true);
+    }
+
+    #[test]
+    fn reparse_second_item_with_errors_after() {
+        let source = "def main() {\n    let a = 1;\n}\ndef f( {}\n";
+        check(source, TextEdit::insert(TextSize::from(25), " + 2"), true);
+    }
+
+    #[test]
+    fn reparse_params() {
+        check(SOURCE, TextEdit::replace(range(9, 10), "value"), true);
+    }
+
+    #[test]
+    fn unbalanced_braces_fall_back() {
+        check(SOURCE, TextEdit::insert(TextSize::from(35), "{"), false);
+        check(SOURCE, TextEdit::delete(range(38, 39)), false);
+    }
+
+    #[test]
+    fn unterminated_string_falls_back() {
+        check(SOURCE, TextEdit::replace(range(35, 36), "'oops"), false);
+    }
+
+    #[test]
+    fn edit_between_items_falls_back() {
+        check(
+            SOURCE,
+            TextEdit::insert(TextSize::from(41), "def f() {}"),
+            false,
+        );
+    }
+
+    #[test]
+    fn comment_at_end_falls_back() {
+        let source = "def main(x: Int) -> Int {\n    let y = x + 1;\n    let z = 'hi'; // c\n    \
+                      y < z\n}\n\ndef other(a: Int, b: Bool) {\n    let z = a >> 2;\n}\n";
+        check(source, TextEdit::replace(range(79, 81), "//"), false);
+
+        let source = "def f() { let x = ; }\ndef g( {}\nconst A: Int = 1 << 2;\n";
+        check(source, TextEdit::replace(range(21, 22), "//"), false);
+    }
+
+    #[test]
+    fn end_of_file_error_falls_back() {
+        let source = "def main(x: Int) -> Int {\n    y < z\n}\n\ndef other() {}\n";
+        check(source, TextEdit::replace(range(13, 14), "|"), false);
+    }
+
+    #[test]
+    fn patch_ident() {
+        check_token(SOURCE, TextEdit::insert(TextSize::from(28), "es"), true);
+        check_token(SOURCE, TextEdit::replace(range(4, 8), "start"), true);
+    }
+
+    #[test]
+    fn patch_string_and_comment() {
+        let source = "def main() {\n    // say hi\n    let x = 'hi';\n}\n";
+        check_token(source, TextEdit::insert(TextSize::from(25), " there"), true);
+        check_token(source, TextEdit::insert(TextSize::from(41), " there"), true);
+    }
+
+    #[test]
+    fn patch_whitespace_with_errors_after() {
+        let source = "def main() {\n    let x = ;\n}\n";
+        check_token(source, TextEdit::insert(TextSize::from(13), "  "), true);
+    }
+
+    #[test]
+    fn kind_change_falls_back() {
+        check_token(SOURCE, TextEdit::replace(range(27, 28), "let"), false);
+        check_token(SOURCE, TextEdit::insert(TextSize::from(28), "'"), false);
+        check_token(SOURCE, TextEdit::replace(range(27, 28), "1"), false);
+    }
+
+    #[test]
+    fn merging_tokens_falls_back() {
+        check_token(SOURCE, TextEdit::delete(range(30, 31)), false);
+        check_token(SOURCE, TextEdit::replace(range(28, 29), "+"), false);
+    }
+
+    /// A small xorshift generator, so that the random edits are the same on every run.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    #[test]
+    fn random_edits_match_full_parse() {
+        let sources = [
+            "def main(x: Int) -> Int {\n    let y = x + 1;\n    let z = 'hi'; // c\n    y < z\n}\n\n\
+             def other(a: Int, b: Bool) {\n    let z = a >> 2;\n}\n",
+            "def f() { let x = ; }\ndef g( {}\nconst A: Int = 1 << 2;\n",
+            "def main() {\n    /* block */ let a = f(1, 2) && true;\n}\n// end\n",
+        ];
+        let inserts = [
+            "",
+            "//",
+            "/*",
+            "*/",
+            "{",
+            "}",
+            "(",
+            ")",
+            ";",
+            "'",
+            "\n",
+            " ",
+            "|",
+            "&",
+            ">",
+            "=",
+            "def",
+            "let",
+            "x",
+            "1",
+            "def f() {}",
+        ];
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..2_000 {
+            let source = sources[rng.below(sources.len())];
+            let start = rng.below(source.len() + 1);
+            let end = start + rng.below((source.len() - start).min(4) + 1);
+            let edit = TextEdit::replace(
+                range(start as u32, end as u32),
+                inserts[rng.below(inserts.len())],
+            );
+
+            let mut text = source.to_string();
+            edit.apply(&mut text);
+
+            let incremental = parse(source).reparse(&edit);
+            let full = parse(&text);
+            assert_eq!(
+                incremental.errors(),
+                full.errors(),
+                "{edit:?} on {source:?}"
+            );
+            assert_eq!(incremental, full, "{edit:?} on {source:?}");
+        }
+    }
+}
@@ -4,7 +4,7 @@ use lexer::{Base, Token, TokenKind};
 use rowan::{GreenNodeBuilder, Language, TextRange, TextSize};
 use syntax::{RueLanguage, SyntaxKind};
 
-use crate::{event::Event, output::Output, parse_error::ParseError};
+use crate::{event::Event, output::Output, ParseError, ParseErrorKind};
 
 pub struct Sink<'a, 't> {
     events: Vec<Event>,
@@ -38,7 +38,7 @@ impl<'a, 't> Sink<'a, 't> {
                     let mut idx = i;
                     let mut fp = forward_parent;
                     while let Some(fwd) = fp {
-                        idx += fwd as usize;
+                        idx += fwd;
                         fp = match std::mem::replace(&mut self.events[idx], Event::tombstone()) {
                             Event::StartNode {
                                 kind,
@@ -59,21 +59,31 @@ impl<'a, 't> Sink<'a, 't> {
                 }
                 Event::FinishNode => self.builder.finish_node(),
                 Event::AddToken { kind, token_count } => self.token(kind, token_count),
-                Event::Error(message) => {
-                    let span = match self.tokens.get(self.cursor) {
-                        Some(token) => TextRange::new(
-                            TextSize::from(token.span.start as u32),
-                            TextSize::from(token.span.end as u32),
-                        ),
-                        None => TextRange::default(),
-                    };
-                    self.errors.push(ParseError { message, span });
+                Event::Error { kind, span } => self.errors.push(ParseError { kind, span }),
+                Event::ChainedComparison {
+                    op,
+                    first,
+                    middle,
+                    rest,
+                } => {
+                    let suggestion = format!(
+                        "{} && {} {}",
+                        self.text(first),
+                        self.text(middle),
+                        self.text(rest)
+                    );
+                    self.errors.push(ParseError {
+                        kind: ParseErrorKind::ChainedComparison { suggestion },
+                        span: op,
+                    });
                 }
             }
 
             self.eat_trivia();
         }
 
+        self.errors.sort_by_key(|error| error.span.start());
+
         Output {
             green_node: self.builder.finish(),
             errors: self.errors,
@@ -90,6 +100,15 @@ impl<'a, 't> Sink<'a, 't> {
         }
     }
 
+    /// The source text of a range, which must start and end on token boundaries.
+    fn text(&self, range: TextRange) -> String {
+        self.tokens
+            .iter()
+            .filter(|token| range.contains_range(text_range(&token.span)))
+            .map(|token| token.text)
+            .collect()
+    }
+
     fn token(&mut self, kind: SyntaxKind, token_count: usize) {
         let mut text = String::new();
         let tokens = &self.tokens[self.cursor..self.cursor + token_count];
@@ -103,30 +122,56 @@ impl<'a, 't> Sink<'a, 't> {
         self.cursor += token_count;
     }
 
+    /// Reports the suffix of a number if it isn't one of the valid ones, and returns whether it
+    /// was valid.
+    fn check_suffix(&mut self, token: &Token, valid: &[&str]) -> bool {
+        let Some(suffix_start) = token.kind.suffix_start() else {
+            return true;
+        };
+
+        let suffix = &token.text[suffix_start..];
+
+        if valid.contains(&suffix) {
+            return true;
+        }
+
+        self.errors.push(ParseError {
+            kind: ParseErrorKind::InvalidSuffix {
+                suffix: suffix.to_string(),
+            },
+            span: text_range(&(token.span.start + suffix_start..token.span.end)),
+        });
+        false
+    }
+
     fn handle_errors(&mut self, token: &Token) {
         match token.kind {
-            TokenKind::String { is_terminated } => {
-                if !is_terminated {
-                    self.errors.push(ParseError {
-                        message: "Unterminated string literal".into(),
-                        span: text_range(&token.span),
-                    });
-                }
+            TokenKind::String { is_terminated } if !is_terminated => {
+                self.errors.push(ParseError {
+                    kind: ParseErrorKind::UnterminatedString,
+                    span: text_range(&token.span),
+                });
             }
-            TokenKind::Integer { base, is_empty } => {
+            TokenKind::Integer {
+                base,
+                is_empty,
+                suffix_start,
+            } => {
+                let digits_end = suffix_start.map_or(token.text.len(), |start| start as usize);
+
                 if is_empty {
                     self.errors.push(ParseError {
-                        message: "Expected digits after the integer base prefix".into(),
+                        kind: ParseErrorKind::MissingDigits,
                         span: text_range(&token.span),
                     });
                 } else if matches!(base, Base::Binary | Base::Octal) {
                     let start = token.span.start + 2;
-                    let digits = &token.text[2..];
+                    let digits = &token.text[2..digits_end];
 
                     for (index, c) in digits.char_indices() {
                         if c != '_' && c.to_digit(base as u32).is_none() {
                             self.errors.push(ParseError {
-                                message: format!("Invalid base {} digit", base as u32),
+                                kind: ParseErrorKind::InvalidDigit { base },
                                 span: TextRange::at(
                                     TextSize::from(start as u32 + index as u32),
                                     TextSize::from(1),
@@ -135,12 +180,38 @@ impl<'a, 't> Sink<'a, 't> {
                         }
                     }
                 }
+
+                self.check_suffix(token, INTEGER_SUFFIXES);
+            }
+            TokenKind::Float {
+                is_empty_exponent, ..
+            } => {
+                // Floats can't be used yet, but that's only worth pointing out once the literal
+                // is written correctly.
+                let kind = if is_empty_exponent {
+                    ParseErrorKind::EmptyExponent
+                } else if self.check_suffix(token, FLOAT_SUFFIXES) {
+                    ParseErrorKind::UnsupportedFloat
+                } else {
+                    return;
+                };
+
+                self.errors.push(ParseError {
+                    kind,
+                    span: text_range(&token.span),
+                });
             }
             _ => {}
         };
     }
 }
 
+const INTEGER_SUFFIXES: &[&str] = &[
+    "i8", "i16", "i32", "i64", "i128", "u8", "u16", "u32", "u64", "u128",
+];
+
+const FLOAT_SUFFIXES: &[&str] = &["f32", "f64"];
+
 fn text_range(span: &Range<usize>) -> TextRange {
     TextRange::new(
         TextSize::from(span.start as u32),
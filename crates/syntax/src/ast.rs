@@ -0,0 +1,280 @@
+//! Typed wrappers around the untyped syntax tree.
+//!
+//! Every accessor returns an `Option`, since the tree is built from source code
+//! which may be missing any part of the syntax after error recovery.
+
+use crate::{SyntaxKind, SyntaxNode, SyntaxToken};
+
+pub trait AstNode: Sized {
+    fn can_cast(kind: SyntaxKind) -> bool;
+    fn cast(node: SyntaxNode) -> Option<Self>;
+    fn syntax(&self) -> &SyntaxNode;
+}
+
+macro_rules! ast_node {
+    ($name:ident) => {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct $name(SyntaxNode);
+
+        impl AstNode for $name {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                kind == SyntaxKind::$name
+            }
+
+            fn cast(node: SyntaxNode) -> Option<Self> {
+                Self::can_cast(node.kind()).then(|| Self(node))
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                &self.0
+            }
+        }
+    };
+}
+
+macro_rules! ast_enum {
+    ($name:ident { $( $variant:ident ),+ $(,)? }) => {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $( $variant($variant), )+
+        }
+
+        impl AstNode for $name {
+            fn can_cast(kind: SyntaxKind) -> bool {
+                matches!(kind, $( SyntaxKind::$variant )|+)
+            }
+
+            fn cast(node: SyntaxNode) -> Option<Self> {
+                match node.kind() {
+                    $( SyntaxKind::$variant => Some(Self::$variant($variant(node))), )+
+                    _ => None,
+                }
+            }
+
+            fn syntax(&self) -> &SyntaxNode {
+                match self {
+                    $( Self::$variant(node) => node.syntax(), )+
+                }
+            }
+        }
+    };
+}
+
+ast_node!(Root);
+ast_node!(DefItem);
+ast_node!(ConstItem);
+ast_node!(ParamList);
+ast_node!(Param);
+ast_node!(Block);
+ast_node!(LetStmt);
+ast_node!(ExprStmt);
+ast_node!(Literal);
+ast_node!(NameRef);
+ast_node!(ParenExpr);
+ast_node!(BinaryExpr);
+ast_node!(PrefixExpr);
+ast_node!(CallExpr);
+ast_node!(ArgList);
+ast_node!(FieldExpr);
+ast_node!(TryExpr);
+ast_node!(NameType);
+
+ast_enum!(Item { DefItem, ConstItem });
+ast_enum!(Stmt { LetStmt, ExprStmt });
+ast_enum!(Type { NameType });
+ast_enum!(Expr {
+    Literal,
+    NameRef,
+    ParenExpr,
+    BinaryExpr,
+    PrefixExpr,
+    CallExpr,
+    FieldExpr,
+    TryExpr,
+});
+
+fn child<N: AstNode>(node: &SyntaxNode) -> Option<N> {
+    node.children().find_map(N::cast)
+}
+
+fn children<N: AstNode>(node: &SyntaxNode) -> impl Iterator<Item = N> {
+    node.children().filter_map(N::cast)
+}
+
+fn token(node: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+    node.children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| token.kind() == kind)
+}
+
+/// The first direct token which isn't trivia, such as the operator of a binary expression.
+fn op_token(node: &SyntaxNode) -> Option<SyntaxToken> {
+    node.children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| !token.kind().is_trivia())
+}
+
+impl Root {
+    pub fn items(&self) -> impl Iterator<Item = Item> {
+        children(&self.0)
+    }
+}
+
+impl DefItem {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+
+    pub fn param_list(&self) -> Option<ParamList> {
+        child(&self.0)
+    }
+
+    pub fn return_type(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    pub fn body(&self) -> Option<Block> {
+        child(&self.0)
+    }
+}
+
+impl ConstItem {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl ParamList {
+    pub fn params(&self) -> impl Iterator<Item = Param> {
+        children(&self.0)
+    }
+}
+
+impl Param {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+}
+
+impl Block {
+    pub fn stmts(&self) -> impl Iterator<Item = Stmt> {
+        children(&self.0)
+    }
+
+    /// The expression at the end of the block without a semicolon, which is its value.
+    pub fn tail_expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl LetStmt {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+
+    pub fn ty(&self) -> Option<Type> {
+        child(&self.0)
+    }
+
+    pub fn value(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl ExprStmt {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl Literal {
+    pub fn token(&self) -> Option<SyntaxToken> {
+        op_token(&self.0)
+    }
+}
+
+impl NameRef {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+}
+
+impl ParenExpr {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl BinaryExpr {
+    pub fn lhs(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn rhs(&self) -> Option<Expr> {
+        children(&self.0).nth(1)
+    }
+
+    pub fn op(&self) -> Option<SyntaxToken> {
+        op_token(&self.0)
+    }
+}
+
+impl PrefixExpr {
+    pub fn op(&self) -> Option<SyntaxToken> {
+        op_token(&self.0)
+    }
+
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl CallExpr {
+    pub fn callee(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn arg_list(&self) -> Option<ArgList> {
+        child(&self.0)
+    }
+}
+
+impl ArgList {
+    pub fn args(&self) -> impl Iterator<Item = Expr> {
+        children(&self.0)
+    }
+}
+
+impl FieldExpr {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+}
+
+impl TryExpr {
+    pub fn expr(&self) -> Option<Expr> {
+        child(&self.0)
+    }
+}
+
+impl NameType {
+    pub fn name(&self) -> Option<SyntaxToken> {
+        token(&self.0, SyntaxKind::Ident)
+    }
+}
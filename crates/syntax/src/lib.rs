@@ -1,9 +1,12 @@
+pub mod ast;
 mod language;
+mod line_index;
 mod set;
 mod syntax_kind;
 mod wrappers;
 
 pub use language::*;
+pub use line_index::*;
 pub use set::*;
 pub use syntax_kind::*;
 pub use wrappers::*;
@@ -0,0 +1,215 @@
+use rowan::{TextRange, TextSize};
+
+/// Converts between byte offsets and line/column positions in a source file.
+///
+/// Lines and columns are zero based. Columns are measured in UTF-8 bytes,
+/// and can be converted to and from UTF-16 code units for editors which need them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    len: TextSize,
+    line_starts: Vec<TextSize>,
+    wide_chars: Vec<Vec<WideChar>>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineCol {
+    pub line: u32,
+    pub col: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WideLineCol {
+    pub line: u32,
+    pub col: u32,
+}
+
+/// A character which takes up more than one byte in UTF-8.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct WideChar {
+    start: TextSize,
+    end: TextSize,
+}
+
+impl WideChar {
+    fn len(&self) -> TextSize {
+        self.end - self.start
+    }
+
+    fn wide_len(&self) -> TextSize {
+        let len = u32::from(self.len());
+        TextSize::from(if len == 4 { 2 } else { 1 })
+    }
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut line_starts = vec![TextSize::from(0)];
+        let mut wide_chars = vec![Vec::new()];
+        let mut line_start = TextSize::from(0);
+
+        for (offset, c) in text.char_indices() {
+            let offset = TextSize::from(offset as u32);
+            let len = TextSize::of(c);
+
+            if c == '\n' {
+                line_start = offset + len;
+                line_starts.push(line_start);
+                wide_chars.push(Vec::new());
+            } else if !c.is_ascii() {
+                let start = offset - line_start;
+                wide_chars.last_mut().unwrap().push(WideChar {
+                    start,
+                    end: start + len,
+                });
+            }
+        }
+
+        Self {
+            len: TextSize::of(text),
+            line_starts,
+            wide_chars,
+        }
+    }
+
+    pub fn len(&self) -> TextSize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == TextSize::from(0)
+    }
+
+    pub fn line_count(&self) -> u32 {
+        self.line_starts.len() as u32
+    }
+
+    /// The range of a line, excluding its trailing newline.
+    pub fn line_range(&self, line: u32) -> Option<TextRange> {
+        let start = *self.line_starts.get(line as usize)?;
+        let end = match self.line_starts.get(line as usize + 1) {
+            Some(next) => *next - TextSize::from(1),
+            None => self.len,
+        };
+        Some(TextRange::new(start, end))
+    }
+
+    /// Finds the line and UTF-8 column of an offset.
+    ///
+    /// Offsets past the end of the text are clamped to the end.
+    pub fn line_col(&self, offset: TextSize) -> LineCol {
+        let offset = offset.min(self.len);
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+
+        LineCol {
+            line: line as u32,
+            col: (offset - self.line_starts[line]).into(),
+        }
+    }
+
+    /// Finds the offset of a line and UTF-8 column, if it is within the text.
+    pub fn offset(&self, line_col: LineCol) -> Option<TextSize> {
+        let range = self.line_range(line_col.line)?;
+        let offset = range.start() + TextSize::from(line_col.col);
+
+        if offset > range.end() {
+            return None;
+        }
+
+        Some(offset)
+    }
+
+    pub fn to_wide(&self, line_col: LineCol) -> WideLineCol {
+        let mut col = TextSize::from(line_col.col);
+
+        if let Some(wide_chars) = self.wide_chars.get(line_col.line as usize) {
+            for c in wide_chars {
+                if c.end <= TextSize::from(line_col.col) {
+                    col = col - c.len() + c.wide_len();
+                } else {
+                    break;
+                }
+            }
+        }
+
+        WideLineCol {
+            line: line_col.line,
+            col: col.into(),
+        }
+    }
+
+    pub fn to_utf8(&self, wide: WideLineCol) -> LineCol {
+        let mut col = TextSize::from(wide.col);
+
+        if let Some(wide_chars) = self.wide_chars.get(wide.line as usize) {
+            for c in wide_chars {
+                if col > c.start {
+                    col = col - c.wide_len() + c.len();
+                } else {
+                    break;
+                }
+            }
+        }
+
+        LineCol {
+            line: wide.line,
+            col: col.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_col_roundtrip() {
+        let text = "def main() {\n    let x = 5;\n}\n";
+        let index = LineIndex::new(text);
+
+        assert_eq!(index.line_count(), 4);
+
+        for offset in 0..=text.len() as u32 {
+            let offset = TextSize::from(offset);
+            let line_col = index.line_col(offset);
+            assert_eq!(index.offset(line_col), Some(offset));
+        }
+
+        assert_eq!(
+            index.line_col(TextSize::from(17)),
+            LineCol { line: 1, col: 4 }
+        );
+        assert_eq!(index.offset(LineCol { line: 1, col: 40 }), None);
+    }
+
+    #[test]
+    fn line_range() {
+        let index = LineIndex::new("ab\ncd");
+
+        assert_eq!(
+            index.line_range(0),
+            Some(TextRange::new(0.into(), 2.into()))
+        );
+        assert_eq!(
+            index.line_range(1),
+            Some(TextRange::new(3.into(), 5.into()))
+        );
+        assert_eq!(index.line_range(2), None);
+    }
+
+    #[test]
+    fn wide_columns() {
+        let index = LineIndex::new("let é = '𝕏';");
+
+        let line_col = index.line_col(TextSize::from(7));
+        assert_eq!(line_col, LineCol { line: 0, col: 7 });
+        assert_eq!(index.to_wide(line_col), WideLineCol { line: 0, col: 6 });
+
+        let line_col = index.line_col(TextSize::from(15));
+        assert_eq!(index.to_wide(line_col), WideLineCol { line: 0, col: 12 });
+
+        for col in [0, 4, 6, 9, 11, 12, 13] {
+            let wide = WideLineCol { line: 0, col };
+            assert_eq!(index.to_wide(index.to_utf8(wide)), wide);
+        }
+    }
+}
@@ -1,8 +1,10 @@
+use num_traits::FromPrimitive;
+
 use crate::SyntaxKind;
 
 pub const EMPTY_SET: Set = Set::new(&[]);
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct Set(u128);
 
 impl Set {
@@ -24,9 +26,23 @@ impl Set {
         Set(self.0 | other.0)
     }
 
+    pub const fn with(self, kind: SyntaxKind) -> Set {
+        Set(self.0 | mask(kind))
+    }
+
     pub const fn contains(&self, kind: SyntaxKind) -> bool {
         self.0 & mask(kind) != 0
     }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = SyntaxKind> {
+        (0..128u8)
+            .filter(move |&i| self.0 & (1u128 << i) != 0)
+            .filter_map(SyntaxKind::from_u8)
+    }
 }
 
 const fn mask(kind: SyntaxKind) -> u128 {
@@ -44,4 +60,13 @@ mod tests {
         assert!(ts.contains(SyntaxKind::Colon));
         assert!(!ts.contains(SyntaxKind::Plus));
     }
+
+    #[test]
+    fn token_set_iter() {
+        let ts = Set::new(&[SyntaxKind::Eof, SyntaxKind::Colon]).with(SyntaxKind::Plus);
+        assert_eq!(
+            ts.iter().collect::<Vec<_>>(),
+            &[SyntaxKind::Plus, SyntaxKind::Colon, SyntaxKind::Eof]
+        );
+    }
 }
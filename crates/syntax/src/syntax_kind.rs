@@ -1,4 +1,4 @@
-use lexer::TokenKind;
+use lexer::{Glued, TokenKind};
 use num_derive::{FromPrimitive, ToPrimitive};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ToPrimitive, FromPrimitive)]
@@ -6,7 +6,9 @@ pub enum SyntaxKind {
     Ident,
     String,
     Integer,
+    Float,
     DefKw,
+    ConstKw,
     LetKw,
     TrueKw,
     FalseKw,
@@ -31,6 +33,8 @@ pub enum SyntaxKind {
     Dot,
     Equals,
     Exclamation,
+    Tilde,
+    Question,
     Whitespace,
     LineComment,
     BlockComment,
@@ -50,30 +54,125 @@ pub enum SyntaxKind {
     UnsignedRightShift,
 
     Root,
+    Missing,
 
     Literal,
     NameRef,
     ParenExpr,
     BinaryExpr,
     PrefixExpr,
+    CallExpr,
+    ArgList,
+    FieldExpr,
+    TryExpr,
 
     DefItem,
+    ConstItem,
     ParamList,
     Param,
     Block,
 
     LetStmt,
+    ExprStmt,
 
     NameType,
 }
 
+impl SyntaxKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            Self::Whitespace | Self::LineComment | Self::BlockComment
+        )
+    }
+
+    /// The glued operator this kind is made up of, if it's one.
+    pub fn glued(self) -> Option<Glued> {
+        Glued::ALL
+            .into_iter()
+            .find(|&glued| Self::from(glued) == self)
+    }
+
+    /// A human readable name for the kind, used when reporting what was expected or found.
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Ident => "identifier",
+            Self::String => "string literal",
+            Self::Integer => "integer literal",
+            Self::Float => "float literal",
+            Self::DefKw => "`def`",
+            Self::ConstKw => "`const`",
+            Self::LetKw => "`let`",
+            Self::TrueKw => "`true`",
+            Self::FalseKw => "`false`",
+            Self::OpenParen => "`(`",
+            Self::CloseParen => "`)`",
+            Self::OpenBrace => "`{`",
+            Self::CloseBrace => "`}`",
+            Self::LessThan => "`<`",
+            Self::GreaterThan => "`>`",
+            Self::Plus => "`+`",
+            Self::Minus => "`-`",
+            Self::Star => "`*`",
+            Self::Slash => "`/`",
+            Self::Percent => "`%`",
+            Self::And => "`&`",
+            Self::Or => "`|`",
+            Self::Xor => "`^`",
+            Self::Arrow => "`->`",
+            Self::Colon => "`:`",
+            Self::Semicolon => "`;`",
+            Self::Comma => "`,`",
+            Self::Dot => "`.`",
+            Self::Equals => "`=`",
+            Self::Exclamation => "`!`",
+            Self::Tilde => "`~`",
+            Self::Question => "`?`",
+            Self::Whitespace => "whitespace",
+            Self::LineComment | Self::BlockComment => "comment",
+            Self::Error => "unknown token",
+            Self::Eof => "end of file",
+            Self::LazyAnd => "`&&`",
+            Self::LazyOr => "`||`",
+            Self::EqualTo => "`==`",
+            Self::NotEqual => "`!=`",
+            Self::LessThanEquals => "`<=`",
+            Self::GreaterThanEquals => "`>=`",
+            Self::LeftShift => "`<<`",
+            Self::RightShift => "`>>`",
+            Self::UnsignedRightShift => "`>>>`",
+            Self::Literal => "literal",
+            Self::NameRef => "name",
+            Self::ParenExpr
+            | Self::BinaryExpr
+            | Self::PrefixExpr
+            | Self::CallExpr
+            | Self::FieldExpr
+            | Self::TryExpr => "expression",
+            Self::ArgList => "argument list",
+            Self::DefItem => "function",
+            Self::ConstItem => "constant",
+            Self::ParamList => "parameter list",
+            Self::Param => "parameter",
+            Self::Block => "block",
+            Self::LetStmt => "let statement",
+            Self::ExprStmt => "expression statement",
+            Self::NameType => "type",
+            Self::Missing => "missing token",
+            Self::Tombstone | Self::Root => "syntax node",
+        }
+    }
+}
+
 impl From<TokenKind> for SyntaxKind {
     fn from(value: TokenKind) -> Self {
         match value {
-            TokenKind::Ident { .. } => Self::Ident,
+            TokenKind::Ident => Self::Ident,
             TokenKind::String { .. } => Self::String,
             TokenKind::Integer { .. } => Self::Integer,
+            TokenKind::Float { .. } => Self::Float,
             TokenKind::DefKw => Self::DefKw,
+            TokenKind::ConstKw => Self::ConstKw,
             TokenKind::LetKw => Self::LetKw,
             TokenKind::TrueKw => Self::TrueKw,
             TokenKind::FalseKw => Self::FalseKw,
@@ -98,6 +197,8 @@ impl From<TokenKind> for SyntaxKind {
             TokenKind::Dot => Self::Dot,
             TokenKind::Equals => Self::Equals,
             TokenKind::Exclamation => Self::Exclamation,
+            TokenKind::Tilde => Self::Tilde,
+            TokenKind::Question => Self::Question,
             TokenKind::Whitespace => Self::Whitespace,
             TokenKind::LineComment => Self::LineComment,
             TokenKind::BlockComment { .. } => Self::BlockComment,
@@ -106,9 +207,26 @@ impl From<TokenKind> for SyntaxKind {
     }
 }
 
+impl From<Glued> for SyntaxKind {
+    fn from(value: Glued) -> Self {
+        match value {
+            Glued::LazyAnd => Self::LazyAnd,
+            Glued::LazyOr => Self::LazyOr,
+            Glued::EqualTo => Self::EqualTo,
+            Glued::NotEqual => Self::NotEqual,
+            Glued::LessThanEquals => Self::LessThanEquals,
+            Glued::GreaterThanEquals => Self::GreaterThanEquals,
+            Glued::LeftShift => Self::LeftShift,
+            Glued::RightShift => Self::RightShift,
+            Glued::UnsignedRightShift => Self::UnsignedRightShift,
+        }
+    }
+}
+
 #[macro_export]
 macro_rules ! T {
     [def] => { SyntaxKind::DefKw };
+    [const] => { SyntaxKind::ConstKw };
     [let] => { SyntaxKind::LetKw };
     [true] => { SyntaxKind::TrueKw };
     [false] => { SyntaxKind::FalseKw };
@@ -133,6 +251,8 @@ macro_rules ! T {
     [.] => { SyntaxKind::Dot };
     [=] => { SyntaxKind::Equals };
     [!] => { SyntaxKind::Exclamation };
+    [~] => { SyntaxKind::Tilde };
+    [?] => { SyntaxKind::Question };
     [&&] => { SyntaxKind::LazyAnd };
     [||] => { SyntaxKind::LazyOr };
     [==] => { SyntaxKind::EqualTo };
@@ -143,5 +263,3 @@ macro_rules ! T {
     [>>] => { SyntaxKind::RightShift };
     [>>>] => { SyntaxKind::UnsignedRightShift };
 }
-
-pub use T;
@@ -1,3 +1,6 @@
 use crate::RueLanguage;
 
 pub type SyntaxNode = rowan::SyntaxNode<RueLanguage>;
+pub type SyntaxToken = rowan::SyntaxToken<RueLanguage>;
+pub type SyntaxElement = rowan::SyntaxElement<RueLanguage>;
+pub type SyntaxNodePtr = rowan::ast::SyntaxNodePtr<RueLanguage>;
@@ -0,0 +1,75 @@
+use std::{env, fs, process::ExitCode};
+
+use diagnostics::{render, Severity, SourceFile};
+use hir::Analysis;
+
+/// The longest atom the cost estimate allows for, which fits a hash or a large amount.
+const MAX_ATOM_BYTES: usize = 32;
+
+/// Compiles a file to CLVM, and prints the serialized program as hex, or as Chialisp with
+/// `--chialisp`. With `--cost`, the worst case cost of the program and each function is
+/// printed too.
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let chialisp = args.iter().any(|arg| arg == "--chialisp");
+    let cost = args.iter().any(|arg| arg == "--cost");
+
+    let Some(path) = args.iter().find(|arg| !arg.starts_with("--")) else {
+        eprintln!("usage: compile [--chialisp] [--cost] <file>");
+        return ExitCode::FAILURE;
+    };
+
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("could not read `{path}`: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let file = SourceFile::new(path, &source);
+    let parse = parser::parse(&source);
+    let analysis = Analysis::new(&parse.tree());
+
+    let diagnostics = parse
+        .errors()
+        .iter()
+        .map(|error| error.to_diagnostic())
+        .chain(analysis.diagnostics())
+        .collect::<Vec<_>>();
+
+    for diagnostic in &diagnostics {
+        eprint!("{}", render(diagnostic, &file));
+    }
+
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        return ExitCode::FAILURE;
+    }
+
+    match codegen::compile(&analysis) {
+        Ok(program) => {
+            if chialisp {
+                print!("{}", program.to_chialisp(true));
+            } else {
+                println!("{}", program.to_hex());
+            }
+
+            if cost {
+                println!();
+                print!("{}", program.estimate_cost(MAX_ATOM_BYTES));
+            }
+
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            match error.to_diagnostic() {
+                Some(diagnostic) => eprint!("{}", render(&diagnostic, &file)),
+                None => eprintln!("error: {error}"),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,9 +1,13 @@
-use lexer::Lexer;
-use parser::Parser;
+use diagnostics::{render, SourceFile};
 
 fn main() {
     let source = include_str!("hello_world.rue");
-    let tokens = Lexer::new(source).collect::<Vec<_>>();
-    let output = Parser::parse_tokens(&tokens);
-    println!("{}", output.debug_tree());
+    let parse = parser::parse(source);
+    println!("{}", parse.debug_tree());
+
+    let file = SourceFile::new("hello_world.rue", source);
+
+    for error in parse.errors() {
+        eprint!("{}", render(&error.to_diagnostic(), &file));
+    }
 }
@@ -1,4 +1,5 @@
-use lexer::Lexer;
+use diagnostics::{render, SourceFile};
+use lexer::{Lexer, TokenKind};
 use parser::Parser;
 use std::io::{self, Write};
 
@@ -12,15 +13,25 @@ fn main() -> io::Result<()> {
         write!(stdout, "→ ")?;
         stdout.flush()?;
 
-        stdin.read_line(&mut input)?;
+        if stdin.read_line(&mut input)? == 0 {
+            return Ok(());
+        }
+
+        let source = input.trim();
+        let tokens = Lexer::new(source).collect::<Vec<_>>();
 
-        let tokens = Lexer::new(&input.trim()).collect::<Vec<_>>();
-        let output = Parser::parse_tokens(&tokens);
+        let first = tokens.iter().find(|token| !token.kind.is_trivia());
+        let output = match first.map(|token| token.kind) {
+            Some(TokenKind::DefKw | TokenKind::ConstKw) => Parser::parse_item(&tokens),
+            Some(TokenKind::LetKw) => Parser::parse_stmt(&tokens),
+            _ => Parser::parse_expr(&tokens),
+        };
+        let file = SourceFile::new("<repl>", source);
 
         println!("{}", output.debug_tree());
 
         for error in output.errors {
-            println!("{}", error);
+            print!("{}", render(&error.to_diagnostic(), &file));
         }
 
         input.clear();
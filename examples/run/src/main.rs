@@ -0,0 +1,75 @@
+use std::{env, fs, process::ExitCode};
+
+use diagnostics::{render, Severity, SourceFile};
+use hir::Analysis;
+use interpreter::{Interpreter, Value};
+use num_bigint::BigInt;
+
+/// Runs the `main` function of a file with the arguments after it.
+///
+/// Arguments are integers or booleans if they look like one, and strings otherwise.
+fn main() -> ExitCode {
+    let mut args = env::args().skip(1);
+
+    let Some(path) = args.next() else {
+        eprintln!("usage: run <file> [args...]");
+        return ExitCode::FAILURE;
+    };
+
+    let source = match fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("could not read `{path}`: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let file = SourceFile::new(&path, &source);
+    let parse = parser::parse(&source);
+    let analysis = Analysis::new(&parse.tree());
+
+    let diagnostics = parse
+        .errors()
+        .iter()
+        .map(|error| error.to_diagnostic())
+        .chain(analysis.diagnostics())
+        .collect::<Vec<_>>();
+
+    for diagnostic in &diagnostics {
+        eprint!("{}", render(diagnostic, &file));
+    }
+
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        return ExitCode::FAILURE;
+    }
+
+    let args = args.map(|arg| parse_arg(&arg)).collect();
+
+    match Interpreter::new(&analysis).run_main(args) {
+        Ok(value) => {
+            println!("{value}");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            match error.to_diagnostic() {
+                Some(diagnostic) => eprint!("{}", render(&diagnostic, &file)),
+                None => eprintln!("error: {error}"),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_arg(arg: &str) -> Value {
+    match arg {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => match arg.parse::<BigInt>() {
+            Ok(value) => Value::Int(value),
+            Err(_) => Value::from(arg),
+        },
+    }
+}